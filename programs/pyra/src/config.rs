@@ -3,6 +3,3 @@ pub use constants::*;
 
 mod errors;
 pub use errors::*;
-
-mod drift_markets;
-pub use drift_markets::*;
@@ -6,27 +6,48 @@ pub const PUBKEY_SIZE: usize = 32;
 pub const U1_SIZE: usize = 1;
 pub const U8_SIZE: usize = 1;
 pub const U16_SIZE: usize = 2;
+pub const U32_SIZE: usize = 4;
 pub const U64_SIZE: usize = 8;
+pub const VEC_LENGTH_PREFIX_SIZE: usize = 4;
 pub const DEPOSIT_ADDRESS_SPACE: usize = 0;
 
 pub const INIT_ACCOUNT_RENT_FEE: u64 = 35_000_000; // 0.035 SOL
 
-pub const AUTO_REPAY_MAX_HEALTH_RESULT_PERCENT: u8 = 20;
-pub const AUTO_REPAY_MAX_SLIPPAGE_BPS: u16 = 100;
-pub const PYTH_MAX_PRICE_AGE_SECONDS: u64 = 60;
+// Sanity bounds for values set in the GlobalConfig
+pub const MAX_SPEND_FEE_BPS: u64 = 500;
+pub const MAX_REFERRAL_FEE_SHARE_BPS: u64 = 10_000;
+pub const HARD_MAX_SLIPPAGE_BPS: u16 = 500;
+pub const MAX_HEALTH_PERCENT: u8 = 100;
+pub const MIN_GOVERNANCE_DELAY_SLOTS: u64 = 216_000; // ~1 day
+
+pub const MAX_DRIFT_MARKETS: usize = 32;
+pub const MAX_SETTLEMENT_PROVIDERS: usize = 16;
+pub const DEFAULT_SETTLEMENT_PROVIDER_ID: u16 = 0;
+pub const MAX_VAULT_DELEGATES: usize = 8;
+pub const MAX_VAULT_GUARDIANS: usize = 8;
+pub const MAX_SPEND_LIMIT_WINDOWS: usize = 3;
+pub const MAX_RECENT_SPENDS: usize = 32;
+pub const MAX_UTC_OFFSET_SECONDS: u32 = 50_400; // UTC+14
+pub const MAX_SPEND_LIMIT_BOOST_DURATION_SECONDS: u64 = 604_800; // 7 days
+pub const MAX_SPEND_HOLD_DURATION_SECONDS: u64 = 2_592_000; // 30 days
+
+// Only timeframes allowed for calendar spend limits, a month resets on the 1st rather than every 30 days
+pub const SECONDS_PER_DAY: u64 = 86_400;
+pub const SECONDS_PER_WEEK: u64 = 604_800;
+pub const SECONDS_PER_LONGEST_CALENDAR_MONTH: u64 = 2_678_400; // 31 days
+pub const MAX_MERCHANT_CATEGORY_LIMITS: usize = 16;
+pub const MAX_MERCHANT_IDS: usize = 32;
+pub const MERCHANT_ID_HASH_SIZE: usize = 32;
+pub const MIN_RECOVERY_DELAY_SLOTS: u64 = 216_000; // ~1 day
+pub const MAX_DEPOSIT_SWEEP_AGE_SLOTS: u64 = 150; // ~1 minute
+
+pub const GLOBAL_CONFIG_RESERVED_SPACE: usize = 128;
+
+pub const VAULT_VERSION: u8 = 3;
+pub const VAULT_RESERVED_SPACE: usize = 238;
 
 pub const USDC_MARKET_INDEX: u16 = 0;
 pub const USDC_MINT: Pubkey = pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
 pub const WSOL_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
 
-pub const DOMAIN_BASE: u32 = 6;
-pub const PROVIDER_BASE_ADDRESS: &str = "0x55a2eeB9028ee51Ef91352Fa9f84A9450C5Af099";
-pub const PYRA_CALLER_BASE_ADDRESS: &str = "0x28A0105A0cf8C0485a4956ba14b5274e9ED229DE";
-pub const RENT_RECLAIMER: Pubkey = pubkey!("AhLjdeYqv4Ytw5sukK4z3x37ZGaSJ44pRqdcxqHP4ChS");
-pub const SPEND_CALLER: Pubkey = pubkey!("JDd7PJDZJ8kwwzJpvUZ5qp9kXYAr9YdAEEVUNE1pFqhP");
-pub const SPEND_FEE_DESTINATION: Pubkey = pubkey!("HPvsnVZQSeFr3TtD2JBjvvzxiZhnuk5MHfKRiswD4mYu");
-
-pub const SPEND_FEE_BPS: u64 = 50;
-
 pub const TIME_LOCK_RENT_PAYER_SEEDS: &[u8] = b"time_lock_rent_payer";
-pub const TIME_LOCK_DURATION_SLOTS: u64 = 450;
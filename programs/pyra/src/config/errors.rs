@@ -88,4 +88,178 @@ pub enum PyraError {
     IllegalRescueSupportedToken,
     #[msg("Cannot transfer zero tokens")]
     TransferZero,
+    #[msg("Signer is not the global config admin")]
+    InvalidConfigAdmin,
+    #[msg("Program data account does not match the program")]
+    InvalidProgramData,
+    #[msg("Signer is not the program upgrade authority")]
+    InvalidUpgradeAuthority,
+    #[msg("Auto repay max health result percent is above maximum")]
+    InvalidHealthResultPercent,
+    #[msg("Pyth max price age cannot be zero")]
+    InvalidPythMaxPriceAge,
+    #[msg("Time lock duration cannot be zero")]
+    InvalidTimeLockDuration,
+    #[msg("Drift market is already in the registry")]
+    DriftMarketAlreadyExists,
+    #[msg("Drift market registry is full")]
+    DriftMarketRegistryFull,
+    #[msg("Drift market is disabled for deposits")]
+    DriftMarketDisabled,
+    #[msg("Governance delay is below minimum")]
+    InvalidGovernanceDelay,
+    #[msg("Signer must be the guardian or admin")]
+    InvalidGuardian,
+    #[msg("Base units per token do not match the mint decimals")]
+    InvalidBaseUnits,
+    #[msg("Signer is not the pauser")]
+    InvalidPauser,
+    #[msg("Protocol feature is paused")]
+    ProtocolPaused,
+    #[msg("Vault version is not supported by this program")]
+    UnsupportedVaultVersion,
+    #[msg("Vault is already on the latest version")]
+    VaultAlreadyUpToDate,
+    #[msg("New owner must be different to the current owner")]
+    InvalidNewOwner,
+    #[msg("Owner transfer order does not match the vault or new owner")]
+    InvalidOwnerTransferOrder,
+    #[msg("Signer is not the vault owner or a delegate with the required permission")]
+    InvalidVaultAuthority,
+    #[msg("Vault delegate registry is full")]
+    VaultDelegatesFull,
+    #[msg("Vault delegate not found")]
+    VaultDelegateNotFound,
+    #[msg("Delegate must be different to the vault owner")]
+    InvalidDelegate,
+    #[msg("Spend limit is above the delegate's maximum")]
+    DelegateSpendLimitExceeded,
+    #[msg("Delegates can only withdraw to the vault owner")]
+    InvalidDelegateWithdrawDestination,
+    #[msg("Source and destination Drift sub-accounts must be different")]
+    IdenticalDriftSubAccounts,
+    #[msg("Transfers out of the spend sub-account must go through a time locked order")]
+    SpendSubAccountTransferTimeLocked,
+    #[msg("The spend sub-account cannot be closed")]
+    CannotCloseSpendSubAccount,
+    #[msg("Drift sub-accounts other than the spend sub-account must be closed first")]
+    DriftSubAccountsStillOpen,
+    #[msg("Collateral transfer order does not match the vault")]
+    InvalidCollateralTransferOrder,
+    #[msg("Spend sub-account order does not match the vault")]
+    InvalidSpendSubAccountOrder,
+    #[msg("Drift sub-account is already the spend sub-account")]
+    SpendSubAccountAlreadySet,
+    #[msg("Card is frozen")]
+    CardFrozen,
+    #[msg("Card is already frozen")]
+    CardAlreadyFrozen,
+    #[msg("Card is not frozen")]
+    CardNotFrozen,
+    #[msg("Unfreeze card order does not match the vault")]
+    InvalidUnfreezeCardOrder,
+    #[msg("Guardian set is empty, too large, or contains duplicates or the owner")]
+    InvalidGuardianSet,
+    #[msg("Guardian threshold must be between 1 and the number of guardians")]
+    InvalidGuardianThreshold,
+    #[msg("Recovery delay is below minimum")]
+    InvalidRecoveryDelay,
+    #[msg("Not enough guardians signed to reach the threshold")]
+    GuardianThresholdNotReached,
+    #[msg("Recovery order does not match the vault")]
+    InvalidRecoveryOrder,
+    #[msg("Withdraw order does not match the vault")]
+    InvalidWithdrawOrder,
+    #[msg("Spend limits order does not match the vault")]
+    InvalidSpendLimitsOrder,
+    #[msg("Time locked orders must be fulfilled or cancelled before the vault can be closed")]
+    OpenOrdersRemaining,
+    #[msg("Sweep token accounts must be held by the vault or its deposit address, and sent to an owner token account")]
+    InvalidSweepTokenAccount,
+    #[msg("Settle and close order does not match the vault")]
+    InvalidSettleAndCloseOrder,
+    #[msg("Referral fee share BPS is above maximum")]
+    InvalidReferralFeeShareBPS,
+    #[msg("Owner cannot refer their own vault")]
+    InvalidReferrer,
+    #[msg("Sponsor does not match the vault")]
+    InvalidSponsor,
+    #[msg("Referrer rewards accounts are required for referred vaults")]
+    MissingReferrerRewards,
+    #[msg("No referrer rewards to claim")]
+    NoReferrerRewards,
+    #[msg("Withdrawal would leave the rent payer unable to cover its obligations")]
+    InsufficientRentSurplus,
+    #[msg("Refund message did not mint USDC to the vault's refund mule")]
+    InvalidRefund,
+    #[msg("Settlement provider is not in the registry")]
+    InvalidSettlementProvider,
+    #[msg("Settlement provider is already in the registry")]
+    SettlementProviderAlreadyExists,
+    #[msg("Settlement provider registry is full")]
+    SettlementProviderRegistryFull,
+    #[msg("Settlement provider is disabled")]
+    SettlementProviderDisabled,
+    #[msg("start_collateral_spend must be followed by a swap, deposit_collateral_spend and start_spend")]
+    IllegalCollateralSpendInstructions,
+    #[msg("Collateral spend must sell a market other than USDC")]
+    InvalidCollateralSpendMarket,
+    #[msg("Swap did not return enough USDC to fund the spend")]
+    InsufficientCollateralSpendSwap,
+    #[msg("Merchant controls exceed the maximum size or contain duplicates")]
+    InvalidMerchantControls,
+    #[msg("Merchant controls can only be loosened through a time locked order")]
+    MerchantControlsNotTightening,
+    #[msg("Merchant controls order does not match the vault")]
+    InvalidMerchantControlsOrder,
+    #[msg("Merchant is blocked by the vault's merchant controls")]
+    MerchantBlocked,
+    #[msg("Insufficient merchant category spend limit")]
+    InsufficientMerchantCategorySpendLimit,
+    #[msg("Spend limit windows exceed the maximum, or have a zero or duplicate timeframe")]
+    InvalidSpendLimitWindows,
+    #[msg(
+        "Calendar spend limits need a calendar period with its timeframe for every timeframe, and a UTC offset within 14 hours"
+    )]
+    InvalidCalendarSpendLimits,
+    #[msg("Insufficient spend count limit")]
+    InsufficientSpendCountLimit,
+    #[msg("Minimum interval since the previous spend has not elapsed")]
+    SpendIntervalNotElapsed,
+    #[msg("Spend limit boost must last between 1 second and the maximum boost duration")]
+    InvalidSpendLimitBoost,
+    #[msg("Spend hold must last between 1 second and the maximum hold duration")]
+    InvalidSpendHold,
+    #[msg("Capture amount exceeds the remaining spend hold")]
+    InsufficientSpendHold,
+    #[msg("Spend hold has expired")]
+    SpendHoldExpired,
+    #[msg("Spend hold has not expired")]
+    SpendHoldNotExpired,
+    #[msg("Capture spend must be followed by start spend for the captured amount")]
+    IllegalCaptureSpendInstructions,
+    #[msg("Drift spot market is missing from the remaining accounts")]
+    InvalidSpotMarket,
+    #[msg("Order was created before the vault last changed owner")]
+    StaleOwnerEpoch,
+    #[msg("Loans must be repaid before the vault's collateral is withdrawn or it's closed")]
+    SpotBorrowsRemaining,
+    #[msg("Deposit address token account of a supported mint is missing from the sweep")]
+    MissingSweepTokenAccount,
+    #[msg(
+        "Owner must attest no orders from before the vault was upgraded remain before closing it"
+    )]
+    UncountedOrdersNotAttested,
+    #[msg("Delegates can't change when spend limits reset or loosen the velocity limits")]
+    IllegalDelegateSpendLimitChange,
+    #[msg("Vault must be upgraded to the current version")]
+    VaultUpgradeRequired,
+    #[msg("Spend record is invalid or missing")]
+    InvalidSpendRecord,
+    #[msg("Spend has already been fully refunded")]
+    SpendAlreadyRefunded,
+    #[msg("Spend record can still restore spend limits")]
+    SpendRecordStillRefundable,
+    #[msg("Spend holds must be captured or released before closing the vault")]
+    OpenSpendHoldsRemaining,
 }
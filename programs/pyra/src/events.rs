@@ -0,0 +1,667 @@
+use crate::config::{MAX_SPEND_LIMIT_WINDOWS, MERCHANT_ID_HASH_SIZE};
+use crate::state::{
+    AdminAction, CalendarPeriod, MerchantControls, ProtocolFeature, RentPayer, SpendLimitMode,
+    SpendLimitWindow, SpendLimitWindowParams, SpendVelocityLimits,
+};
+use anchor_lang::prelude::*;
+
+// Admin
+
+#[event]
+pub struct GlobalConfigInitialized {
+    pub admin: Pubkey,
+    pub guardian: Pubkey,
+    pub pauser: Pubkey,
+}
+
+#[event]
+pub struct DriftMarketRegistryInitialized {
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct SettlementProviderRegistryInitialized {
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct AdminActionProposed {
+    pub admin_proposal: Pubkey,
+    pub action: AdminAction,
+    pub release_slot: u64,
+}
+
+#[event]
+pub struct AdminActionExecuted {
+    pub admin_proposal: Pubkey,
+    pub action: AdminAction,
+}
+
+#[event]
+pub struct AdminActionCancelled {
+    pub admin_proposal: Pubkey,
+    pub canceller: Pubkey,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub feature: ProtocolFeature,
+    pub is_paused: bool,
+    pub paused_features: u8,
+}
+
+#[event]
+pub struct BridgeRentReclaimed {
+    pub message_sent_event_data: Pubkey,
+    pub lamports_reclaimed: u64,
+}
+
+#[event]
+pub struct RentTreasuryInitialized {
+    pub admin: Pubkey,
+    pub refundable_init_fees: u64,
+    pub live_bridge_rent: u64,
+}
+
+#[event]
+pub struct TimeLockRentPayerInitialized {
+    pub admin: Pubkey,
+    pub live_time_lock_rent: u64,
+}
+
+#[event]
+pub struct RentPayerFunded {
+    pub rent_payer: RentPayer,
+    pub funder: Pubkey,
+    pub amount_lamports: u64,
+    pub balance_lamports: u64,
+    pub obligations_lamports: u64,
+}
+
+#[event]
+pub struct RentSurplusWithdrawn {
+    pub rent_payer: RentPayer,
+    pub destination: Pubkey,
+    pub amount_lamports: u64,
+    pub balance_lamports: u64,
+    pub reserved_lamports: u64,
+}
+
+// User
+
+#[event]
+pub struct UserInitialized {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub deposit_address: Pubkey,
+    pub spend_limit_per_transaction: u64,
+    pub spend_limit_per_timeframe: u64,
+    pub timeframe_in_seconds: u64,
+    pub next_timeframe_reset_timestamp: u64,
+    pub settlement_provider_id: u16,
+    pub sponsor: Option<Pubkey>,
+    pub referrer: Option<Pubkey>,
+}
+
+#[event]
+pub struct UserClosed {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub init_rent_fee_refunded: u64,
+    // The sponsor if the init fee was sponsored, otherwise the owner
+    pub init_rent_fee_recipient: Pubkey,
+    // Only non-zero when the spend sub-account was created (and paid for) by the owner
+    pub drift_rent_reclaimed: u64,
+    pub idle_lamports_swept: u64,
+    pub token_accounts_swept: Vec<SweptTokenAccount>,
+    pub settle_and_close_order: Option<Pubkey>,
+    // Delegates, merchant controls and guardians of the vault that were closed with it
+    pub vault_accounts_closed: Vec<Pubkey>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SweptTokenAccount {
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub amount_base_units: u64,
+    // Token account rent, sent to the owner when closed
+    pub lamports_reclaimed: u64,
+}
+
+#[event]
+pub struct DepositAddressSwept {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub token_accounts_swept: Vec<SweptTokenAccount>,
+    // Positions in DriftMarketRegistry::get_deposit_mints swept since the deposit_sweep_slot, including this sweep
+    pub swept_deposit_mint_flags: u64,
+    pub deposit_sweep_slot: u64,
+}
+
+#[event]
+pub struct SettleAndCloseInitiated {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub settle_and_close_order: Pubkey,
+    pub release_slot: u64,
+}
+
+#[event]
+pub struct SettleAndCloseCancelled {
+    pub owner: Pubkey,
+    pub settle_and_close_order: Pubkey,
+}
+
+#[event]
+pub struct SettleWithdrawn {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub settle_and_close_order: Pubkey,
+    pub drift_market_index: u16,
+    pub mint: Pubkey,
+    pub amount_base_units: u64,
+}
+
+#[event]
+pub struct VaultUpgraded {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event]
+pub struct UncountedOrdersAttested {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct OwnerTransferInitiated {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub owner_transfer_order: Pubkey,
+    pub new_owner: Pubkey,
+    pub release_slot: u64,
+}
+
+#[event]
+pub struct OwnerTransferAccepted {
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct OwnerTransferCancelled {
+    pub owner: Pubkey,
+    pub owner_transfer_order: Pubkey,
+}
+
+#[event]
+pub struct VaultDelegateSet {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub permissions: u8,
+    pub max_spend_limit_per_transaction: u64,
+    pub max_spend_limit_per_timeframe: u64,
+}
+
+#[event]
+pub struct VaultDelegateRevoked {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+}
+
+#[event]
+pub struct VaultDelegatesClosed {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct VaultGuardiansSet {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8,
+    pub recovery_delay_slots: u64,
+}
+
+#[event]
+pub struct VaultGuardiansRemoved {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct RecoveryInitiated {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub recovery_order: Pubkey,
+    pub new_owner: Pubkey,
+    pub approving_guardians: Vec<Pubkey>,
+    pub release_slot: u64,
+}
+
+#[event]
+pub struct RecoveryVetoed {
+    pub owner: Pubkey,
+    pub recovery_order: Pubkey,
+}
+
+#[event]
+pub struct VaultRecovered {
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub vault: Pubkey,
+    pub recovery_order: Pubkey,
+}
+
+// Sub Accounts
+
+#[event]
+pub struct DriftSubAccountInitialized {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub sub_account_id: u16,
+    pub drift_sub_account_count: u16,
+}
+
+#[event]
+pub struct DriftSubAccountClosed {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub sub_account_id: u16,
+    pub drift_sub_account_count: u16,
+    pub lamports_reclaimed: u64,
+}
+
+#[event]
+pub struct CollateralTransferInitiated {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub collateral_transfer_order: Pubkey,
+    pub drift_market_index: u16,
+    pub amount_base_units: u64,
+    pub from_sub_account_id: u16,
+    pub to_sub_account_id: u16,
+    pub release_slot: u64,
+}
+
+#[event]
+pub struct CollateralTransferred {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub drift_market_index: u16,
+    pub amount_base_units: u64,
+    pub from_sub_account_id: u16,
+    pub to_sub_account_id: u16,
+    // Only set when the transfer was out of the spend sub-account through a time locked order
+    pub collateral_transfer_order: Option<Pubkey>,
+}
+
+#[event]
+pub struct CollateralTransferCancelled {
+    pub owner: Pubkey,
+    pub collateral_transfer_order: Pubkey,
+}
+
+#[event]
+pub struct SpendSubAccountInitiated {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub spend_sub_account_order: Pubkey,
+    pub spend_sub_account_id: u16,
+    pub release_slot: u64,
+}
+
+#[event]
+pub struct SpendSubAccountCancelled {
+    pub owner: Pubkey,
+    pub spend_sub_account_order: Pubkey,
+}
+
+#[event]
+pub struct SpendSubAccountUpdated {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub previous_spend_sub_account_id: u16,
+    pub spend_sub_account_id: u16,
+}
+
+// Balance
+
+#[event]
+pub struct DepositFulfilled {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub caller: Pubkey,
+    pub drift_market_index: u16,
+    pub drift_sub_account_id: u16,
+    pub mint: Pubkey,
+    pub amount_base_units: u64,
+}
+
+#[event]
+pub struct DepositRescued {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub amount_base_units: u64,
+}
+
+#[event]
+pub struct WithdrawInitiated {
+    pub owner: Pubkey,
+    pub authority: Pubkey,
+    pub vault: Pubkey,
+    pub withdraw_order: Pubkey,
+    pub amount_base_units: u64,
+    pub drift_market_index: u16,
+    pub drift_sub_account_id: u16,
+    pub reduce_only: bool,
+    pub destination: Pubkey,
+    pub release_slot: u64,
+}
+
+#[event]
+pub struct WithdrawFulfilled {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub withdraw_order: Pubkey,
+    pub drift_market_index: u16,
+    pub drift_sub_account_id: u16,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount_base_units: u64,
+    pub idle_funds_used: u64,
+    pub amount_sent_base_units: u64,
+}
+
+#[event]
+pub struct WithdrawCancelled {
+    pub owner: Pubkey,
+    pub withdraw_order: Pubkey,
+}
+
+// Spend
+
+#[event]
+pub struct SpendStarted {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub amount_usdc_base_units: u64,
+    pub fee_usdc_base_units: u64,
+    // Part of the fee sent to the vault's referrer rather than the spend fee destination
+    pub referrer_fee_usdc_base_units: u64,
+    pub drift_sub_account_id: u16,
+    pub idle_funds_used: u64,
+    pub drift_withdraw_usdc_base_units: u64,
+    // Part of the Drift withdraw that was borrowed, as it exceeded the sub-account's USDC collateral
+    pub borrowed_usdc_base_units: u64,
+    pub remaining_spend_limit_per_timeframe: u64,
+    pub merchant_category_code: u16,
+    pub merchant_id_hash: [u8; MERCHANT_ID_HASH_SIZE],
+}
+
+#[event]
+pub struct SpendCompleted {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub amount_usdc_base_units: u64,
+    pub settlement_provider_id: u16,
+    pub destination_domain: u32,
+    pub mint_recipient: Pubkey,
+    pub cctp_nonce: u64,
+}
+
+#[event]
+pub struct CollateralSpendStarted {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub caller: Pubkey,
+    pub collateral_market_index: u16,
+    pub collateral_amount_base_units: u64,
+    pub starting_usdc_balance: u64,
+}
+
+#[event]
+pub struct CollateralSpendDeposited {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub caller: Pubkey,
+    pub collateral_market_index: u16,
+    pub collateral_amount_base_units: u64,
+    pub amount_usdc_base_units: u64,
+    pub collateral_price: i64,
+    pub collateral_price_exponent: i32,
+    pub usdc_price: i64,
+    pub usdc_price_exponent: i32,
+}
+
+#[event]
+pub struct RefundReceived {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    // CCTP nonce of the SpendCompleted being refunded
+    pub spend_cctp_nonce: u64,
+    pub amount_usdc_base_units: u64,
+    pub drift_deposit_usdc_base_units: u64,
+    pub spend_limit_restored: u64,
+    pub remaining_spend_limit_per_timeframe: u64,
+}
+
+#[event]
+pub struct SettlementProviderSet {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub previous_settlement_provider_id: u16,
+    pub settlement_provider_id: u16,
+}
+
+#[event]
+pub struct CardFrozen {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct UnfreezeCardInitiated {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub unfreeze_card_order: Pubkey,
+    pub release_slot: u64,
+}
+
+#[event]
+pub struct CardUnfrozen {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub unfreeze_card_order: Pubkey,
+}
+
+#[event]
+pub struct UnfreezeCardCancelled {
+    pub owner: Pubkey,
+    pub unfreeze_card_order: Pubkey,
+}
+
+#[event]
+pub struct SpendLimitsInitiated {
+    pub owner: Pubkey,
+    pub authority: Pubkey,
+    pub vault: Pubkey,
+    pub spend_limits_order: Pubkey,
+    pub spend_limit_per_transaction: u64,
+    pub spend_limit_per_timeframe: u64,
+    pub timeframe_in_seconds: u64,
+    pub next_timeframe_reset_timestamp: u64,
+    pub spend_limit_windows: Vec<SpendLimitWindowParams>,
+    pub spend_limit_mode: SpendLimitMode,
+    pub utc_offset_seconds: i32,
+    pub spend_velocity_limits: SpendVelocityLimits,
+    pub calendar_period: CalendarPeriod,
+    pub release_slot: u64,
+}
+
+#[event]
+pub struct SpendLimitsUpdated {
+    pub owner: Pubkey,
+    // Owner or delegate that increased the limits, or the caller that fulfilled the order
+    pub authority: Pubkey,
+    pub vault: Pubkey,
+    pub spend_limit_per_transaction: u64,
+    pub spend_limit_per_timeframe: u64,
+    pub remaining_spend_limit_per_timeframe: u64,
+    pub timeframe_in_seconds: u64,
+    pub next_timeframe_reset_timestamp: u64,
+    pub spend_limit_windows: [SpendLimitWindow; MAX_SPEND_LIMIT_WINDOWS],
+    pub spend_limit_mode: SpendLimitMode,
+    pub utc_offset_seconds: i32,
+    pub spend_velocity_limits: SpendVelocityLimits,
+    pub calendar_period: CalendarPeriod,
+    pub spend_limit_window_calendar_periods: [CalendarPeriod; MAX_SPEND_LIMIT_WINDOWS],
+    // False when increased instantly, true when set by a fulfilled spend limits order
+    pub is_time_locked: bool,
+}
+
+#[event]
+pub struct SpendLimitsBoosted {
+    pub owner: Pubkey,
+    // Owner or delegate that boosted the limits
+    pub authority: Pubkey,
+    pub vault: Pubkey,
+    pub additional_spend_limit_per_transaction: u64,
+    pub additional_spend_limit: u64,
+    // Less than the additional spend limit when the boost replaced an active boost that had been drawn from
+    pub remaining_additional_spend_limit: u64,
+    pub expiry_timestamp: u64,
+}
+
+#[event]
+pub struct SpendAuthorized {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub spend_hold: Pubkey,
+    pub authorization_id: u64,
+    pub amount_usdc_base_units: u64,
+    pub expiry_timestamp: u64,
+    pub remaining_spend_limit_per_timeframe: u64,
+    pub merchant_category_code: u16,
+    pub merchant_id_hash: [u8; MERCHANT_ID_HASH_SIZE],
+}
+
+#[event]
+pub struct SpendCaptured {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub spend_hold: Pubkey,
+    pub authorization_id: u64,
+    pub amount_usdc_base_units: u64,
+    pub remaining_hold_usdc_base_units: u64,
+    // True when the capture was final or used the whole hold, which closes it
+    pub is_hold_closed: bool,
+    // Uncaptured amount released when the hold was closed
+    pub released_usdc_base_units: u64,
+    pub spend_limit_restored: u64,
+}
+
+#[event]
+pub struct SpendHoldReleased {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub spend_hold: Pubkey,
+    // Anyone once the hold has expired, or the spend caller when the authorization is reversed
+    pub caller: Pubkey,
+    pub authorization_id: u64,
+    pub released_usdc_base_units: u64,
+    pub spend_limit_restored: u64,
+}
+
+#[event]
+pub struct SpendRecordClosed {
+    pub vault: Pubkey,
+    pub spend_record: Pubkey,
+    pub caller: Pubkey,
+    pub cctp_nonce: u64,
+    pub unrefunded_usdc_base_units: u64,
+}
+
+#[event]
+pub struct MerchantControlsInitiated {
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    pub merchant_controls_order: Pubkey,
+    pub merchant_controls: MerchantControls,
+    pub release_slot: u64,
+}
+
+#[event]
+pub struct MerchantControlsUpdated {
+    pub owner: Pubkey,
+    // Owner that tightened the controls, or the caller that fulfilled the order
+    pub authority: Pubkey,
+    pub vault: Pubkey,
+    pub merchant_controls: MerchantControls,
+    // False when tightened instantly, true when set by a fulfilled merchant controls order
+    pub is_time_locked: bool,
+}
+
+// Collateral Repay
+
+#[event]
+pub struct CollateralRepayStarted {
+    pub owner: Pubkey,
+    pub caller: Pubkey,
+    pub mint_deposit: Pubkey,
+    pub mint_withdraw: Pubkey,
+    pub starting_deposit_balance: u64,
+    pub starting_withdraw_balance: u64,
+}
+
+#[event]
+pub struct CollateralRepayDeposited {
+    pub owner: Pubkey,
+    pub caller: Pubkey,
+    pub drift_sub_account_id: u16,
+    pub deposit_market_index: u16,
+    pub amount_deposited: u64,
+    pub amount_returned: u64,
+    pub is_auto_repay: bool,
+    pub is_settle_repay: bool,
+}
+
+#[event]
+pub struct CollateralRepayWithdrawn {
+    pub owner: Pubkey,
+    pub caller: Pubkey,
+    pub drift_sub_account_id: u16,
+    pub deposit_market_index: u16,
+    pub withdraw_market_index: u16,
+    pub amount_deposited: u64,
+    pub amount_withdrawn: u64,
+    pub deposit_price: i64,
+    pub deposit_price_exponent: i32,
+    pub withdraw_price: i64,
+    pub withdraw_price_exponent: i32,
+    // Only calculated for auto repays (when neither the owner nor a collateral repay delegate has signed)
+    pub resulting_health: Option<u8>,
+}
+
+// Referral
+
+#[event]
+pub struct ReferrerRegistered {
+    pub referrer: Pubkey,
+    pub referrer_rewards: Pubkey,
+}
+
+#[event]
+pub struct ReferrerRewardsClaimed {
+    pub referrer: Pubkey,
+    pub destination_usdc: Pubkey,
+    pub amount_usdc_base_units: u64,
+}
@@ -12,3 +12,9 @@ pub use admin::*;
 
 mod spend;
 pub use spend::*;
+
+mod sub_account;
+pub use sub_account::*;
+
+mod referral;
+pub use referral::*;
@@ -1,2 +1,38 @@
 mod reclaim_bridge_rent;
-pub use reclaim_bridge_rent::*;
\ No newline at end of file
+pub use reclaim_bridge_rent::*;
+
+mod init_rent_treasury;
+pub use init_rent_treasury::*;
+
+mod init_time_lock_rent_payer;
+pub use init_time_lock_rent_payer::*;
+
+mod fund_rent_payer;
+pub use fund_rent_payer::*;
+
+mod withdraw_rent_surplus;
+pub use withdraw_rent_surplus::*;
+
+mod init_global_config;
+pub use init_global_config::*;
+
+mod init_drift_market_registry;
+pub use init_drift_market_registry::*;
+
+mod init_settlement_provider_registry;
+pub use init_settlement_provider_registry::*;
+
+mod propose_admin_action;
+pub use propose_admin_action::*;
+
+mod execute_admin_action;
+pub use execute_admin_action::*;
+
+mod cancel_admin_action;
+pub use cancel_admin_action::*;
+
+mod pause;
+pub use pause::*;
+
+mod unpause;
+pub use unpause::*;
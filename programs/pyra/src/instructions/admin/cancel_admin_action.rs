@@ -0,0 +1,45 @@
+use crate::{
+    config::PyraError,
+    events::AdminActionCancelled,
+    state::{AdminProposal, GlobalConfig},
+    utils::close_time_lock,
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelAdminAction<'info> {
+    #[account(mut)]
+    pub admin_proposal: Box<Account<'info, AdminProposal>>,
+
+    /// CHECK: Receives the proposal rent, checked against the time lock owner when closing
+    #[account(mut)]
+    pub admin: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = canceller.key().eq(&global_config.guardian)
+            || canceller.key().eq(&global_config.admin) @ PyraError::InvalidGuardian
+    )]
+    pub canceller: Signer<'info>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Cancels a pending admin proposal. Can be called by the guardian (or the admin withdrawing their own proposal).
+pub fn cancel_admin_action_handler(ctx: Context<CancelAdminAction>) -> Result<()> {
+    close_time_lock(
+        &ctx.accounts.admin_proposal,
+        &ctx.accounts.admin.to_account_info(),
+    )?;
+
+    emit_cpi!(AdminActionCancelled {
+        admin_proposal: ctx.accounts.admin_proposal.key(),
+        canceller: ctx.accounts.canceller.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use crate::{
+    events::AdminActionExecuted,
+    state::{
+        AdminAction, AdminProposal, DriftMarket, DriftMarketRegistry, GlobalConfig,
+        SettlementProvider, SettlementProviderRegistry,
+    },
+    utils::{close_time_lock, validate_time_lock},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteAdminAction<'info> {
+    #[account(mut)]
+    pub admin_proposal: Box<Account<'info, AdminProposal>>,
+
+    /// CHECK: Receives the proposal rent, checked against the time lock owner when closing
+    #[account(mut)]
+    pub admin: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"drift_market_registry"],
+        bump = drift_market_registry.bump
+    )]
+    pub drift_market_registry: Box<Account<'info, DriftMarketRegistry>>,
+
+    #[account(
+        mut,
+        seeds = [b"settlement_provider_registry"],
+        bump = settlement_provider_registry.bump
+    )]
+    pub settlement_provider_registry: Box<Account<'info, SettlementProviderRegistry>>,
+}
+
+/// Permissionless function to execute an admin proposal once its governance delay has passed
+pub fn execute_admin_action_handler(ctx: Context<ExecuteAdminAction>) -> Result<()> {
+    // Proposals are invalidated if the admin changes before they're executed
+    validate_time_lock(
+        &ctx.accounts.global_config.admin,
+        &ctx.accounts.admin_proposal.time_lock,
+    )?;
+
+    let action = ctx.accounts.admin_proposal.action.clone();
+
+    close_time_lock(
+        &ctx.accounts.admin_proposal,
+        &ctx.accounts.admin.to_account_info(),
+    )?;
+
+    let global_config = &mut ctx.accounts.global_config;
+    let registry = &mut ctx.accounts.drift_market_registry;
+    let settlement_registry = &mut ctx.accounts.settlement_provider_registry;
+
+    match action.clone() {
+        AdminAction::UpdateGlobalConfig(params) => global_config.set_params(params)?,
+        AdminAction::SetRoles {
+            admin,
+            guardian,
+            pauser,
+        } => {
+            global_config.admin = admin;
+            global_config.guardian = guardian;
+            global_config.pauser = pauser;
+        }
+        AdminAction::AddDriftMarket {
+            market_index,
+            mint,
+            pyth_feed,
+            base_units_per_token,
+        } => registry.add_market(DriftMarket {
+            market_index,
+            mint,
+            pyth_feed,
+            base_units_per_token,
+            is_disabled: false,
+        })?,
+        AdminAction::UpdateDriftMarket {
+            market_index,
+            pyth_feed,
+        } => registry.update_market(market_index, pyth_feed)?,
+        AdminAction::SetDriftMarketDisabled {
+            market_index,
+            is_disabled,
+        } => registry.set_market_disabled(market_index, is_disabled)?,
+        AdminAction::DelistDriftMarket { market_index } => registry.delist_market(market_index)?,
+        AdminAction::AddSettlementProvider {
+            provider_id,
+            destination_domain,
+            mint_recipient,
+            destination_caller,
+        } => settlement_registry.add_provider(SettlementProvider {
+            provider_id,
+            destination_domain,
+            mint_recipient,
+            destination_caller,
+            is_disabled: false,
+        })?,
+        AdminAction::UpdateSettlementProvider {
+            provider_id,
+            destination_domain,
+            mint_recipient,
+            destination_caller,
+        } => settlement_registry.update_provider(
+            provider_id,
+            destination_domain,
+            mint_recipient,
+            destination_caller,
+        )?,
+        AdminAction::SetSettlementProviderDisabled {
+            provider_id,
+            is_disabled,
+        } => settlement_registry.set_provider_disabled(provider_id, is_disabled)?,
+    }
+
+    emit_cpi!(AdminActionExecuted {
+        admin_proposal: ctx.accounts.admin_proposal.key(),
+        action,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use crate::{
+    check,
+    config::PyraError,
+    events::RentPayerFunded,
+    state::{RentPayer, RentTreasury},
+};
+use anchor_lang::{
+    prelude::*,
+    system_program::{self, Transfer},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(rent_payer: RentPayer)]
+pub struct FundRentPayer<'info> {
+    /// CHECK: Safe once address is correct
+    #[account(
+        mut,
+        seeds = [rent_payer.seeds()],
+        bump
+    )]
+    pub rent_payer_account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    #[account(
+        seeds = [b"rent_treasury"],
+        bump = rent_treasury.bump
+    )]
+    pub rent_treasury: Box<Account<'info, RentTreasury>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Tops up one of the rent payers, anyone can fund them
+pub fn fund_rent_payer_handler(
+    ctx: Context<FundRentPayer>,
+    rent_payer: RentPayer,
+    amount_lamports: u64,
+) -> Result<()> {
+    check!(amount_lamports > 0, PyraError::TransferZero);
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.funder.to_account_info(),
+                to: ctx.accounts.rent_payer_account.to_account_info(),
+            },
+        ),
+        amount_lamports,
+    )?;
+
+    let obligations_lamports = ctx
+        .accounts
+        .rent_treasury
+        .obligations(rent_payer, &ctx.accounts.rent_payer_account)?;
+
+    emit_cpi!(RentPayerFunded {
+        rent_payer,
+        funder: ctx.accounts.funder.key(),
+        amount_lamports,
+        balance_lamports: ctx.accounts.rent_payer_account.lamports(),
+        obligations_lamports,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use crate::{
+    config::PyraError,
+    events::DriftMarketRegistryInitialized,
+    state::{DriftMarketRegistry, GlobalConfig},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitDriftMarketRegistry<'info> {
+    #[account(
+        init,
+        seeds = [b"drift_market_registry"],
+        bump,
+        payer = admin,
+        space = DriftMarketRegistry::INIT_SPACE
+    )]
+    pub drift_market_registry: Box<Account<'info, DriftMarketRegistry>>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = admin @ PyraError::InvalidConfigAdmin
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates the empty Drift market registry, markets are then listed through admin proposals
+pub fn init_drift_market_registry_handler(ctx: Context<InitDriftMarketRegistry>) -> Result<()> {
+    let registry = &mut ctx.accounts.drift_market_registry;
+    registry.bump = ctx.bumps.drift_market_registry;
+    registry.markets = Vec::new();
+
+    emit_cpi!(DriftMarketRegistryInitialized {
+        admin: ctx.accounts.admin.key(),
+    });
+
+    Ok(())
+}
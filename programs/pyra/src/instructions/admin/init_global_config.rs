@@ -0,0 +1,59 @@
+use crate::{
+    config::PyraError,
+    events::GlobalConfigInitialized,
+    program::Pyra,
+    state::{GlobalConfig, GlobalConfigParams},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitGlobalConfig<'info> {
+    #[account(
+        init,
+        seeds = [b"global_config"],
+        bump,
+        payer = admin,
+        space = GlobalConfig::INIT_SPACE
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        constraint = pyra_program.programdata_address()? == Some(program_data.key()) @ PyraError::InvalidProgramData
+    )]
+    pub pyra_program: Program<'info, Pyra>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ PyraError::InvalidUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates the global config. Can only be called once, by the program's upgrade authority, who becomes the config admin.
+pub fn init_global_config_handler(
+    ctx: Context<InitGlobalConfig>,
+    guardian: Pubkey,
+    pauser: Pubkey,
+    params: GlobalConfigParams,
+) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+    global_config.admin = ctx.accounts.admin.key();
+    global_config.guardian = guardian;
+    global_config.pauser = pauser;
+    global_config.paused_features = 0;
+    global_config.bump = ctx.bumps.global_config;
+    global_config.set_params(params)?;
+
+    emit_cpi!(GlobalConfigInitialized {
+        admin: ctx.accounts.admin.key(),
+        guardian,
+        pauser,
+    });
+
+    Ok(())
+}
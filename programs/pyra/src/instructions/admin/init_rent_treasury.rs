@@ -0,0 +1,51 @@
+use crate::{
+    config::PyraError,
+    events::RentTreasuryInitialized,
+    state::{GlobalConfig, RentTreasury},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitRentTreasury<'info> {
+    #[account(
+        init,
+        seeds = [b"rent_treasury"],
+        bump,
+        payer = admin,
+        space = RentTreasury::INIT_SPACE
+    )]
+    pub rent_treasury: Box<Account<'info, RentTreasury>>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = admin @ PyraError::InvalidConfigAdmin
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates the rent treasury, seeded with the obligations of accounts opened before it existed
+pub fn init_rent_treasury_handler(
+    ctx: Context<InitRentTreasury>,
+    refundable_init_fees: u64,
+    live_bridge_rent: u64,
+) -> Result<()> {
+    let rent_treasury = &mut ctx.accounts.rent_treasury;
+    rent_treasury.bump = ctx.bumps.rent_treasury;
+    rent_treasury.refundable_init_fees = refundable_init_fees;
+    rent_treasury.live_bridge_rent = live_bridge_rent;
+
+    emit_cpi!(RentTreasuryInitialized {
+        admin: ctx.accounts.admin.key(),
+        refundable_init_fees,
+        live_bridge_rent,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use crate::{
+    config::PyraError,
+    events::SettlementProviderRegistryInitialized,
+    state::{GlobalConfig, SettlementProviderRegistry},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitSettlementProviderRegistry<'info> {
+    #[account(
+        init,
+        seeds = [b"settlement_provider_registry"],
+        bump,
+        payer = admin,
+        space = SettlementProviderRegistry::INIT_SPACE
+    )]
+    pub settlement_provider_registry: Box<Account<'info, SettlementProviderRegistry>>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = admin @ PyraError::InvalidConfigAdmin
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates the empty settlement provider registry, providers are then added through admin proposals
+pub fn init_settlement_provider_registry_handler(
+    ctx: Context<InitSettlementProviderRegistry>,
+) -> Result<()> {
+    let registry = &mut ctx.accounts.settlement_provider_registry;
+    registry.bump = ctx.bumps.settlement_provider_registry;
+    registry.providers = Vec::new();
+
+    emit_cpi!(SettlementProviderRegistryInitialized {
+        admin: ctx.accounts.admin.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use crate::{
+    check,
+    config::{PyraError, TIME_LOCK_RENT_PAYER_SEEDS},
+    events::TimeLockRentPayerInitialized,
+    state::{GlobalConfig, TimeLockRentPayer},
+};
+use anchor_lang::{
+    prelude::*,
+    system_program::{self, Allocate, Assign, Transfer},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitTimeLockRentPayer<'info> {
+    /// CHECK: Safe once address is correct, it's checked to still be a system account below
+    #[account(
+        mut,
+        seeds = [TIME_LOCK_RENT_PAYER_SEEDS],
+        bump
+    )]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = admin @ PyraError::InvalidConfigAdmin
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Gives the time lock rent payer data to track its rent, seeded with the rent of time locks opened before it did.
+/// It keeps its SOL, and is owned by the program from then on.
+pub fn init_time_lock_rent_payer_handler(
+    ctx: Context<InitTimeLockRentPayer>,
+    live_time_lock_rent: u64,
+) -> Result<()> {
+    let time_lock_rent_payer = ctx.accounts.time_lock_rent_payer.to_account_info();
+    check!(
+        time_lock_rent_payer.owner.eq(&system_program::ID),
+        PyraError::AccountAlreadyInitialized
+    );
+    check!(
+        time_lock_rent_payer.data_is_empty(),
+        PyraError::AccountAlreadyInitialized
+    );
+
+    // Top up the rent for the data, so none of the SOL for time locks is locked up
+    let required_lamports = Rent::get()?
+        .minimum_balance(TimeLockRentPayer::INIT_SPACE)
+        .saturating_sub(time_lock_rent_payer.lamports());
+    if required_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.admin.to_account_info(),
+                    to: time_lock_rent_payer.clone(),
+                },
+            ),
+            required_lamports,
+        )?;
+    }
+
+    let bump = ctx.bumps.time_lock_rent_payer;
+    let time_lock_rent_payer_seeds = &[TIME_LOCK_RENT_PAYER_SEEDS, &[bump]];
+    let signer_seeds = &[&time_lock_rent_payer_seeds[..]];
+
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            Allocate {
+                account_to_allocate: time_lock_rent_payer.clone(),
+            },
+            signer_seeds,
+        ),
+        u64::try_from(TimeLockRentPayer::INIT_SPACE).map_err(|_| PyraError::MathOverflow)?,
+    )?;
+
+    system_program::assign(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            Assign {
+                account_to_assign: time_lock_rent_payer.clone(),
+            },
+            signer_seeds,
+        ),
+        &crate::ID,
+    )?;
+
+    let time_lock_rent_payer_data = TimeLockRentPayer {
+        bump,
+        live_time_lock_rent,
+    };
+    time_lock_rent_payer_data
+        .try_serialize(&mut &mut time_lock_rent_payer.try_borrow_mut_data()?[..])?;
+
+    emit_cpi!(TimeLockRentPayerInitialized {
+        admin: ctx.accounts.admin.key(),
+        live_time_lock_rent,
+    });
+
+    Ok(())
+}
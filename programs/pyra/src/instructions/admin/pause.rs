@@ -0,0 +1,33 @@
+use crate::{
+    config::PyraError,
+    events::PauseUpdated,
+    state::{GlobalConfig, ProtocolFeature},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    #[account(
+        mut,
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = pauser @ PyraError::InvalidPauser
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    pub pauser: Signer<'info>,
+}
+
+/// Instantly pauses a protocol feature (or the whole protocol with ProtocolFeature::All) during an incident
+pub fn pause_handler(ctx: Context<Pause>, feature: ProtocolFeature) -> Result<()> {
+    ctx.accounts.global_config.set_paused(feature, true);
+
+    emit_cpi!(PauseUpdated {
+        feature,
+        is_paused: true,
+        paused_features: ctx.accounts.global_config.paused_features,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use crate::{
+    check,
+    config::{PyraError, ANCHOR_DISCRIMINATOR},
+    events::AdminActionProposed,
+    state::{AdminAction, AdminProposal, GlobalConfig, TimeLock},
+    utils::allocate_time_lock_owner_payer,
+};
+use anchor_lang::{prelude::*, Discriminator};
+use anchor_spl::token_interface::Mint;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeAdminAction<'info> {
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = admin @ PyraError::InvalidConfigAdmin
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub admin_proposal: Signer<'info>,
+
+    /// Only required when adding a Drift market, to validate the base units against the mint decimals
+    pub mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates a time locked admin proposal, which can be executed permissionlessly once the governance delay has passed, or cancelled by the guardian before then
+pub fn propose_admin_action_handler(
+    ctx: Context<ProposeAdminAction>,
+    action: AdminAction,
+) -> Result<()> {
+    validate_action(&ctx, &action)?;
+
+    allocate_time_lock_owner_payer(
+        &ctx.accounts.admin,
+        &ctx.accounts.admin_proposal,
+        &ctx.accounts.system_program,
+        AdminProposal::INIT_SPACE,
+    )?;
+
+    let current_slot = Clock::get()?.slot;
+    let release_slot = current_slot
+        .checked_add(ctx.accounts.global_config.governance_delay_slots)
+        .ok_or(PyraError::MathOverflow)?;
+
+    let admin_proposal_data = AdminProposal {
+        time_lock: TimeLock {
+            owner: ctx.accounts.admin.key(),
+            is_owner_payer: true,
+            release_slot,
+            owner_epoch: 0,
+        },
+        action: action.clone(),
+    };
+    let admin_proposal_data_vec = admin_proposal_data.try_to_vec()?;
+
+    // Actions are variable length, so the data may be shorter than the account space
+    let data_end = ANCHOR_DISCRIMINATOR
+        .checked_add(admin_proposal_data_vec.len())
+        .ok_or(PyraError::MathOverflow)?;
+    let mut data = ctx.accounts.admin_proposal.try_borrow_mut_data()?;
+    data[..ANCHOR_DISCRIMINATOR].copy_from_slice(&AdminProposal::DISCRIMINATOR);
+    data[ANCHOR_DISCRIMINATOR..data_end].copy_from_slice(&admin_proposal_data_vec[..]);
+    drop(data);
+
+    emit_cpi!(AdminActionProposed {
+        admin_proposal: ctx.accounts.admin_proposal.key(),
+        action,
+        release_slot,
+    });
+
+    Ok(())
+}
+
+/// Fails early on actions that could never be executed. State-dependent checks (eg: market already exists) are done on execution.
+fn validate_action(ctx: &Context<ProposeAdminAction>, action: &AdminAction) -> Result<()> {
+    match action {
+        AdminAction::UpdateGlobalConfig(params) => params.validate()?,
+        AdminAction::AddDriftMarket {
+            mint,
+            base_units_per_token,
+            ..
+        } => {
+            let mint_account = match ctx.accounts.mint.as_ref() {
+                Some(mint_account) => mint_account,
+                None => return Err(PyraError::InvalidMint.into()),
+            };
+            check!(mint_account.key().eq(mint), PyraError::InvalidMint);
+
+            let expected_base_units = 10_u64
+                .checked_pow(mint_account.decimals.into())
+                .ok_or(PyraError::MathOverflow)?;
+            check!(
+                expected_base_units == *base_units_per_token,
+                PyraError::InvalidBaseUnits
+            );
+        }
+        AdminAction::SetRoles { .. }
+        | AdminAction::UpdateDriftMarket { .. }
+        | AdminAction::SetDriftMarketDisabled { .. }
+        | AdminAction::DelistDriftMarket { .. }
+        | AdminAction::AddSettlementProvider { .. }
+        | AdminAction::UpdateSettlementProvider { .. }
+        | AdminAction::SetSettlementProviderDisabled { .. } => {}
+    }
+
+    Ok(())
+}
@@ -1,4 +1,8 @@
-use crate::config::{PyraError, RENT_RECLAIMER};
+use crate::{
+    config::PyraError,
+    events::BridgeRentReclaimed,
+    state::{GlobalConfig, RentPayer, RentTreasury},
+};
 use anchor_lang::prelude::*;
 use message_transmitter::{
     cpi::{accounts::ReclaimEventAccountContext, reclaim_event_account},
@@ -6,10 +10,11 @@ use message_transmitter::{
     program::MessageTransmitter,
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ReclaimBridgeRent<'info> {
     #[account(
-        constraint = rent_reclaimer.key().eq(&RENT_RECLAIMER) @ PyraError::InvalidRentReclaimer
+        constraint = rent_reclaimer.key().eq(&global_config.rent_reclaimer) @ PyraError::InvalidRentReclaimer
     )]
     pub rent_reclaimer: Signer<'info>,
 
@@ -30,6 +35,19 @@ pub struct ReclaimBridgeRent<'info> {
     pub message_sent_event_data: UncheckedAccount<'info>,
 
     pub cctp_message_transmitter: Program<'info, MessageTransmitter>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"rent_treasury"],
+        bump = rent_treasury.bump
+    )]
+    pub rent_treasury: Box<Account<'info, RentTreasury>>,
 }
 
 /// Reclaims account rent once the bridge for spend is fully processed
@@ -53,7 +71,22 @@ pub fn reclaim_bridge_rent_handler(
 
     let reclaim_cpi_params = ReclaimEventAccountParams { attestation };
 
+    let lamports_before_reclaim = ctx.accounts.bridge_rent_payer.lamports();
     reclaim_event_account(reclaim_cpi_ctx, reclaim_cpi_params)?;
+    let lamports_reclaimed = ctx
+        .accounts
+        .bridge_rent_payer
+        .lamports()
+        .saturating_sub(lamports_before_reclaim);
+
+    ctx.accounts
+        .rent_treasury
+        .release_obligations(RentPayer::Bridge, lamports_reclaimed);
+
+    emit_cpi!(BridgeRentReclaimed {
+        message_sent_event_data: ctx.accounts.message_sent_event_data.key(),
+        lamports_reclaimed,
+    });
 
     Ok(())
 }
@@ -0,0 +1,33 @@
+use crate::{
+    config::PyraError,
+    events::PauseUpdated,
+    state::{GlobalConfig, ProtocolFeature},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Unpause<'info> {
+    #[account(
+        mut,
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = pauser @ PyraError::InvalidPauser
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    pub pauser: Signer<'info>,
+}
+
+/// Unpauses a protocol feature. Features paused individually stay paused when unpausing ProtocolFeature::All.
+pub fn unpause_handler(ctx: Context<Unpause>, feature: ProtocolFeature) -> Result<()> {
+    ctx.accounts.global_config.set_paused(feature, false);
+
+    emit_cpi!(PauseUpdated {
+        feature,
+        is_paused: false,
+        paused_features: ctx.accounts.global_config.paused_features,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use crate::{
+    check,
+    config::PyraError,
+    events::RentSurplusWithdrawn,
+    state::{GlobalConfig, RentPayer, RentTreasury},
+};
+use anchor_lang::{
+    prelude::*,
+    system_program::{self, Transfer},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(rent_payer: RentPayer)]
+pub struct WithdrawRentSurplus<'info> {
+    /// CHECK: Safe once address is correct
+    #[account(
+        mut,
+        seeds = [rent_payer.seeds()],
+        bump
+    )]
+    pub rent_payer_account: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"rent_treasury"],
+        bump = rent_treasury.bump
+    )]
+    pub rent_treasury: Box<Account<'info, RentTreasury>>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump,
+        has_one = admin @ PyraError::InvalidConfigAdmin
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    pub admin: Signer<'info>,
+
+    /// CHECK: Can be any account chosen by the admin
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Withdraws SOL from a rent payer, leaving enough to cover its outstanding obligations and stay rent exempt
+pub fn withdraw_rent_surplus_handler(
+    ctx: Context<WithdrawRentSurplus>,
+    rent_payer: RentPayer,
+    amount_lamports: u64,
+) -> Result<()> {
+    check!(amount_lamports > 0, PyraError::TransferZero);
+
+    let reserved_lamports = ctx
+        .accounts
+        .rent_treasury
+        .reserved_lamports(rent_payer)
+        .checked_add(Rent::get()?.minimum_balance(rent_payer.space()))
+        .ok_or(PyraError::MathOverflow)?;
+    let surplus_lamports = ctx
+        .accounts
+        .rent_payer_account
+        .lamports()
+        .saturating_sub(reserved_lamports);
+    check!(
+        amount_lamports <= surplus_lamports,
+        PyraError::InsufficientRentSurplus
+    );
+
+    match rent_payer {
+        // Holds data, so it's owned by the program and debited directly
+        RentPayer::TimeLock => {
+            ctx.accounts
+                .rent_payer_account
+                .sub_lamports(amount_lamports)?;
+            ctx.accounts.destination.add_lamports(amount_lamports)?;
+        }
+        RentPayer::Init | RentPayer::Bridge => {
+            let rent_payer_bump = ctx.bumps.rent_payer_account;
+            let rent_payer_seeds = &[rent_payer.seeds(), &[rent_payer_bump]];
+            let signer_seeds = &[&rent_payer_seeds[..]];
+
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.rent_payer_account.to_account_info(),
+                        to: ctx.accounts.destination.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount_lamports,
+            )?;
+        }
+    }
+
+    emit_cpi!(RentSurplusWithdrawn {
+        rent_payer,
+        destination: ctx.accounts.destination.key(),
+        amount_lamports,
+        balance_lamports: ctx.accounts.rent_payer_account.lamports(),
+        reserved_lamports,
+    });
+
+    Ok(())
+}
@@ -1,6 +1,13 @@
-use crate::{check, config::PyraError, state::WithdrawOrder, utils::close_time_lock};
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::WithdrawCancelled,
+    state::{Vault, WithdrawOrder},
+    utils::close_time_lock,
+};
 use anchor_lang::prelude::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CancelWithdraw<'info> {
     #[account(mut)]
@@ -13,6 +20,15 @@ pub struct CancelWithdraw<'info> {
     pub time_lock_rent_payer: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
+
+    // No has_one, so orders left over from a previous owner can still be cancelled
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
 }
 
 /// Creates a time locked withdraw order, which can be fulfilled permissionlessly once the time lock has expired. Time locks prevent edge cases of double spend with the Pyra card.
@@ -28,10 +44,27 @@ pub fn cancel_withdraw_handler<'info>(
         PyraError::InvalidTimeLockOwner
     );
 
+    check!(
+        ctx.accounts
+            .withdraw_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidWithdrawOrder
+    );
+
     close_time_lock(
         &ctx.accounts.withdraw_order,
         &ctx.accounts.time_lock_rent_payer.to_account_info(),
     )?;
 
+    ctx.accounts
+        .vault
+        .remove_open_order(&ctx.accounts.withdraw_order.time_lock);
+
+    emit_cpi!(WithdrawCancelled {
+        owner: ctx.accounts.owner.key(),
+        withdraw_order: ctx.accounts.withdraw_order.key(),
+    });
+
     Ok(())
 }
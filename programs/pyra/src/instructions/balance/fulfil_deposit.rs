@@ -1,7 +1,8 @@
 use crate::{
     check,
-    config::{PyraError, DEPOSIT_ADDRESS_SPACE, WSOL_MINT},
-    state::Vault,
+    config::{PyraError, DEPOSIT_ADDRESS_SPACE, VAULT_VERSION, WSOL_MINT},
+    events::DepositFulfilled,
+    state::{DriftMarketRegistry, GlobalConfig, ProtocolFeature, Vault},
     utils::get_drift_market,
 };
 use anchor_lang::prelude::*;
@@ -17,11 +18,14 @@ use drift::{
 };
 use solana_program::{program::invoke_signed, system_instruction};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct FulfilDeposit<'info> {
     #[account(
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
-        bump = vault.bump
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
     )]
     pub vault: Box<Account<'info, Vault>>,
 
@@ -44,7 +48,7 @@ pub struct FulfilDeposit<'info> {
 
     #[account(
         init_if_needed,
-        seeds = [b"deposit_mule".as_ref(), owner.key().as_ref(), mint.key().as_ref()],
+        seeds = [b"deposit_mule".as_ref(), vault.key().as_ref(), mint.key().as_ref()],
         bump,
         payer = caller,
         token::mint = mint,
@@ -60,8 +64,13 @@ pub struct FulfilDeposit<'info> {
 
     pub mint: Box<InterfaceAccount<'info, Mint>>,
 
-    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
-    #[account(mut)]
+    /// CHECK: Seeds ensure deposits go to the spend sub-account, the Drift CPI performs the remaining checks
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), vault.key().as_ref(), vault.spend_sub_account_id.to_le_bytes().as_ref()],
+        seeds::program = drift_program.key(),
+        bump
+    )]
     pub drift_user: UncheckedAccount<'info>,
 
     /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
@@ -83,6 +92,18 @@ pub struct FulfilDeposit<'info> {
     pub drift_program: Program<'info, Drift>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"drift_market_registry"],
+        bump = drift_market_registry.bump
+    )]
+    pub drift_market_registry: Box<Account<'info, DriftMarketRegistry>>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
 }
 
 /// Anyone can deposit into a Pyra account by sending funds to the deposit_address of that account, this function permissionlessly moves funds from that address into Drift
@@ -90,12 +111,20 @@ pub fn fulfil_deposit_handler<'info>(
     ctx: Context<'_, '_, '_, 'info, FulfilDeposit<'info>>,
     drift_market_index: u16,
 ) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::Deposits),
+        PyraError::ProtocolPaused
+    );
+
     // Validate market index and mint
-    let drift_market = get_drift_market(drift_market_index)?;
+    let drift_market = get_drift_market(&ctx.accounts.drift_market_registry, drift_market_index)?;
     check!(
         &ctx.accounts.mint.key().eq(&drift_market.mint),
         PyraError::InvalidMint
     );
+    check!(!drift_market.is_disabled, PyraError::DriftMarketDisabled);
 
     // Transfer tokens from deposit address ATA to vault's mule
     let deposit_address_bump = ctx.bumps.deposit_address;
@@ -112,7 +141,8 @@ pub fn fulfil_deposit_handler<'info>(
     // Drift Deposit CPI
     let vault_bump = ctx.accounts.vault.bump;
     let owner = ctx.accounts.owner.key();
-    let seeds_vault = &[b"vault", owner.as_ref(), &[vault_bump]];
+    let vault_id = ctx.accounts.vault.vault_id;
+    let seeds_vault = &[b"vault", vault_id.as_ref(), &[vault_bump]];
     let vault_signer = &[&seeds_vault[..]];
 
     let mut cpi_ctx = CpiContext::new_with_signer(
@@ -133,7 +163,8 @@ pub fn fulfil_deposit_handler<'info>(
 
     // reduce_only = false to allow for a loan position to become a collateral position
     ctx.accounts.mule.reload()?;
-    drift_deposit(cpi_ctx, drift_market_index, ctx.accounts.mule.amount, false)?;
+    let amount_base_units = ctx.accounts.mule.amount;
+    drift_deposit(cpi_ctx, drift_market_index, amount_base_units, false)?;
 
     // Close vault's mule
     let cpi_ctx_close = CpiContext::new_with_signer(
@@ -147,6 +178,16 @@ pub fn fulfil_deposit_handler<'info>(
     );
     close_account(cpi_ctx_close)?;
 
+    emit_cpi!(DepositFulfilled {
+        owner,
+        vault,
+        caller: ctx.accounts.caller.key(),
+        drift_market_index,
+        drift_sub_account_id: ctx.accounts.vault.spend_sub_account_id,
+        mint: ctx.accounts.mint.key(),
+        amount_base_units,
+    });
+
     Ok(())
 }
 
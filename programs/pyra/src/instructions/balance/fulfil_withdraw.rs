@@ -1,8 +1,9 @@
 use crate::{
     check,
-    config::{PyraError, DEPOSIT_ADDRESS_SPACE, WSOL_MINT},
-    state::{Vault, WithdrawOrder},
-    utils::{close_time_lock, get_drift_market, validate_ata, validate_time_lock},
+    config::{PyraError, DEPOSIT_ADDRESS_SPACE, VAULT_VERSION, WSOL_MINT},
+    events::WithdrawFulfilled,
+    state::{DriftMarketRegistry, GlobalConfig, ProtocolFeature, Vault, WithdrawOrder},
+    utils::{close_time_lock, get_drift_market, validate_ata, validate_vault_time_lock},
 };
 use anchor_lang::prelude::*;
 use anchor_spl::{
@@ -20,6 +21,7 @@ use solana_program::{
     system_instruction,
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct FulfilWithdraw<'info> {
     #[account(mut)]
@@ -33,14 +35,17 @@ pub struct FulfilWithdraw<'info> {
     pub caller: Signer<'info>,
 
     #[account(
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
-        bump = vault.bump
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
     )]
     pub vault: Box<Account<'info, Vault>>,
 
     #[account(
         init_if_needed,
-        seeds = [b"withdraw_mule".as_ref(), owner.key().as_ref(), mint.key().as_ref()],
+        seeds = [b"withdraw_mule".as_ref(), vault.key().as_ref(), mint.key().as_ref()],
         bump,
         payer = caller,
         token::mint = mint,
@@ -53,8 +58,17 @@ pub struct FulfilWithdraw<'info> {
 
     pub mint: Box<InterfaceAccount<'info, Mint>>,
 
-    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
-    #[account(mut)]
+    /// CHECK: Seeds ensure the sub-account matches the order, the Drift CPI performs the remaining checks
+    #[account(
+        mut,
+        seeds = [
+            b"user".as_ref(),
+            vault.key().as_ref(),
+            withdraw_order.drift_sub_account_id.to_le_bytes().as_ref()
+        ],
+        seeds::program = drift_program.key(),
+        bump
+    )]
     pub drift_user: UncheckedAccount<'info>,
 
     /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
@@ -103,12 +117,31 @@ pub struct FulfilWithdraw<'info> {
     /// CHECK: Checked in handler as the account doesn't need to exist
     #[account(mut)]
     pub deposit_address_spl: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"drift_market_registry"],
+        bump = drift_market_registry.bump
+    )]
+    pub drift_market_registry: Box<Account<'info, DriftMarketRegistry>>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
 }
 
 /// Permissionless function to fulfil a withdraw order, sending funds to the order's destination
 pub fn fulfil_withdraw_handler<'info>(
-    ctx: Context<'_, '_, '_, 'info, FulfilWithdraw<'info>>,
+    mut ctx: Context<'_, '_, '_, 'info, FulfilWithdraw<'info>>,
 ) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::Withdrawals),
+        PyraError::ProtocolPaused
+    );
+
     check!(
         ctx.accounts
             .destination
@@ -117,10 +150,19 @@ pub fn fulfil_withdraw_handler<'info>(
         PyraError::InvalidWithdrawDestination
     );
 
-    let (amount_base_units, drift_market_index, reduce_only) = get_order_data(&ctx)?;
+    check!(
+        ctx.accounts
+            .withdraw_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidWithdrawOrder
+    );
+
+    let (amount_base_units, drift_market_index, reduce_only, drift_sub_account_id) =
+        get_order_data(&mut ctx)?;
 
     // Validate market index and mint
-    let drift_market = get_drift_market(drift_market_index)?;
+    let drift_market = get_drift_market(&ctx.accounts.drift_market_registry, drift_market_index)?;
     check!(
         &ctx.accounts.mint.key().eq(&drift_market.mint),
         PyraError::InvalidMint
@@ -133,7 +175,8 @@ pub fn fulfil_withdraw_handler<'info>(
     // Withdraw required funds remaining from Drift
     let vault_bump = ctx.accounts.vault.bump;
     let owner = ctx.accounts.owner.key();
-    let seeds_vault = &[b"vault", owner.as_ref(), &[vault_bump]];
+    let vault_id = ctx.accounts.vault.vault_id;
+    let seeds_vault = &[b"vault", vault_id.as_ref(), &[vault_bump]];
     let vault_signer = &[&seeds_vault[..]];
 
     if funds_to_withdraw_after_idle > 0 {
@@ -165,6 +208,7 @@ pub fn fulfil_withdraw_handler<'info>(
     // Send mule's balance to destination
     ctx.accounts.mule.reload()?;
     let amount_to_withdraw = ctx.accounts.mule.amount;
+    let idle_funds_used = amount_base_units.saturating_sub(funds_to_withdraw_after_idle);
 
     if is_sol {
         // wSOL must be unwrapped and sent as raw SOL, as the destination likely won't have a wSOL ATA
@@ -173,25 +217,50 @@ pub fn fulfil_withdraw_handler<'info>(
         withdraw_spl(&ctx, vault_signer, amount_to_withdraw)?;
     }
 
+    ctx.accounts
+        .vault
+        .remove_open_order(&ctx.accounts.withdraw_order.time_lock);
+
+    emit_cpi!(WithdrawFulfilled {
+        owner,
+        vault: ctx.accounts.vault.key(),
+        withdraw_order: ctx.accounts.withdraw_order.key(),
+        drift_market_index,
+        drift_sub_account_id,
+        mint: ctx.accounts.mint.key(),
+        destination: ctx.accounts.destination.key(),
+        amount_base_units,
+        idle_funds_used,
+        // Idle SOL is sent straight to the destination, skipping the mule
+        amount_sent_base_units: if is_sol {
+            amount_to_withdraw.saturating_add(idle_funds_used)
+        } else {
+            amount_to_withdraw
+        },
+    });
+
     Ok(())
 }
 
-fn get_order_data(ctx: &Context<FulfilWithdraw>) -> Result<(u64, u16, bool)> {
-    validate_time_lock(
-        &ctx.accounts.owner.key(),
-        &ctx.accounts.withdraw_order.time_lock,
-    )?;
+fn get_order_data(ctx: &mut Context<FulfilWithdraw>) -> Result<(u64, u16, bool, u16)> {
+    validate_vault_time_lock(&ctx.accounts.vault, &ctx.accounts.withdraw_order.time_lock)?;
 
     let amount_base_units = ctx.accounts.withdraw_order.amount_base_units;
     let drift_market_index = ctx.accounts.withdraw_order.drift_market_index;
     let reduce_only = ctx.accounts.withdraw_order.reduce_only;
+    let drift_sub_account_id = ctx.accounts.withdraw_order.drift_sub_account_id;
 
     close_time_lock(
         &ctx.accounts.withdraw_order,
         &ctx.accounts.time_lock_rent_payer.to_account_info(),
     )?;
 
-    Ok((amount_base_units, drift_market_index, reduce_only))
+    Ok((
+        amount_base_units,
+        drift_market_index,
+        reduce_only,
+        drift_sub_account_id,
+    ))
 }
 
 fn transfer_idle_funds(
@@ -1,19 +1,34 @@
 use crate::{
-    config::{PyraError, ANCHOR_DISCRIMINATOR, TIME_LOCK_DURATION_SLOTS},
-    state::{TimeLock, Vault, WithdrawOrder},
-    utils::{allocate_time_lock_owner_payer, allocate_time_lock_program_payer},
+    check,
+    config::{PyraError, ANCHOR_DISCRIMINATOR, VAULT_VERSION},
+    events::WithdrawInitiated,
+    state::{
+        DelegatePermission, GlobalConfig, ProtocolFeature, TimeLock, Vault, VaultDelegates,
+        WithdrawOrder,
+    },
+    utils::{
+        allocate_time_lock_owner_payer, allocate_time_lock_program_payer, validate_vault_authority,
+    },
 };
 use anchor_lang::{prelude::*, Discriminator};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InitiateWithdraw<'info> {
     #[account(
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
-        bump = vault.bump
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
     )]
     pub vault: Box<Account<'info, Vault>>,
 
-    pub owner: Signer<'info>,
+    /// CHECK: Checked by the vault's has_one constraint
+    pub owner: UncheckedAccount<'info>,
+
+    // Owner or a delegate with the required permission
+    pub authority: Signer<'info>,
 
     #[account(mut)]
     pub withdraw_order: Signer<'info>,
@@ -26,6 +41,18 @@ pub struct InitiateWithdraw<'info> {
 
     /// CHECK: Can be any account
     pub destination: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        seeds = [b"vault_delegates".as_ref(), vault.key().as_ref()],
+        bump = vault_delegates.bump
+    )]
+    pub vault_delegates: Option<Box<Account<'info, VaultDelegates>>>,
 }
 
 /// Creates a time locked withdraw order, which can be fulfilled permissionlessly once the time lock has expired. Time locks prevent edge cases of double spend with the Pyra card.
@@ -34,7 +61,29 @@ pub fn initiate_withdraw_handler<'info>(
     amount_base_units: u64,
     drift_market_index: u16,
     reduce_only: bool,
+    drift_sub_account_id: u16,
 ) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::Withdrawals),
+        PyraError::ProtocolPaused
+    );
+
+    let delegate = validate_vault_authority(
+        &ctx.accounts.vault,
+        &ctx.accounts.authority.key(),
+        &ctx.accounts.vault_delegates,
+        DelegatePermission::WithdrawToOwner,
+    )?;
+    if delegate.is_some() {
+        check!(
+            ctx.accounts.destination.key().eq(&ctx.accounts.owner.key()),
+            PyraError::InvalidDelegateWithdrawDestination
+        );
+    }
+
+    // Delegates use the program rent payer, as the owner receives the rent when the order is closed
     let is_owner_payer = ctx
         .accounts
         .time_lock_rent_payer
@@ -42,8 +91,12 @@ pub fn initiate_withdraw_handler<'info>(
         .eq(&ctx.accounts.owner.key());
 
     if is_owner_payer {
+        check!(
+            ctx.accounts.authority.key().eq(&ctx.accounts.owner.key()),
+            PyraError::InvalidTimeLockRentPayer
+        );
         allocate_time_lock_owner_payer(
-            &ctx.accounts.owner,
+            &ctx.accounts.authority,
             &ctx.accounts.withdraw_order,
             &ctx.accounts.system_program,
             WithdrawOrder::INIT_SPACE,
@@ -59,25 +112,44 @@ pub fn initiate_withdraw_handler<'info>(
 
     let current_slot = Clock::get()?.slot;
     let release_slot = current_slot
-        .checked_add(TIME_LOCK_DURATION_SLOTS)
+        .checked_add(ctx.accounts.global_config.time_lock_duration_slots)
         .ok_or(PyraError::MathOverflow)?;
 
     let withdraw_order_data = WithdrawOrder {
         time_lock: TimeLock {
             owner: ctx.accounts.owner.key(),
+            owner_epoch: ctx.accounts.vault.owner_epoch,
             is_owner_payer,
             release_slot,
         },
+        vault: ctx.accounts.vault.key(),
         amount_base_units,
         drift_market_index,
         reduce_only,
         destination: ctx.accounts.destination.key(),
+        drift_sub_account_id,
     };
     let withdraw_order_data_vec = withdraw_order_data.try_to_vec()?;
 
     let mut data = ctx.accounts.withdraw_order.try_borrow_mut_data()?;
     data[..ANCHOR_DISCRIMINATOR].copy_from_slice(&WithdrawOrder::DISCRIMINATOR);
     data[ANCHOR_DISCRIMINATOR..].copy_from_slice(&withdraw_order_data_vec[..]);
+    drop(data);
+
+    ctx.accounts.vault.add_open_order()?;
+
+    emit_cpi!(WithdrawInitiated {
+        owner: ctx.accounts.owner.key(),
+        authority: ctx.accounts.authority.key(),
+        vault: ctx.accounts.vault.key(),
+        withdraw_order: ctx.accounts.withdraw_order.key(),
+        amount_base_units,
+        drift_market_index,
+        drift_sub_account_id,
+        reduce_only,
+        destination: ctx.accounts.destination.key(),
+        release_slot,
+    });
 
     Ok(())
 }
@@ -1,7 +1,8 @@
 use crate::{
     check,
-    config::{PyraError, DRIFT_MARKETS},
-    state::Vault,
+    config::{PyraError, VAULT_VERSION},
+    events::DepositRescued,
+    state::{DriftMarketRegistry, Vault},
 };
 use anchor_lang::prelude::*;
 use anchor_spl::{
@@ -12,11 +13,14 @@ use anchor_spl::{
     },
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct RescueDeposit<'info> {
     #[account(
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
-        bump = vault.bump
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
     )]
     pub vault: Box<Account<'info, Vault>>,
 
@@ -53,6 +57,12 @@ pub struct RescueDeposit<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"drift_market_registry"],
+        bump = drift_market_registry.bump
+    )]
+    pub drift_market_registry: Box<Account<'info, DriftMarketRegistry>>,
 }
 
 /// Sends unsupported tokens from the deposit address to the destination (in case the user accidentally sends the wrong token)
@@ -61,7 +71,10 @@ pub fn rescue_deposit_handler<'info>(
 ) -> Result<()> {
     // Validate SPL token is not supported
 
-    if DRIFT_MARKETS
+    if ctx
+        .accounts
+        .drift_market_registry
+        .markets
         .iter()
         .any(|market| market.mint == ctx.accounts.mint.key())
     {
@@ -105,5 +118,12 @@ pub fn rescue_deposit_handler<'info>(
         deposit_address_signer,
     ))?;
 
+    emit_cpi!(DepositRescued {
+        owner: ctx.accounts.owner.key(),
+        vault,
+        mint: ctx.accounts.mint.key(),
+        amount_base_units: balance,
+    });
+
     Ok(())
 }
@@ -1,9 +1,16 @@
 use crate::{
     check,
-    config::PyraError,
+    config::{PyraError, VAULT_VERSION},
+    events::CollateralRepayDeposited,
     load_mut,
-    state::{CollateralRepayLedger, Vault},
-    utils::{get_account_health, get_drift_market, validate_start_collateral_repay_ix},
+    state::{
+        CollateralRepayLedger, DelegatePermission, DriftMarketRegistry, GlobalConfig,
+        ProtocolFeature, SettleAndCloseOrder, Vault, VaultDelegates,
+    },
+    utils::{
+        get_account_health, get_drift_market, is_settle_repay, validate_start_collateral_repay_ix,
+        validate_vault_authority,
+    },
 };
 use anchor_lang::{
     prelude::*,
@@ -21,7 +28,9 @@ use drift::{
     state::{state::State as DriftState, user::User as DriftUser},
 };
 
+#[event_cpi]
 #[derive(Accounts)]
+#[instruction(deposit_market_index: u16, drift_sub_account_id: u16)]
 pub struct DepositCollateralRepay<'info> {
     #[account(mut)]
     pub caller: Signer<'info>,
@@ -38,14 +47,16 @@ pub struct DepositCollateralRepay<'info> {
     pub owner: UncheckedAccount<'info>,
 
     #[account(
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
-        bump = vault.bump
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
     )]
     pub vault: Box<Account<'info, Vault>>,
 
     #[account(
         init_if_needed,
-        seeds = [b"collateral_repay_mule".as_ref(), owner.key().as_ref(), mint.key().as_ref()],
+        seeds = [b"collateral_repay_mule".as_ref(), vault.key().as_ref(), mint.key().as_ref()],
         bump,
         payer = caller,
         token::mint = mint,
@@ -58,7 +69,7 @@ pub struct DepositCollateralRepay<'info> {
     // Checked here as required for health calculations
     #[account(
         mut,
-        seeds = [b"user".as_ref(), vault.key().as_ref(), (0u16).to_le_bytes().as_ref()],
+        seeds = [b"user".as_ref(), vault.key().as_ref(), drift_sub_account_id.to_le_bytes().as_ref()],
         seeds::program = drift_program.key(),
         bump
     )]
@@ -88,23 +99,54 @@ pub struct DepositCollateralRepay<'info> {
 
     #[account(
         mut,
-        seeds = [b"collateral_repay_ledger".as_ref(), owner.key().as_ref()],
+        seeds = [b"collateral_repay_ledger".as_ref(), vault.key().as_ref()],
         bump
     )]
     pub ledger: Box<Account<'info, CollateralRepayLedger>>,
+
+    #[account(
+        seeds = [b"drift_market_registry"],
+        bump = drift_market_registry.bump
+    )]
+    pub drift_market_registry: Box<Account<'info, DriftMarketRegistry>>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        seeds = [b"vault_delegates".as_ref(), vault.key().as_ref()],
+        bump = vault_delegates.bump
+    )]
+    pub vault_delegates: Option<Box<Account<'info, VaultDelegates>>>,
+
+    // Only required for settle repays, which repay the loans of a vault being settled and closed
+    pub settle_and_close_order: Option<Box<Account<'info, SettleAndCloseOrder>>>,
 }
 
 /// Second collateral repay instruction, takes place after swap. Deposits tokens into Drift to pay off loan.
 pub fn deposit_collateral_repay_handler<'info>(
     ctx: Context<'_, '_, 'info, 'info, DepositCollateralRepay<'info>>,
     deposit_market_index: u16,
+    drift_sub_account_id: u16,
 ) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::CollateralRepay),
+        PyraError::ProtocolPaused
+    );
+
     let vault_bump = ctx.accounts.vault.bump;
     let owner = ctx.accounts.owner.key();
-    let seeds = &[b"vault", owner.as_ref(), &[vault_bump]];
+    let vault_id = ctx.accounts.vault.vault_id;
+    let seeds = &[b"vault", vault_id.as_ref(), &[vault_bump]];
     let signer_seeds = &[&seeds[..]];
 
-    let deposit_market = get_drift_market(deposit_market_index)?;
+    let deposit_market =
+        get_drift_market(&ctx.accounts.drift_market_registry, deposit_market_index)?;
     check!(
         &ctx.accounts.mint.key().eq(&deposit_market.mint),
         PyraError::InvalidMint
@@ -118,8 +160,20 @@ pub fn deposit_collateral_repay_handler<'info>(
         load_instruction_at_checked(index - 2, &ctx.accounts.instructions.to_account_info())?;
     validate_start_collateral_repay_ix(&current_instruction, &start_instruction)?;
 
-    // Validate auto repay threshold if owner hasn't signed
-    if !ctx.accounts.owner.is_signer {
+    // Validate auto repay threshold if neither the owner nor a collateral repay delegate has signed, unless the
+    // owner has asked to settle and close the vault
+    let is_settle_repay =
+        is_settle_repay(&ctx.accounts.vault, &ctx.accounts.settle_and_close_order)?;
+    let is_auto_repay = !is_settle_repay
+        && !ctx.accounts.owner.is_signer
+        && validate_vault_authority(
+            &ctx.accounts.vault,
+            &ctx.accounts.caller.key(),
+            &ctx.accounts.vault_delegates,
+            DelegatePermission::CollateralRepay,
+        )
+        .is_err();
+    if is_auto_repay {
         let withdraw_instruction =
             load_instruction_at_checked(index + 1, &ctx.accounts.instructions.to_account_info())?;
         let withdraw_market_index = u16::from_le_bytes(
@@ -214,6 +268,17 @@ pub fn deposit_collateral_repay_handler<'info>(
     let true_amount_deposited = amount_deposit_base_units.saturating_sub(remaining_balance);
     ctx.accounts.ledger.deposit = true_amount_deposited;
 
+    emit_cpi!(CollateralRepayDeposited {
+        owner,
+        caller: ctx.accounts.caller.key(),
+        drift_sub_account_id,
+        deposit_market_index,
+        amount_deposited: true_amount_deposited,
+        amount_returned: remaining_balance,
+        is_auto_repay,
+        is_settle_repay,
+    });
+
     Ok(())
 }
 
@@ -1,7 +1,8 @@
 use crate::{
     check,
-    config::{PyraError, ANCHOR_DISCRIMINATOR},
-    state::{CollateralRepayLedger, Vault},
+    config::{PyraError, ANCHOR_DISCRIMINATOR, VAULT_VERSION},
+    events::CollateralRepayStarted,
+    state::{CollateralRepayLedger, GlobalConfig, ProtocolFeature, Vault},
 };
 use anchor_lang::{prelude::*, solana_program::instruction::Instruction, Discriminator};
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
@@ -10,6 +11,7 @@ use solana_program::{
     sysvar::instructions::{self, load_current_index_checked, load_instruction_at_checked},
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct StartCollateralRepay<'info> {
     #[account(mut)]
@@ -35,8 +37,10 @@ pub struct StartCollateralRepay<'info> {
     pub owner: UncheckedAccount<'info>,
 
     #[account(
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
-        bump = vault.bump
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
     )]
     pub vault: Box<Account<'info, Vault>>,
 
@@ -56,18 +60,31 @@ pub struct StartCollateralRepay<'info> {
 
     #[account(
         init,
-        seeds = [b"collateral_repay_ledger".as_ref(), owner.key().as_ref()],
+        seeds = [b"collateral_repay_ledger".as_ref(), vault.key().as_ref()],
         bump,
         payer = caller,
         space = CollateralRepayLedger::INIT_SPACE
     )]
     pub ledger: Box<Account<'info, CollateralRepayLedger>>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
 }
 
 /// First collateral repay instruction, takes place before swap. Validates order and state of instructions, and logs starting balances
 pub fn start_collateral_repay_handler<'info>(
     ctx: Context<'_, '_, 'info, 'info, StartCollateralRepay<'info>>,
 ) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::CollateralRepay),
+        PyraError::ProtocolPaused
+    );
+
     let index: usize =
         load_current_index_checked(&ctx.accounts.instructions.to_account_info())?.into();
     let current_instruction =
@@ -86,7 +103,11 @@ pub fn start_collateral_repay_handler<'info>(
         &withdraw_instruction,
     )?;
 
-    validate_user_accounts_context(&deposit_instruction, &withdraw_instruction)?;
+    validate_user_accounts_context(
+        &ctx.accounts.vault.key(),
+        &deposit_instruction,
+        &withdraw_instruction,
+    )?;
 
     validate_drift_markets(&deposit_instruction, &withdraw_instruction)?;
 
@@ -98,6 +119,15 @@ pub fn start_collateral_repay_handler<'info>(
     ledger.deposit = ctx.accounts.caller_deposit_spl.amount;
     ledger.withdraw = ctx.accounts.caller_withdraw_spl.amount;
 
+    emit_cpi!(CollateralRepayStarted {
+        owner: ctx.accounts.owner.key(),
+        caller: ctx.accounts.caller.key(),
+        mint_deposit: ctx.accounts.mint_deposit.key(),
+        mint_withdraw: ctx.accounts.mint_withdraw.key(),
+        starting_deposit_balance: ctx.accounts.ledger.deposit,
+        starting_withdraw_balance: ctx.accounts.ledger.withdraw,
+    });
+
     Ok(())
 }
 
@@ -154,6 +184,7 @@ pub fn validate_instruction_order(
 
 #[inline(never)]
 fn validate_user_accounts_context(
+    vault: &Pubkey,
     deposit_instruction: &Instruction,
     withdraw_instruction: &Instruction,
 ) -> Result<()> {
@@ -164,14 +195,21 @@ fn validate_user_accounts_context(
         PyraError::InvalidUserAccounts
     );
 
-    let deposit_owner = deposit_instruction.accounts[2].pubkey;
-    let withdraw_owner = withdraw_instruction.accounts[2].pubkey;
+    let deposit_vault = deposit_instruction.accounts[3].pubkey;
+    let withdraw_vault = withdraw_instruction.accounts[3].pubkey;
     check!(
-        deposit_owner.eq(&withdraw_owner),
+        deposit_vault.eq(vault) && withdraw_vault.eq(vault),
         PyraError::InvalidUserAccounts
     );
 
-    // Vault and drift accounts will all be the same if owner is the same
+    // Both must use the same Drift sub-account, otherwise the repay would move collateral between sub-accounts
+    check!(
+        deposit_instruction
+            .data
+            .get(10..12)
+            .eq(&withdraw_instruction.data.get(10..12)),
+        PyraError::InvalidUserAccounts
+    );
 
     Ok(())
 }
@@ -1,14 +1,15 @@
 use crate::{
     check,
-    config::{
-        DriftMarket, PyraError, AUTO_REPAY_MAX_HEALTH_RESULT_PERCENT, AUTO_REPAY_MAX_SLIPPAGE_BPS,
-        PYTH_MAX_PRICE_AGE_SECONDS,
-    },
+    config::{PyraError, VAULT_VERSION},
+    events::CollateralRepayWithdrawn,
     load_mut,
-    state::{CollateralRepayLedger, Vault},
+    state::{
+        CollateralRepayLedger, DelegatePermission, DriftMarketRegistry, GlobalConfig,
+        ProtocolFeature, SettleAndCloseOrder, Vault, VaultDelegates,
+    },
     utils::{
-        get_account_health, get_drift_market, normalize_price_exponents,
-        validate_start_collateral_repay_ix,
+        get_account_health, get_drift_market, is_settle_repay, validate_start_collateral_repay_ix,
+        validate_swap_prices, validate_vault_authority, SwapLeg,
     },
 };
 use anchor_lang::{
@@ -26,9 +27,11 @@ use drift::{
     program::Drift,
     state::{state::State as DriftState, user::User as DriftUser},
 };
-use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 
+#[event_cpi]
 #[derive(Accounts)]
+#[instruction(withdraw_market_index: u16, drift_sub_account_id: u16)]
 pub struct WithdrawCollateralRepay<'info> {
     #[account(mut)]
     pub caller: Signer<'info>,
@@ -45,14 +48,16 @@ pub struct WithdrawCollateralRepay<'info> {
     pub owner: UncheckedAccount<'info>,
 
     #[account(
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
-        bump = vault.bump
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
     )]
     pub vault: Box<Account<'info, Vault>>,
 
     #[account(
         init_if_needed,
-        seeds = [b"collateral_repay_mule".as_ref(), owner.key().as_ref(), mint.key().as_ref()],
+        seeds = [b"collateral_repay_mule".as_ref(), vault.key().as_ref(), mint.key().as_ref()],
         bump,
         payer = caller,
         token::mint = mint,
@@ -64,7 +69,7 @@ pub struct WithdrawCollateralRepay<'info> {
 
     #[account(
         mut,
-        seeds = [b"user".as_ref(), vault.key().as_ref(), (0u16).to_le_bytes().as_ref()],
+        seeds = [b"user".as_ref(), vault.key().as_ref(), drift_sub_account_id.to_le_bytes().as_ref()],
         seeds::program = drift_program.key(),
         bump
     )]
@@ -101,23 +106,54 @@ pub struct WithdrawCollateralRepay<'info> {
 
     #[account(
         mut,
-        seeds = [b"collateral_repay_ledger".as_ref(), owner.key().as_ref()],
+        seeds = [b"collateral_repay_ledger".as_ref(), vault.key().as_ref()],
         bump,
         close = caller
     )]
     pub ledger: Box<Account<'info, CollateralRepayLedger>>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        seeds = [b"drift_market_registry"],
+        bump = drift_market_registry.bump
+    )]
+    pub drift_market_registry: Box<Account<'info, DriftMarketRegistry>>,
+
+    #[account(
+        seeds = [b"vault_delegates".as_ref(), vault.key().as_ref()],
+        bump = vault_delegates.bump
+    )]
+    pub vault_delegates: Option<Box<Account<'info, VaultDelegates>>>,
+
+    // Only required for settle repays, which repay the loans of a vault being settled and closed
+    pub settle_and_close_order: Option<Box<Account<'info, SettleAndCloseOrder>>>,
 }
 
 /// Third collateral repay instruction, takes place after deposit. Withdraws collateral from Drift, checking values of deposit and withdraw are below slippage.
 pub fn withdraw_collateral_repay_handler<'info>(
     ctx: Context<'_, '_, 'info, 'info, WithdrawCollateralRepay<'info>>,
     withdraw_market_index: u16,
+    drift_sub_account_id: u16,
 ) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::CollateralRepay),
+        PyraError::ProtocolPaused
+    );
+
     let owner = ctx.accounts.owner.key();
-    let vault_seeds = &[b"vault", owner.as_ref(), &[ctx.accounts.vault.bump]];
+    let vault_id = ctx.accounts.vault.vault_id;
+    let vault_seeds = &[b"vault", vault_id.as_ref(), &[ctx.accounts.vault.bump]];
     let signer_seeds_vault = &[&vault_seeds[..]];
 
-    let withdraw_market = get_drift_market(withdraw_market_index)?;
+    let withdraw_market =
+        get_drift_market(&ctx.accounts.drift_market_registry, withdraw_market_index)?;
     check!(
         &ctx.accounts.mint.key().eq(&withdraw_market.mint),
         PyraError::InvalidMint
@@ -182,14 +218,22 @@ pub fn withdraw_collateral_repay_handler<'info>(
             .try_into()
             .map_err(|_| PyraError::FailedToDeserializeMarketIndex)?,
     );
-    let deposit_market = get_drift_market(deposit_market_index)?;
-
-    validate_prices(
-        &ctx,
-        true_amount_deposited,
-        true_amount_withdrawn,
-        deposit_market,
-        withdraw_market,
+    let deposit_market =
+        get_drift_market(&ctx.accounts.drift_market_registry, deposit_market_index)?;
+
+    let (deposit_price, withdraw_price) = validate_swap_prices(
+        SwapLeg {
+            amount_base_units: true_amount_deposited,
+            market: deposit_market,
+            price_update: &ctx.accounts.deposit_price_update,
+        },
+        SwapLeg {
+            amount_base_units: true_amount_withdrawn,
+            market: withdraw_market,
+            price_update: &ctx.accounts.withdraw_price_update,
+        },
+        ctx.accounts.global_config.pyth_max_price_age_seconds,
+        ctx.accounts.global_config.auto_repay_max_slippage_bps,
     )?;
 
     // Transfer tokens from mule to caller's ATA
@@ -220,98 +264,43 @@ pub fn withdraw_collateral_repay_handler<'info>(
     );
     close_account(cpi_ctx_close)?;
 
-    // Validate auto repay threshold if owner hasn't signed
-    if !ctx.accounts.owner.is_signer {
-        validate_health(&ctx, deposit_market_index, withdraw_market.market_index)?;
-    }
-
-    Ok(())
-}
-
-/// Takes the deposit and withdraw amounts, their prices, and validates that the withdraw amount is within slippage of the deposit amount
-#[inline(never)]
-fn validate_prices<'info>(
-    ctx: &Context<'_, '_, '_, 'info, WithdrawCollateralRepay<'info>>,
-    deposit_amount: u64,
-    withdraw_amount: u64,
-    deposit_market: &DriftMarket,
-    withdraw_market: &DriftMarket,
-) -> Result<()> {
-    // Get the deposit price, assuming worst case of lowest end of confidence interval
-    let deposit_feed_id: [u8; 32] = get_feed_id_from_hex(deposit_market.pyth_feed)?;
-    let deposit_price = ctx.accounts.deposit_price_update.get_price_no_older_than(
-        &Clock::get()?,
-        PYTH_MAX_PRICE_AGE_SECONDS,
-        &deposit_feed_id,
-    )?;
-    check!(deposit_price.price > 0, PyraError::NegativeOraclePrice);
-    let deposit_lowest_price = u64::try_from(deposit_price.price)
-        .map_err(|_| PyraError::MathOverflow)?
-        .checked_sub(deposit_price.conf)
-        .ok_or(PyraError::NegativeOraclePrice)?;
-
-    // Get the withdraw price, assuming worst case of highest end of confidence interval
-    let withdraw_feed_id: [u8; 32] = get_feed_id_from_hex(withdraw_market.pyth_feed)?;
-    let withdraw_price = ctx.accounts.withdraw_price_update.get_price_no_older_than(
-        &Clock::get()?,
-        PYTH_MAX_PRICE_AGE_SECONDS,
-        &withdraw_feed_id,
-    )?;
-    check!(withdraw_price.price > 0, PyraError::NegativeOraclePrice);
-    let withdraw_highest_price = u64::try_from(withdraw_price.price)
-        .map_err(|_| PyraError::MathOverflow)?
-        .checked_add(withdraw_price.conf)
-        .ok_or(PyraError::MathOverflow)?;
-
-    // Normalize prices to the same exponents
-    let (deposit_lowest_price_normalized, withdraw_highest_price_normalized) =
-        normalize_price_exponents(
-            deposit_lowest_price as u128,
-            deposit_price.exponent,
-            withdraw_highest_price as u128,
-            withdraw_price.exponent,
-        )?;
-
-    // Normalize amounts to the same decimals (base units per token)
-    let deposit_amount_normalized: u128 = (deposit_amount as u128)
-        .checked_mul(withdraw_market.base_units_per_token as u128)
-        .ok_or(PyraError::MathOverflow)?;
-    let withdraw_amount_normalized: u128 = (withdraw_amount as u128)
-        .checked_mul(deposit_market.base_units_per_token as u128)
-        .ok_or(PyraError::MathOverflow)?;
-
-    // Calculate values
-    let deposit_value: u128 = deposit_amount_normalized
-        .checked_mul(deposit_lowest_price_normalized)
-        .ok_or(PyraError::MathOverflow)?;
-    let withdraw_value: u128 = withdraw_amount_normalized
-        .checked_mul(withdraw_highest_price_normalized)
-        .ok_or(PyraError::MathOverflow)?;
-
-    // Sanity check on slippage
-    const HARD_MAX_SLIPPAGE_BPS: u16 = 500;
-    check!(
-        AUTO_REPAY_MAX_SLIPPAGE_BPS <= HARD_MAX_SLIPPAGE_BPS,
-        PyraError::InvalidSlippageBPS
-    );
-
-    // Allow for slippage, using integar multiplication to prevent floating point errors
-    let slippage_multiplier_deposit: u128 = 100 * 100; // 100% x 100bps
-    let slippage_multiplier_withdraw: u128 = slippage_multiplier_deposit
-        .checked_sub(AUTO_REPAY_MAX_SLIPPAGE_BPS as u128)
-        .ok_or(PyraError::MathOverflow)?;
-
-    let deposit_slippage_check_value = deposit_value
-        .checked_mul(slippage_multiplier_deposit)
-        .ok_or(PyraError::MathOverflow)?;
-    let withdraw_slippage_check_value = withdraw_value
-        .checked_mul(slippage_multiplier_withdraw)
-        .ok_or(PyraError::MathOverflow)?;
-
-    check!(
-        deposit_slippage_check_value >= withdraw_slippage_check_value,
-        PyraError::MaxSlippageExceeded
-    );
+    // Validate auto repay threshold if neither the owner nor a collateral repay delegate has signed, unless the
+    // owner has asked to settle and close the vault
+    let is_settle_repay =
+        is_settle_repay(&ctx.accounts.vault, &ctx.accounts.settle_and_close_order)?;
+    let is_auto_repay = !is_settle_repay
+        && !ctx.accounts.owner.is_signer
+        && validate_vault_authority(
+            &ctx.accounts.vault,
+            &ctx.accounts.caller.key(),
+            &ctx.accounts.vault_delegates,
+            DelegatePermission::CollateralRepay,
+        )
+        .is_err();
+    let resulting_health = if is_auto_repay {
+        Some(validate_health(
+            &ctx,
+            deposit_market_index,
+            withdraw_market.market_index,
+        )?)
+    } else {
+        None
+    };
+
+    emit_cpi!(CollateralRepayWithdrawn {
+        owner,
+        caller: ctx.accounts.caller.key(),
+        drift_sub_account_id,
+        deposit_market_index,
+        withdraw_market_index,
+        amount_deposited: true_amount_deposited,
+        amount_withdrawn: true_amount_withdrawn,
+        deposit_price: deposit_price.price,
+        deposit_price_exponent: deposit_price.exponent,
+        withdraw_price: withdraw_price.price,
+        withdraw_price_exponent: withdraw_price.exponent,
+        resulting_health,
+    });
 
     Ok(())
 }
@@ -321,7 +310,7 @@ fn validate_health<'info>(
     ctx: &Context<'_, '_, 'info, 'info, WithdrawCollateralRepay<'info>>,
     deposit_market_index: u16,
     withdraw_market_index: u16,
-) -> Result<()> {
+) -> Result<u8> {
     let user = &mut load_mut!(ctx.accounts.drift_user)?;
     let health = get_account_health(
         user,
@@ -334,9 +323,13 @@ fn validate_health<'info>(
     check!(health > 0, PyraError::AutoRepayNotEnoughSold);
 
     check!(
-        health <= AUTO_REPAY_MAX_HEALTH_RESULT_PERCENT,
+        health
+            <= ctx
+                .accounts
+                .global_config
+                .auto_repay_max_health_result_percent,
         PyraError::AutoRepayTooMuchSold
     );
 
-    Ok(())
+    Ok(health)
 }
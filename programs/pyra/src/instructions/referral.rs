@@ -0,0 +1,5 @@
+mod register_referrer;
+pub use register_referrer::*;
+
+mod claim_referrer_rewards;
+pub use claim_referrer_rewards::*;
@@ -0,0 +1,96 @@
+use crate::{
+    check,
+    config::{PyraError, USDC_MINT},
+    events::ReferrerRewardsClaimed,
+    state::{GlobalConfig, ProtocolFeature, ReferrerRewards},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimReferrerRewards<'info> {
+    pub referrer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"referrer_rewards".as_ref(), referrer.key().as_ref()],
+        bump = referrer_rewards.bump,
+        has_one = referrer
+    )]
+    pub referrer_rewards: Box<Account<'info, ReferrerRewards>>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = referrer_rewards,
+        associated_token::token_program = token_program
+    )]
+    pub referrer_rewards_usdc: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = usdc_mint,
+        token::token_program = token_program
+    )]
+    pub destination_usdc: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = USDC_MINT)]
+    pub usdc_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Sends the referrer's unclaimed share of spend fees to any USDC account of their choice
+pub fn claim_referrer_rewards_handler(ctx: Context<ClaimReferrerRewards>) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::Withdrawals),
+        PyraError::ProtocolPaused
+    );
+
+    let amount_usdc_base_units = ctx.accounts.referrer_rewards.unclaimed_usdc_base_units;
+    check!(amount_usdc_base_units > 0, PyraError::NoReferrerRewards);
+
+    let referrer = ctx.accounts.referrer.key();
+    let seeds_referrer_rewards = &[
+        b"referrer_rewards".as_ref(),
+        referrer.as_ref(),
+        &[ctx.accounts.referrer_rewards.bump],
+    ];
+    let referrer_rewards_signer = &[&seeds_referrer_rewards[..]];
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.referrer_rewards_usdc.to_account_info(),
+                to: ctx.accounts.destination_usdc.to_account_info(),
+                authority: ctx.accounts.referrer_rewards.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
+            },
+            referrer_rewards_signer,
+        ),
+        amount_usdc_base_units,
+        ctx.accounts.usdc_mint.decimals,
+    )?;
+
+    ctx.accounts.referrer_rewards.unclaimed_usdc_base_units = 0;
+
+    emit_cpi!(ReferrerRewardsClaimed {
+        referrer,
+        destination_usdc: ctx.accounts.destination_usdc.key(),
+        amount_usdc_base_units,
+    });
+
+    Ok(())
+}
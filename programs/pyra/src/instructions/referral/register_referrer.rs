@@ -0,0 +1,74 @@
+use crate::{
+    check,
+    config::{PyraError, USDC_MINT},
+    events::ReferrerRegistered,
+    state::{GlobalConfig, ProtocolFeature, ReferrerRewards},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [b"referrer_rewards".as_ref(), referrer.key().as_ref()],
+        bump,
+        payer = referrer,
+        space = ReferrerRewards::INIT_SPACE
+    )]
+    pub referrer_rewards: Box<Account<'info, ReferrerRewards>>,
+
+    #[account(
+        init,
+        payer = referrer,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = referrer_rewards,
+        associated_token::token_program = token_program
+    )]
+    pub referrer_rewards_usdc: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = USDC_MINT)]
+    pub usdc_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Registers the signer as a referrer, creating the rewards ledger and the USDC account its share of spend fees is held in
+pub fn register_referrer_handler(ctx: Context<RegisterReferrer>) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::UserInit),
+        PyraError::ProtocolPaused
+    );
+
+    let referrer_rewards = &mut ctx.accounts.referrer_rewards;
+    referrer_rewards.referrer = ctx.accounts.referrer.key();
+    referrer_rewards.bump = ctx.bumps.referrer_rewards;
+    referrer_rewards.unclaimed_usdc_base_units = 0;
+    referrer_rewards.total_earned_usdc_base_units = 0;
+
+    emit_cpi!(ReferrerRegistered {
+        referrer: ctx.accounts.referrer.key(),
+        referrer_rewards: ctx.accounts.referrer_rewards.key(),
+    });
+
+    Ok(())
+}
@@ -1,9 +1,33 @@
 mod start_spend;
 pub use start_spend::*;
 
+mod authorize_spend;
+pub use authorize_spend::*;
+
+mod capture_spend;
+pub use capture_spend::*;
+
+mod release_spend_hold;
+pub use release_spend_hold::*;
+
+mod close_spend_record;
+pub use close_spend_record::*;
+
 mod complete_spend;
 pub use complete_spend::*;
 
+mod start_collateral_spend;
+pub use start_collateral_spend::*;
+
+mod deposit_collateral_spend;
+pub use deposit_collateral_spend::*;
+
+mod receive_refund;
+pub use receive_refund::*;
+
+mod set_settlement_provider;
+pub use set_settlement_provider::*;
+
 mod initiate_spend_limits;
 pub use initiate_spend_limits::*;
 
@@ -12,3 +36,27 @@ pub use fulfil_spend_limits::*;
 
 mod increase_spend_limits;
 pub use increase_spend_limits::*;
+
+mod boost_spend_limits;
+pub use boost_spend_limits::*;
+
+mod freeze_card;
+pub use freeze_card::*;
+
+mod initiate_unfreeze_card;
+pub use initiate_unfreeze_card::*;
+
+mod fulfil_unfreeze_card;
+pub use fulfil_unfreeze_card::*;
+
+mod cancel_unfreeze_card;
+pub use cancel_unfreeze_card::*;
+
+mod tighten_merchant_controls;
+pub use tighten_merchant_controls::*;
+
+mod initiate_merchant_controls;
+pub use initiate_merchant_controls::*;
+
+mod fulfil_merchant_controls;
+pub use fulfil_merchant_controls::*;
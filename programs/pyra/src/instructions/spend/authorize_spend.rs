@@ -0,0 +1,128 @@
+use crate::{
+    check,
+    config::{PyraError, MAX_SPEND_HOLD_DURATION_SECONDS, MERCHANT_ID_HASH_SIZE, VAULT_VERSION},
+    events::SpendAuthorized,
+    state::{GlobalConfig, ProtocolFeature, SpendHold, Vault},
+    utils::process_spend_limits,
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(authorization_id: u64)]
+pub struct AuthorizeSpend<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Can be any account, once it has a Vault
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = spend_caller.key().eq(&global_config.spend_caller) @ PyraError::InvalidSpendCaller
+    )]
+    pub spend_caller: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [b"spend_hold".as_ref(), vault.key().as_ref(), authorization_id.to_le_bytes().as_ref()],
+        bump,
+        payer = spend_caller,
+        space = SpendHold::INIT_SPACE
+    )]
+    pub spend_hold: Box<Account<'info, SpendHold>>,
+
+    /// CHECK: Checked in handler as the account doesn't need to exist, seeds ensure the vault's controls can't be skipped
+    #[account(
+        mut,
+        seeds = [b"vault_merchant_controls".as_ref(), vault.key().as_ref()],
+        bump
+    )]
+    pub vault_merchant_controls: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Reserves spend limit for a card authorization in a spend hold, without moving funds. The hold is settled by capture_spend, or released once it expires.
+pub fn authorize_spend_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuthorizeSpend<'info>>,
+    authorization_id: u64,
+    amount_usdc_base_units: u64,
+    hold_duration_in_seconds: u64,
+    merchant_category_code: u16,
+    merchant_id_hash: [u8; MERCHANT_ID_HASH_SIZE],
+) -> Result<()> {
+    check!(
+        !ctx.accounts.global_config.is_paused(ProtocolFeature::Spend),
+        PyraError::ProtocolPaused
+    );
+
+    check!(!ctx.accounts.vault.is_card_frozen, PyraError::CardFrozen);
+
+    check!(
+        hold_duration_in_seconds > 0 && hold_duration_in_seconds <= MAX_SPEND_HOLD_DURATION_SECONDS,
+        PyraError::InvalidSpendHold
+    );
+
+    let boost_usdc_base_units = process_spend_limits(
+        &mut ctx.accounts.vault,
+        &ctx.accounts.vault_merchant_controls.to_account_info(),
+        amount_usdc_base_units,
+        merchant_category_code,
+        &merchant_id_hash,
+    )?;
+
+    ctx.accounts.vault.add_spend_hold()?;
+
+    let current_timestamp_signed = Clock::get()?.unix_timestamp;
+    check!(current_timestamp_signed > 0, PyraError::InvalidTimestamp);
+    let current_timestamp =
+        u64::try_from(current_timestamp_signed).map_err(|_| PyraError::MathOverflow)?;
+    let expiry_timestamp = current_timestamp
+        .checked_add(hold_duration_in_seconds)
+        .ok_or(PyraError::MathOverflow)?;
+
+    let spend_hold = &mut ctx.accounts.spend_hold;
+    spend_hold.vault = ctx.accounts.vault.key();
+    spend_hold.bump = ctx.bumps.spend_hold;
+    spend_hold.authorization_id = authorization_id;
+    spend_hold.amount_usdc_base_units = amount_usdc_base_units;
+    spend_hold.expiry_timestamp = expiry_timestamp;
+    spend_hold.authorization_timestamp = current_timestamp;
+    spend_hold.timeframe_reset_timestamp = ctx.accounts.vault.next_timeframe_reset_timestamp;
+    spend_hold.window_reset_timestamps = ctx
+        .accounts
+        .vault
+        .spend_limit_windows
+        .map(|window| window.next_timeframe_reset_timestamp);
+    spend_hold.boost_usdc_base_units = boost_usdc_base_units;
+    spend_hold.boost_expiry_timestamp = ctx.accounts.vault.spend_limit_boost.expiry_timestamp;
+    spend_hold.merchant_category_code = merchant_category_code;
+    spend_hold.rent_payer = ctx.accounts.spend_caller.key();
+
+    emit_cpi!(SpendAuthorized {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        spend_hold: ctx.accounts.spend_hold.key(),
+        authorization_id,
+        amount_usdc_base_units,
+        expiry_timestamp,
+        remaining_spend_limit_per_timeframe: ctx.accounts.vault.remaining_spend_limit_per_timeframe,
+        merchant_category_code,
+        merchant_id_hash,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use crate::{
+    check,
+    config::{PyraError, MAX_SPEND_LIMIT_BOOST_DURATION_SECONDS, VAULT_VERSION},
+    events::SpendLimitsBoosted,
+    state::{DelegatePermission, GlobalConfig, ProtocolFeature, Vault, VaultDelegates},
+    utils::{validate_delegate_spend_limits, validate_vault_authority},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BoostSpendLimits<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Checked by the vault's has_one constraint
+    pub owner: UncheckedAccount<'info>,
+
+    // Owner or a delegate with the required permission
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        seeds = [b"vault_delegates".as_ref(), vault.key().as_ref()],
+        bump = vault_delegates.bump
+    )]
+    pub vault_delegates: Option<Box<Account<'info, VaultDelegates>>>,
+}
+
+/// Instantly raises the user's spend limits until the boost expires, after which spends fall back to the base limits. No time lock is required, as the expiry is known when the boost is set.
+pub fn boost_spend_limits_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, BoostSpendLimits<'info>>,
+    additional_spend_limit_per_transaction: u64,
+    additional_spend_limit: u64,
+    duration_in_seconds: u64,
+) -> Result<()> {
+    check!(
+        !ctx.accounts.global_config.is_paused(ProtocolFeature::Spend),
+        PyraError::ProtocolPaused
+    );
+
+    check!(
+        duration_in_seconds > 0 && duration_in_seconds <= MAX_SPEND_LIMIT_BOOST_DURATION_SECONDS,
+        PyraError::InvalidSpendLimitBoost
+    );
+
+    let delegate = validate_vault_authority(
+        &ctx.accounts.vault,
+        &ctx.accounts.authority.key(),
+        &ctx.accounts.vault_delegates,
+        DelegatePermission::SpendLimits,
+    )?;
+
+    // Delegates' maximums apply to the boosted limits, and a replaced boost's total includes what was already drawn
+    validate_delegate_spend_limits(
+        delegate,
+        ctx.accounts
+            .vault
+            .spend_limit_per_transaction
+            .saturating_add(additional_spend_limit_per_transaction),
+        ctx.accounts
+            .vault
+            .spend_limit_per_timeframe
+            .saturating_add(additional_spend_limit),
+        &[],
+    )?;
+
+    let current_timestamp_signed = Clock::get()?.unix_timestamp;
+    check!(current_timestamp_signed > 0, PyraError::InvalidTimestamp);
+    let current_timestamp =
+        u64::try_from(current_timestamp_signed).map_err(|_| PyraError::MathOverflow)?;
+
+    let spend_limit_boost = ctx.accounts.vault.spend_limit_boost.get_replacement(
+        additional_spend_limit_per_transaction,
+        additional_spend_limit,
+        current_timestamp
+            .checked_add(duration_in_seconds)
+            .ok_or(PyraError::MathOverflow)?,
+        current_timestamp,
+    );
+
+    // Replacing an active boost must not lower it, as decreases are time locked
+    check!(
+        spend_limit_boost.is_loosening(&ctx.accounts.vault.spend_limit_boost, current_timestamp),
+        PyraError::IllegalSpendLimitDecrease
+    );
+
+    ctx.accounts.vault.spend_limit_boost = spend_limit_boost;
+
+    emit_cpi!(SpendLimitsBoosted {
+        owner: ctx.accounts.owner.key(),
+        authority: ctx.accounts.authority.key(),
+        vault: ctx.accounts.vault.key(),
+        additional_spend_limit_per_transaction,
+        additional_spend_limit,
+        remaining_additional_spend_limit: spend_limit_boost.remaining_additional_spend_limit,
+        expiry_timestamp: spend_limit_boost.expiry_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::UnfreezeCardCancelled,
+    state::{UnfreezeCardOrder, Vault},
+    utils::close_time_lock,
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelUnfreezeCard<'info> {
+    #[account(mut)]
+    pub unfreeze_card_order: Box<Account<'info, UnfreezeCardOrder>>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // No has_one, so orders left over from a previous owner can still be cancelled
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+/// Cancels a pending unfreeze card order, keeping the card frozen
+pub fn cancel_unfreeze_card_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelUnfreezeCard<'info>>,
+) -> Result<()> {
+    check!(
+        ctx.accounts
+            .unfreeze_card_order
+            .time_lock
+            .owner
+            .eq(&ctx.accounts.owner.key()),
+        PyraError::InvalidTimeLockOwner
+    );
+
+    check!(
+        ctx.accounts
+            .unfreeze_card_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidUnfreezeCardOrder
+    );
+
+    close_time_lock(
+        &ctx.accounts.unfreeze_card_order,
+        &ctx.accounts.time_lock_rent_payer.to_account_info(),
+    )?;
+
+    ctx.accounts
+        .vault
+        .remove_open_order(&ctx.accounts.unfreeze_card_order.time_lock);
+
+    emit_cpi!(UnfreezeCardCancelled {
+        owner: ctx.accounts.owner.key(),
+        unfreeze_card_order: ctx.accounts.unfreeze_card_order.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use crate::{
+    check,
+    config::{PyraError, ANCHOR_DISCRIMINATOR, VAULT_VERSION},
+    events::SpendCaptured,
+    state::{GlobalConfig, ProtocolFeature, SpendHold, Vault},
+    utils::release_spend_hold,
+};
+use anchor_lang::{
+    prelude::*,
+    solana_program::sysvar::instructions::{
+        self, load_current_index_checked, load_instruction_at_checked,
+    },
+    Discriminator,
+};
+use solana_program::instruction::{get_stack_height, Instruction};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CaptureSpend<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Can be any account, once it has a Vault
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = spend_caller.key().eq(&global_config.spend_caller) @ PyraError::InvalidSpendCaller
+    )]
+    pub spend_caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"spend_hold".as_ref(), vault.key().as_ref(), spend_hold.authorization_id.to_le_bytes().as_ref()],
+        bump = spend_hold.bump,
+        has_one = vault @ PyraError::InvalidSpendHold
+    )]
+    pub spend_hold: Box<Account<'info, SpendHold>>,
+
+    /// CHECK: Safe once address is correct
+    #[account(
+        mut,
+        address = spend_hold.rent_payer @ PyraError::InvalidSpendHold
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    /// CHECK: Checked in handler as the account doesn't need to exist, seeds ensure the vault's controls can't be skipped
+    #[account(
+        mut,
+        seeds = [b"vault_merchant_controls".as_ref(), vault.key().as_ref()],
+        bump
+    )]
+    pub vault_merchant_controls: UncheckedAccount<'info>,
+
+    /// CHECK: Account is safe once address is correct
+    #[account(address = instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Settles all or part of a spend hold, must be followed by start_spend and complete_spend for the captured amount. The final capture releases any uncaptured amount and closes the hold.
+pub fn capture_spend_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CaptureSpend<'info>>,
+    amount_usdc_base_units: u64,
+    is_final_capture: bool,
+) -> Result<()> {
+    check!(
+        !ctx.accounts.global_config.is_paused(ProtocolFeature::Spend),
+        PyraError::ProtocolPaused
+    );
+
+    let index: usize =
+        load_current_index_checked(&ctx.accounts.instructions.to_account_info())?.into();
+    let current_instruction =
+        load_instruction_at_checked(index, &ctx.accounts.instructions.to_account_info())?;
+    let start_spend_instruction =
+        load_instruction_at_checked(index + 1, &ctx.accounts.instructions.to_account_info())?;
+    validate_start_spend_capture_ix(
+        &current_instruction,
+        &start_spend_instruction,
+        &ctx.accounts.vault.key(),
+        amount_usdc_base_units,
+    )?;
+
+    let current_timestamp_signed = Clock::get()?.unix_timestamp;
+    check!(current_timestamp_signed > 0, PyraError::InvalidTimestamp);
+    let current_timestamp =
+        u64::try_from(current_timestamp_signed).map_err(|_| PyraError::MathOverflow)?;
+    check!(
+        !ctx.accounts.spend_hold.is_expired(current_timestamp),
+        PyraError::SpendHoldExpired
+    );
+
+    ctx.accounts.vault.record_capture(current_timestamp)?;
+
+    // Merchants can capture less than they authorized, the final capture returns the rest to the spend limits
+    let is_hold_closed = ctx
+        .accounts
+        .spend_hold
+        .capture(amount_usdc_base_units, is_final_capture)?;
+    let released_usdc_base_units = ctx.accounts.spend_hold.amount_usdc_base_units;
+    let mut spend_limit_restored = 0;
+    if is_hold_closed {
+        spend_limit_restored = release_spend_hold(
+            &mut ctx.accounts.spend_hold,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.vault_merchant_controls.to_account_info(),
+            current_timestamp,
+        )?;
+        ctx.accounts.vault.remove_spend_hold();
+        ctx.accounts
+            .spend_hold
+            .close(ctx.accounts.rent_payer.to_account_info())?;
+    }
+
+    emit_cpi!(SpendCaptured {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        spend_hold: ctx.accounts.spend_hold.key(),
+        authorization_id: ctx.accounts.spend_hold.authorization_id,
+        amount_usdc_base_units,
+        remaining_hold_usdc_base_units: ctx.accounts.spend_hold.amount_usdc_base_units,
+        is_hold_closed,
+        released_usdc_base_units: if is_hold_closed {
+            released_usdc_base_units
+        } else {
+            0
+        },
+        spend_limit_restored,
+    });
+
+    Ok(())
+}
+
+#[inline(never)]
+fn validate_start_spend_capture_ix(
+    current_instruction: &Instruction,
+    start_spend: &Instruction,
+    vault: &Pubkey,
+    amount_usdc_base_units: u64,
+) -> Result<()> {
+    // Ensure we're not in a CPI (to validate introspection)
+    const TOP_LEVEL_STACK_HEIGHT: usize = 1;
+    check!(
+        get_stack_height() == TOP_LEVEL_STACK_HEIGHT,
+        PyraError::IllegalCaptureSpendInstructions
+    );
+    check!(
+        current_instruction.program_id.eq(&crate::id()),
+        PyraError::IllegalCaptureSpendInstructions
+    );
+
+    // Next instruction must be start_spend, which checks it's followed by complete_spend
+    check!(
+        start_spend.program_id.eq(&crate::id()),
+        PyraError::IllegalCaptureSpendInstructions
+    );
+    check!(
+        start_spend.data[..ANCHOR_DISCRIMINATOR].eq(&crate::instruction::StartSpend::DISCRIMINATOR),
+        PyraError::IllegalCaptureSpendInstructions
+    );
+
+    // start_spend skips the spend limits when it follows a capture, so it must spend the captured amount from this vault
+    check!(
+        start_spend.accounts[0].pubkey.eq(vault),
+        PyraError::InvalidUserAccounts
+    );
+    let start_spend_amount_usdc_base_units = u64::from_le_bytes(
+        start_spend.data[ANCHOR_DISCRIMINATOR..ANCHOR_DISCRIMINATOR + 8]
+            .try_into()
+            .map_err(|_| PyraError::IllegalCaptureSpendInstructions)?,
+    );
+    check!(
+        start_spend_amount_usdc_base_units == amount_usdc_base_units,
+        PyraError::IllegalCaptureSpendInstructions
+    );
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::{
+    check,
+    config::PyraError,
+    events::SpendRecordClosed,
+    state::{SpendRecord, Vault},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseSpendRecord<'info> {
+    pub caller: Signer<'info>,
+
+    /// CHECK: Only read if it's still open, as the vault may have been closed since the spend
+    pub vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"spend_record".as_ref(), vault.key().as_ref(), spend_record.cctp_nonce.to_le_bytes().as_ref()],
+        bump = spend_record.bump,
+        has_one = vault @ PyraError::InvalidSpendRecord,
+        close = rent_payer
+    )]
+    pub spend_record: Box<Account<'info, SpendRecord>>,
+
+    /// CHECK: Safe once address is correct
+    #[account(
+        mut,
+        address = spend_record.rent_payer @ PyraError::InvalidSpendRecord
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+}
+
+/// Closes a spend record once refunds of it can no longer restore any spend limit. Permissionless, as the rent returns to the spend caller that paid it.
+pub fn close_spend_record_handler(ctx: Context<CloseSpendRecord>) -> Result<()> {
+    let current_timestamp_signed = Clock::get()?.unix_timestamp;
+    check!(current_timestamp_signed > 0, PyraError::InvalidTimestamp);
+    let current_timestamp =
+        u64::try_from(current_timestamp_signed).map_err(|_| PyraError::MathOverflow)?;
+
+    let spend_record = &ctx.accounts.spend_record;
+    let vault_info = ctx.accounts.vault.to_account_info();
+    if spend_record.amount_usdc_base_units > 0 && vault_info.owner.eq(&crate::ID) {
+        let vault = Vault::try_deserialize(&mut &vault_info.try_borrow_data()?[..])?;
+        check!(
+            !vault.has_current_spend_period(
+                spend_record.spend_timestamp,
+                spend_record.timeframe_reset_timestamp,
+                spend_record.window_reset_timestamps,
+                current_timestamp,
+            ),
+            PyraError::SpendRecordStillRefundable
+        );
+    }
+
+    emit_cpi!(SpendRecordClosed {
+        vault: ctx.accounts.vault.key(),
+        spend_record: spend_record.key(),
+        caller: ctx.accounts.caller.key(),
+        cctp_nonce: spend_record.cctp_nonce,
+        unrefunded_usdc_base_units: spend_record.amount_usdc_base_units,
+    });
+
+    Ok(())
+}
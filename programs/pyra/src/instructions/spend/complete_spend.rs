@@ -1,11 +1,12 @@
 use crate::{
     check,
-    config::{
-        PyraError, ANCHOR_DISCRIMINATOR, DOMAIN_BASE, PROVIDER_BASE_ADDRESS,
-        PYRA_CALLER_BASE_ADDRESS, SPEND_CALLER, USDC_MARKET_INDEX,
+    config::{PyraError, ANCHOR_DISCRIMINATOR, USDC_MINT, VAULT_VERSION},
+    events::SpendCompleted,
+    state::{
+        GlobalConfig, ProtocolFeature, RentPayer, RentTreasury, SettlementProviderRegistry,
+        SpendRecord, Vault,
     },
-    state::Vault,
-    utils::{evm_address_to_solana, get_drift_market},
+    utils::create_pda_account,
 };
 use anchor_lang::{
     prelude::*,
@@ -26,11 +27,14 @@ use token_messenger_minter::{
     token_messenger::DepositForBurnWithCallerParams,
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CompleteSpend<'info> {
     #[account(
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
-        bump = vault.bump
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
     )]
     pub vault: Box<Account<'info, Vault>>,
 
@@ -39,19 +43,23 @@ pub struct CompleteSpend<'info> {
 
     #[account(
         mut,
-        constraint = spend_caller.key().eq(&SPEND_CALLER) @ PyraError::InvalidSpendCaller
+        constraint = spend_caller.key().eq(&global_config.spend_caller) @ PyraError::InvalidSpendCaller
     )]
     pub spend_caller: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"spend_mule".as_ref(), owner.key().as_ref()],
+        seeds = [b"spend_mule".as_ref(), vault.key().as_ref()],
         bump,
         token::mint = usdc_mint,
         token::authority = vault
     )]
     pub mule: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// CHECK: Created in the handler once the CCTP nonce is known, which checks its address
+    #[account(mut)]
+    pub spend_record: UncheckedAccount<'info>,
+
     #[account(mut)] // Mutable for burn
     pub usdc_mint: Box<InterfaceAccount<'info, Mint>>,
 
@@ -87,7 +95,7 @@ pub struct CompleteSpend<'info> {
     pub message_sent_event_data: Signer<'info>,
 
     /// CHECK: This account is passed through to the Circle CPI, which performs the security checks
-    pub event_authority: UncheckedAccount<'info>,
+    pub token_messenger_event_authority: UncheckedAccount<'info>,
 
     pub message_transmitter_program: Program<'info, MessageTransmitter>,
 
@@ -102,12 +110,36 @@ pub struct CompleteSpend<'info> {
     pub instructions: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"rent_treasury"],
+        bump = rent_treasury.bump
+    )]
+    pub rent_treasury: Box<Account<'info, RentTreasury>>,
+
+    #[account(
+        seeds = [b"settlement_provider_registry"],
+        bump = settlement_provider_registry.bump
+    )]
+    pub settlement_provider_registry: Box<Account<'info, SettlementProviderRegistry>>,
 }
 
 /// Second spend instruction (split due to stack size limits), bridges USDC to card partner's address on Base
 pub fn complete_spend_handler<'info>(
     ctx: Context<'_, '_, '_, 'info, CompleteSpend<'info>>,
 ) -> Result<()> {
+    check!(
+        !ctx.accounts.global_config.is_paused(ProtocolFeature::Spend),
+        PyraError::ProtocolPaused
+    );
+
     let index: usize =
         load_current_index_checked(&ctx.accounts.instructions.to_account_info())?.into();
     let current_instruction =
@@ -117,9 +149,8 @@ pub fn complete_spend_handler<'info>(
     validate_start_spend_ix(&current_instruction, &start_instruction)?;
 
     // Validate USDC mint
-    let drift_market = get_drift_market(USDC_MARKET_INDEX)?;
     check!(
-        &ctx.accounts.usdc_mint.key().eq(&drift_market.mint),
+        &ctx.accounts.usdc_mint.key().eq(&USDC_MINT),
         PyraError::InvalidMint
     );
 
@@ -127,7 +158,8 @@ pub fn complete_spend_handler<'info>(
     let bridge_rent_payer_bump = ctx.bumps.bridge_rent_payer;
     let vault_bump = ctx.accounts.vault.bump;
     let owner = ctx.accounts.owner.key();
-    let vault_seeds = &[b"vault", owner.as_ref(), &[vault_bump]];
+    let vault_id = ctx.accounts.vault.vault_id;
+    let vault_seeds = &[b"vault", vault_id.as_ref(), &[vault_bump]];
     let bridge_rent_payer_seeds = &[b"bridge_rent_payer".as_ref(), &[bridge_rent_payer_bump]];
     let signer_seeds_bridge_rent_payer = &[&bridge_rent_payer_seeds[..], &vault_seeds[..]];
 
@@ -154,7 +186,10 @@ pub fn complete_spend_handler<'info>(
                 .to_account_info(),
             token_program: ctx.accounts.token_program.to_account_info(),
             system_program: ctx.accounts.system_program.to_account_info(),
-            event_authority: ctx.accounts.event_authority.to_account_info(),
+            event_authority: ctx
+                .accounts
+                .token_messenger_event_authority
+                .to_account_info(),
             program: ctx
                 .accounts
                 .token_messenger_minter_program
@@ -163,18 +198,81 @@ pub fn complete_spend_handler<'info>(
         signer_seeds_bridge_rent_payer,
     );
 
-    let provider_base_address_solana = evm_address_to_solana(PROVIDER_BASE_ADDRESS)?;
-    let pyra_caller_base_address_solana = evm_address_to_solana(PYRA_CALLER_BASE_ADDRESS)?;
     let amount_usdc_base_units = ctx.accounts.mule.amount;
 
+    // Route the spend to the card provider the vault is bound to
+    let settlement_provider_id = ctx.accounts.vault.settlement_provider_id;
+    let settlement_provider = ctx
+        .accounts
+        .settlement_provider_registry
+        .get_enabled_provider(settlement_provider_id)?;
+    let destination_domain = settlement_provider.destination_domain;
+    let mint_recipient = settlement_provider.mint_recipient;
+
     let bridge_cpi_params = DepositForBurnWithCallerParams {
         amount: amount_usdc_base_units,
-        destination_domain: DOMAIN_BASE,
-        mint_recipient: provider_base_address_solana,
-        destination_caller: pyra_caller_base_address_solana,
+        destination_domain,
+        mint_recipient,
+        destination_caller: settlement_provider.destination_caller,
     };
 
-    deposit_for_burn_with_caller(bridge_cpi_ctx, bridge_cpi_params)?;
+    let lamports_before_bridge = ctx.accounts.bridge_rent_payer.lamports();
+    let cctp_nonce = deposit_for_burn_with_caller(bridge_cpi_ctx, bridge_cpi_params)?.get();
+    let bridge_rent_paid =
+        lamports_before_bridge.saturating_sub(ctx.accounts.bridge_rent_payer.lamports());
+    ctx.accounts
+        .rent_treasury
+        .add_obligations(RentPayer::Bridge, bridge_rent_paid)?;
+
+    // Record the spend under its CCTP nonce, which is only known once it's bridged, so refunds can find it
+    let current_timestamp_signed = Clock::get()?.unix_timestamp;
+    check!(current_timestamp_signed > 0, PyraError::InvalidTimestamp);
+    let current_timestamp =
+        u64::try_from(current_timestamp_signed).map_err(|_| PyraError::MathOverflow)?;
+
+    let vault_key = ctx.accounts.vault.key();
+    let cctp_nonce_bytes = cctp_nonce.to_le_bytes();
+    let (spend_record_address, spend_record_bump) = Pubkey::find_program_address(
+        &[
+            b"spend_record",
+            vault_key.as_ref(),
+            cctp_nonce_bytes.as_ref(),
+        ],
+        &crate::ID,
+    );
+    check!(
+        ctx.accounts.spend_record.key().eq(&spend_record_address),
+        PyraError::InvalidSpendRecord
+    );
+
+    create_pda_account(
+        &ctx.accounts.spend_caller,
+        &ctx.accounts.spend_record.to_account_info(),
+        &[
+            b"spend_record",
+            vault_key.as_ref(),
+            cctp_nonce_bytes.as_ref(),
+            &[spend_record_bump],
+        ],
+        &ctx.accounts.system_program,
+        SpendRecord::INIT_SPACE,
+    )?;
+
+    let spend_record = SpendRecord {
+        vault: vault_key,
+        bump: spend_record_bump,
+        cctp_nonce,
+        amount_usdc_base_units,
+        spend_timestamp: current_timestamp,
+        timeframe_reset_timestamp: ctx.accounts.vault.next_timeframe_reset_timestamp,
+        window_reset_timestamps: ctx
+            .accounts
+            .vault
+            .spend_limit_windows
+            .map(|window| window.next_timeframe_reset_timestamp),
+        rent_payer: ctx.accounts.spend_caller.key(),
+    };
+    spend_record.try_serialize(&mut &mut ctx.accounts.spend_record.try_borrow_mut_data()?[..])?;
 
     // Close spend mule
     let signer_seeds_vault = &[&vault_seeds[..]];
@@ -190,6 +288,16 @@ pub fn complete_spend_handler<'info>(
     );
     close_account(cpi_ctx_close)?;
 
+    emit_cpi!(SpendCompleted {
+        owner,
+        vault: ctx.accounts.vault.key(),
+        amount_usdc_base_units,
+        settlement_provider_id,
+        destination_domain,
+        mint_recipient,
+        cctp_nonce,
+    });
+
     Ok(())
 }
 
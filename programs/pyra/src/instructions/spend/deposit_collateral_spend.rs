@@ -0,0 +1,286 @@
+use crate::{
+    check,
+    config::{PyraError, ANCHOR_DISCRIMINATOR, USDC_MARKET_INDEX, USDC_MINT, VAULT_VERSION},
+    events::CollateralSpendDeposited,
+    state::{CollateralSpendLedger, DriftMarketRegistry, GlobalConfig, ProtocolFeature, Vault},
+    utils::{get_drift_market, validate_swap_prices, SwapLeg},
+};
+use anchor_lang::{prelude::*, Discriminator};
+use anchor_spl::token_interface::{
+    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+    TransferChecked,
+};
+use drift::{
+    cpi::{accounts::Deposit as DriftDeposit, deposit as drift_deposit},
+    program::Drift,
+};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use solana_program::{
+    instruction::{get_stack_height, Instruction},
+    sysvar::instructions::{self, load_current_index_checked, load_instruction_at_checked},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DepositCollateralSpend<'info> {
+    #[account(
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Can be any account, once it has a Vault
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = spend_caller.key().eq(&global_config.spend_caller) @ PyraError::InvalidSpendCaller
+    )]
+    pub spend_caller: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = usdc_mint,
+        token::authority = spend_caller
+    )]
+    pub caller_usdc: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"collateral_spend_mule".as_ref(), vault.key().as_ref(), usdc_mint.key().as_ref()],
+        bump,
+        payer = spend_caller,
+        token::mint = usdc_mint,
+        token::authority = vault
+    )]
+    pub mule: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub usdc_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Seeds ensure spends are funded by the spend sub-account, the Drift CPI performs the remaining checks
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), vault.key().as_ref(), vault.spend_sub_account_id.to_le_bytes().as_ref()],
+        seeds::program = drift_program.key(),
+        bump
+    )]
+    pub drift_user: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    #[account(mut)]
+    pub drift_user_stats: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    #[account(mut)]
+    pub drift_state: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    #[account(mut)]
+    pub spot_market_vault: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub drift_program: Program<'info, Drift>,
+
+    pub system_program: Program<'info, System>,
+
+    pub usdc_price_update: Box<Account<'info, PriceUpdateV2>>,
+
+    pub collateral_price_update: Box<Account<'info, PriceUpdateV2>>,
+
+    /// CHECK: Account is safe once address is correct
+    #[account(address = instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_spend_ledger".as_ref(), vault.key().as_ref()],
+        bump,
+        close = spend_caller
+    )]
+    pub ledger: Box<Account<'info, CollateralSpendLedger>>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        seeds = [b"drift_market_registry"],
+        bump = drift_market_registry.bump
+    )]
+    pub drift_market_registry: Box<Account<'info, DriftMarketRegistry>>,
+}
+
+/// Second collateral spend instruction, takes place after the swap. Deposits the USDC received for the collateral into the spend sub-account, checking the swap is within slippage of the oracle prices.
+pub fn deposit_collateral_spend_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositCollateralSpend<'info>>,
+) -> Result<()> {
+    check!(
+        !ctx.accounts.global_config.is_paused(ProtocolFeature::Spend),
+        PyraError::ProtocolPaused
+    );
+
+    check!(
+        &ctx.accounts.usdc_mint.key().eq(&USDC_MINT),
+        PyraError::InvalidMint
+    );
+
+    let index: usize =
+        load_current_index_checked(&ctx.accounts.instructions.to_account_info())?.into();
+    let current_instruction =
+        load_instruction_at_checked(index, &ctx.accounts.instructions.to_account_info())?;
+    let start_instruction = load_instruction_at_checked(
+        index
+            .checked_sub(2)
+            .ok_or(PyraError::IllegalCollateralSpendInstructions)?,
+        &ctx.accounts.instructions.to_account_info(),
+    )?;
+    validate_start_collateral_spend_ix(&current_instruction, &start_instruction)?;
+
+    // Calculate USDC received from the swap
+    let amount_usdc_base_units = ctx
+        .accounts
+        .caller_usdc
+        .amount
+        .checked_sub(ctx.accounts.ledger.starting_usdc_balance)
+        .ok_or(PyraError::MathOverflow)?;
+
+    // The swap must fund the whole spend, so start_spend doesn't borrow USDC
+    let start_spend_instruction =
+        load_instruction_at_checked(index + 1, &ctx.accounts.instructions.to_account_info())?;
+    check!(
+        start_spend_instruction.data.len() >= ANCHOR_DISCRIMINATOR + 8,
+        PyraError::IllegalCollateralSpendInstructions
+    );
+    let spend_amount_usdc_base_units = u64::from_le_bytes(
+        start_spend_instruction.data[ANCHOR_DISCRIMINATOR..ANCHOR_DISCRIMINATOR + 8]
+            .try_into()
+            .map_err(|_| PyraError::IllegalCollateralSpendInstructions)?,
+    );
+    check!(
+        amount_usdc_base_units >= spend_amount_usdc_base_units,
+        PyraError::InsufficientCollateralSpendSwap
+    );
+
+    // Validate values of USDC received and collateral sold are within slippage
+    let collateral_market_index = ctx.accounts.ledger.collateral_market_index;
+    let collateral_amount_base_units = ctx.accounts.ledger.collateral_amount_base_units;
+    let usdc_market = get_drift_market(&ctx.accounts.drift_market_registry, USDC_MARKET_INDEX)?;
+    let collateral_market =
+        get_drift_market(&ctx.accounts.drift_market_registry, collateral_market_index)?;
+
+    let (usdc_price, collateral_price) = validate_swap_prices(
+        SwapLeg {
+            amount_base_units: amount_usdc_base_units,
+            market: usdc_market,
+            price_update: &ctx.accounts.usdc_price_update,
+        },
+        SwapLeg {
+            amount_base_units: collateral_amount_base_units,
+            market: collateral_market,
+            price_update: &ctx.accounts.collateral_price_update,
+        },
+        ctx.accounts.global_config.pyth_max_price_age_seconds,
+        ctx.accounts.global_config.collateral_spend_max_slippage_bps,
+    )?;
+
+    // Transfer USDC from caller's ATA to mule
+    transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.caller_usdc.to_account_info(),
+                to: ctx.accounts.mule.to_account_info(),
+                authority: ctx.accounts.spend_caller.to_account_info(),
+                mint: ctx.accounts.usdc_mint.to_account_info(),
+            },
+        ),
+        amount_usdc_base_units,
+        ctx.accounts.usdc_mint.decimals,
+    )?;
+
+    // Drift Deposit CPI
+    let vault_id = ctx.accounts.vault.vault_id;
+    let vault_seeds = &[b"vault", vault_id.as_ref(), &[ctx.accounts.vault.bump]];
+    let signer_seeds_vault = &[&vault_seeds[..]];
+
+    let mut cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.drift_program.to_account_info(),
+        DriftDeposit {
+            state: ctx.accounts.drift_state.to_account_info(),
+            user: ctx.accounts.drift_user.to_account_info(),
+            user_stats: ctx.accounts.drift_user_stats.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+            spot_market_vault: ctx.accounts.spot_market_vault.to_account_info(),
+            user_token_account: ctx.accounts.mule.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+        signer_seeds_vault,
+    );
+
+    cpi_ctx.remaining_accounts = ctx.remaining_accounts.to_vec();
+
+    // reduce_only = false so the USDC becomes collateral for start_spend to withdraw
+    drift_deposit(cpi_ctx, USDC_MARKET_INDEX, amount_usdc_base_units, false)?;
+
+    // Close mule
+    let cpi_ctx_close = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.mule.to_account_info(),
+            destination: ctx.accounts.spend_caller.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        },
+        signer_seeds_vault,
+    );
+    close_account(cpi_ctx_close)?;
+
+    emit_cpi!(CollateralSpendDeposited {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        caller: ctx.accounts.spend_caller.key(),
+        collateral_market_index,
+        collateral_amount_base_units,
+        amount_usdc_base_units,
+        collateral_price: collateral_price.price,
+        collateral_price_exponent: collateral_price.exponent,
+        usdc_price: usdc_price.price,
+        usdc_price_exponent: usdc_price.exponent,
+    });
+
+    Ok(())
+}
+
+#[inline(never)]
+fn validate_start_collateral_spend_ix(
+    current_instruction: &Instruction,
+    start_collateral_spend: &Instruction,
+) -> Result<()> {
+    // Ensure we're not in a CPI (to validate introspection)
+    const TOP_LEVEL_STACK_HEIGHT: usize = 1;
+    check!(
+        get_stack_height() == TOP_LEVEL_STACK_HEIGHT,
+        PyraError::IllegalCollateralSpendInstructions
+    );
+    check!(
+        current_instruction.program_id.eq(&crate::id()),
+        PyraError::IllegalCollateralSpendInstructions
+    );
+
+    // Start instruction validates the rest of the instruction order
+    check!(
+        start_collateral_spend.program_id.eq(&crate::id()),
+        PyraError::IllegalCollateralSpendInstructions
+    );
+    check!(
+        start_collateral_spend.data[..ANCHOR_DISCRIMINATOR]
+            .eq(&crate::instruction::StartCollateralSpend::DISCRIMINATOR),
+        PyraError::IllegalCollateralSpendInstructions
+    );
+
+    Ok(())
+}
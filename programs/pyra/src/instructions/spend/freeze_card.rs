@@ -0,0 +1,39 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::CardFrozen,
+    state::Vault,
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FreezeCard<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    pub owner: Signer<'info>,
+}
+
+/// Instantly blocks all spends from the vault. Unfreezing goes through a time locked order.
+pub fn freeze_card_handler(ctx: Context<FreezeCard>) -> Result<()> {
+    check!(
+        !ctx.accounts.vault.is_card_frozen,
+        PyraError::CardAlreadyFrozen
+    );
+
+    ctx.accounts.vault.is_card_frozen = true;
+
+    emit_cpi!(CardFrozen {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::MerchantControlsUpdated,
+    state::{MerchantControlsOrder, Vault, VaultMerchantControls},
+    utils::{close_time_lock, validate_vault_time_lock},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FulfilMerchantControls<'info> {
+    #[account(mut)]
+    pub merchant_controls_order: Box<Account<'info, MerchantControlsOrder>>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Any account, once it has a vault (order checked in handler)
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_merchant_controls".as_ref(), vault.key().as_ref()],
+        bump = vault_merchant_controls.bump
+    )]
+    pub vault_merchant_controls: Box<Account<'info, VaultMerchantControls>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless function to fulfil a merchant controls order, replacing the vault's merchant controls
+pub fn fulfil_merchant_controls_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, FulfilMerchantControls<'info>>,
+) -> Result<()> {
+    check!(
+        ctx.accounts
+            .merchant_controls_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidMerchantControlsOrder
+    );
+
+    validate_vault_time_lock(
+        &ctx.accounts.vault,
+        &ctx.accounts.merchant_controls_order.time_lock,
+    )?;
+
+    let merchant_controls = ctx
+        .accounts
+        .merchant_controls_order
+        .merchant_controls
+        .clone();
+
+    close_time_lock(
+        &ctx.accounts.merchant_controls_order,
+        &ctx.accounts.time_lock_rent_payer.to_account_info(),
+    )?;
+
+    ctx.accounts
+        .vault_merchant_controls
+        .set_controls(merchant_controls.clone());
+
+    ctx.accounts
+        .vault
+        .remove_open_order(&ctx.accounts.merchant_controls_order.time_lock);
+
+    emit_cpi!(MerchantControlsUpdated {
+        owner: ctx.accounts.owner.key(),
+        authority: ctx.accounts.caller.key(),
+        vault: ctx.accounts.vault.key(),
+        merchant_controls,
+        is_time_locked: true,
+    });
+
+    Ok(())
+}
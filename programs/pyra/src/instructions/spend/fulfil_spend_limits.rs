@@ -1,6 +1,9 @@
 use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::SpendLimitsUpdated,
     state::{SpendLimitsOrder, Vault},
-    utils::{close_time_lock, validate_time_lock},
+    utils::{close_time_lock, get_spend_limit_window_calendar_periods, validate_vault_time_lock},
 };
 use anchor_lang::prelude::*;
 
@@ -18,8 +21,10 @@ pub struct FulfilSpendLimits<'info> {
 
     #[account(
         mut,
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
-        bump,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
     )]
     pub vault: Box<Account<'info, Vault>>,
 
@@ -31,14 +36,35 @@ pub struct FulfilSpendLimits<'info> {
 
 /// Permissionless function to fulfil a spend limits order, updating the vault's spend limits
 pub fn fulfil_spend_limits_handler<'info>(
-    ctx: Context<'_, '_, '_, 'info, FulfilSpendLimits<'info>>,
+    mut ctx: Context<'_, '_, '_, 'info, FulfilSpendLimits<'info>>,
 ) -> Result<()> {
-    let (
+    check!(
+        ctx.accounts
+            .spend_limits_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidSpendLimitsOrder
+    );
+
+    let mut spend_limits_order = get_order_data(&mut ctx)?;
+    if spend_limits_order.is_delegate_order {
+        ctx.accounts
+            .vault
+            .keep_spend_limit_timing(&mut spend_limits_order);
+    }
+
+    let SpendLimitsOrder {
         spend_limit_per_transaction,
         spend_limit_per_timeframe,
         timeframe_in_seconds,
         next_timeframe_reset_timestamp,
-    ) = get_order_data(&ctx)?;
+        spend_limit_windows,
+        spend_limit_mode,
+        utc_offset_seconds,
+        spend_velocity_limits,
+        calendar_period,
+        ..
+    } = spend_limits_order;
 
     let spend_limit_per_timeframe_already_used = ctx
         .accounts
@@ -55,35 +81,56 @@ pub fn fulfil_spend_limits_handler<'info>(
     ctx.accounts.vault.spend_limit_per_timeframe = spend_limit_per_timeframe;
     ctx.accounts.vault.timeframe_in_seconds = timeframe_in_seconds;
     ctx.accounts.vault.next_timeframe_reset_timestamp = next_timeframe_reset_timestamp;
+    ctx.accounts.vault.spend_limit_windows = ctx
+        .accounts
+        .vault
+        .get_updated_spend_limit_windows(&spend_limit_windows)?;
+    ctx.accounts.vault.spend_limit_window_calendar_periods =
+        get_spend_limit_window_calendar_periods(&spend_limit_windows);
+    ctx.accounts.vault.spend_limit_mode = spend_limit_mode;
+    ctx.accounts.vault.utc_offset_seconds = utc_offset_seconds;
+    ctx.accounts.vault.spend_velocity_limits = spend_velocity_limits;
+    ctx.accounts.vault.calendar_period = calendar_period;
+
+    ctx.accounts
+        .vault
+        .remove_open_order(&ctx.accounts.spend_limits_order.time_lock);
+
+    emit_cpi!(SpendLimitsUpdated {
+        owner: ctx.accounts.owner.key(),
+        authority: ctx.accounts.caller.key(),
+        vault: ctx.accounts.vault.key(),
+        spend_limit_per_transaction,
+        spend_limit_per_timeframe,
+        remaining_spend_limit_per_timeframe: ctx.accounts.vault.remaining_spend_limit_per_timeframe,
+        timeframe_in_seconds,
+        next_timeframe_reset_timestamp,
+        spend_limit_windows: ctx.accounts.vault.spend_limit_windows,
+        spend_limit_mode,
+        utc_offset_seconds,
+        spend_velocity_limits,
+        calendar_period,
+        spend_limit_window_calendar_periods: ctx.accounts.vault.spend_limit_window_calendar_periods,
+        is_time_locked: true,
+    });
 
     Ok(())
 }
 
 fn get_order_data<'info>(
-    ctx: &Context<'_, '_, '_, 'info, FulfilSpendLimits<'info>>,
-) -> Result<(u64, u64, u64, u64)> {
-    validate_time_lock(
-        &ctx.accounts.owner.key(),
+    ctx: &mut Context<'_, '_, '_, 'info, FulfilSpendLimits<'info>>,
+) -> Result<SpendLimitsOrder> {
+    validate_vault_time_lock(
+        &ctx.accounts.vault,
         &ctx.accounts.spend_limits_order.time_lock,
     )?;
 
-    let spend_limit_per_transaction = ctx.accounts.spend_limits_order.spend_limit_per_transaction;
-    let spend_limit_per_timeframe = ctx.accounts.spend_limits_order.spend_limit_per_timeframe;
-    let timeframe_in_seconds = ctx.accounts.spend_limits_order.timeframe_in_seconds;
-    let next_timeframe_reset_timestamp = ctx
-        .accounts
-        .spend_limits_order
-        .next_timeframe_reset_timestamp;
+    let spend_limits_order = SpendLimitsOrder::clone(&ctx.accounts.spend_limits_order);
 
     close_time_lock(
         &ctx.accounts.spend_limits_order,
         &ctx.accounts.time_lock_rent_payer.to_account_info(),
     )?;
 
-    Ok((
-        spend_limit_per_transaction,
-        spend_limit_per_timeframe,
-        timeframe_in_seconds,
-        next_timeframe_reset_timestamp,
-    ))
+    Ok(spend_limits_order)
 }
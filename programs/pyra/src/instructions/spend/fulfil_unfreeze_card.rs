@@ -0,0 +1,72 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::CardUnfrozen,
+    state::{UnfreezeCardOrder, Vault},
+    utils::{close_time_lock, validate_vault_time_lock},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FulfilUnfreezeCard<'info> {
+    #[account(mut)]
+    pub unfreeze_card_order: Box<Account<'info, UnfreezeCardOrder>>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Any account, once it has a vault (order checked in handler)
+    pub owner: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless function to fulfil an unfreeze card order, allowing spends from the vault again
+pub fn fulfil_unfreeze_card_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, FulfilUnfreezeCard<'info>>,
+) -> Result<()> {
+    check!(
+        ctx.accounts
+            .unfreeze_card_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidUnfreezeCardOrder
+    );
+
+    validate_vault_time_lock(
+        &ctx.accounts.vault,
+        &ctx.accounts.unfreeze_card_order.time_lock,
+    )?;
+
+    close_time_lock(
+        &ctx.accounts.unfreeze_card_order,
+        &ctx.accounts.time_lock_rent_payer.to_account_info(),
+    )?;
+
+    ctx.accounts.vault.is_card_frozen = false;
+
+    ctx.accounts
+        .vault
+        .remove_open_order(&ctx.accounts.unfreeze_card_order.time_lock);
+
+    emit_cpi!(CardUnfrozen {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        unfreeze_card_order: ctx.accounts.unfreeze_card_order.key(),
+    });
+
+    Ok(())
+}
@@ -1,16 +1,48 @@
-use crate::{check, config::PyraError, state::Vault};
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::SpendLimitsUpdated,
+    state::{
+        DelegatePermission, GlobalConfig, ProtocolFeature, SpendLimitWindowParams,
+        SpendVelocityLimits, Vault, VaultDelegates,
+    },
+    utils::{
+        get_spend_limit_window_calendar_periods, validate_calendar_spend_limits,
+        validate_delegate_spend_limit_timing, validate_delegate_spend_limits,
+        validate_vault_authority,
+    },
+};
 use anchor_lang::prelude::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct IncreaseSpendLimits<'info> {
     #[account(
         mut,
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
-        bump = vault.bump
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
     )]
     pub vault: Box<Account<'info, Vault>>,
 
-    pub owner: Signer<'info>,
+    /// CHECK: Checked by the vault's has_one constraint
+    pub owner: UncheckedAccount<'info>,
+
+    // Owner or a delegate with the required permission
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        seeds = [b"vault_delegates".as_ref(), vault.key().as_ref()],
+        bump = vault_delegates.bump
+    )]
+    pub vault_delegates: Option<Box<Account<'info, VaultDelegates>>>,
 }
 
 /// Instantly updates the user's spend limits. No time lock is required if the spend limit is increasing.
@@ -20,7 +52,45 @@ pub fn increase_spend_limits_handler<'info>(
     spend_limit_per_timeframe: u64,
     timeframe_in_seconds: u64,
     next_timeframe_reset_timestamp: u64,
+    spend_limit_windows: Vec<SpendLimitWindowParams>,
+    spend_velocity_limits: SpendVelocityLimits,
 ) -> Result<()> {
+    check!(
+        !ctx.accounts.global_config.is_paused(ProtocolFeature::Spend),
+        PyraError::ProtocolPaused
+    );
+
+    let delegate = validate_vault_authority(
+        &ctx.accounts.vault,
+        &ctx.accounts.authority.key(),
+        &ctx.accounts.vault_delegates,
+        DelegatePermission::SpendLimits,
+    )?;
+    validate_delegate_spend_limits(
+        delegate,
+        spend_limit_per_transaction,
+        spend_limit_per_timeframe,
+        &spend_limit_windows,
+    )?;
+    validate_delegate_spend_limit_timing(
+        delegate,
+        &ctx.accounts.vault,
+        timeframe_in_seconds,
+        next_timeframe_reset_timestamp,
+        &spend_limit_windows,
+        &spend_velocity_limits,
+    )?;
+
+    // The mode and primary calendar period can only be changed by a time locked order, so the new timeframes must
+    // suit the current ones
+    validate_calendar_spend_limits(
+        ctx.accounts.vault.spend_limit_mode,
+        timeframe_in_seconds,
+        ctx.accounts.vault.calendar_period,
+        &spend_limit_windows,
+        ctx.accounts.vault.utc_offset_seconds,
+    )?;
+
     let starting_remaining_spend_limit_per_timeframe =
         ctx.accounts.vault.remaining_spend_limit_per_timeframe;
     let starting_spend_limit_per_transaction = ctx.accounts.vault.spend_limit_per_transaction;
@@ -46,6 +116,32 @@ pub fn increase_spend_limits_handler<'info>(
         PyraError::IllegalSpendLimitDecrease
     );
 
+    check!(
+        spend_velocity_limits.is_loosening(&ctx.accounts.vault.spend_velocity_limits),
+        PyraError::IllegalSpendLimitDecrease
+    );
+
+    // Windows can be removed or raised, but adding a window restricts spends so it must be time locked
+    let new_spend_limit_windows = ctx
+        .accounts
+        .vault
+        .get_updated_spend_limit_windows(&spend_limit_windows)?;
+    for new_window in new_spend_limit_windows
+        .iter()
+        .filter(|window| window.is_active())
+    {
+        let starting_window = ctx
+            .accounts
+            .vault
+            .get_spend_limit_window(new_window.timeframe_in_seconds)
+            .ok_or(PyraError::IllegalSpendLimitDecrease)?;
+        check!(
+            new_window.remaining_spend_limit_per_timeframe
+                >= starting_window.remaining_spend_limit_per_timeframe,
+            PyraError::IllegalSpendLimitDecrease
+        );
+    }
+
     // Assign new values
     ctx.accounts.vault.remaining_spend_limit_per_timeframe =
         new_remaining_spend_limit_per_timeframe;
@@ -53,6 +149,28 @@ pub fn increase_spend_limits_handler<'info>(
     ctx.accounts.vault.spend_limit_per_timeframe = spend_limit_per_timeframe;
     ctx.accounts.vault.timeframe_in_seconds = timeframe_in_seconds;
     ctx.accounts.vault.next_timeframe_reset_timestamp = next_timeframe_reset_timestamp;
+    ctx.accounts.vault.spend_limit_windows = new_spend_limit_windows;
+    ctx.accounts.vault.spend_limit_window_calendar_periods =
+        get_spend_limit_window_calendar_periods(&spend_limit_windows);
+    ctx.accounts.vault.spend_velocity_limits = spend_velocity_limits;
+
+    emit_cpi!(SpendLimitsUpdated {
+        owner: ctx.accounts.owner.key(),
+        authority: ctx.accounts.authority.key(),
+        vault: ctx.accounts.vault.key(),
+        spend_limit_per_transaction,
+        spend_limit_per_timeframe,
+        remaining_spend_limit_per_timeframe: ctx.accounts.vault.remaining_spend_limit_per_timeframe,
+        timeframe_in_seconds,
+        next_timeframe_reset_timestamp,
+        spend_limit_windows: new_spend_limit_windows,
+        spend_limit_mode: ctx.accounts.vault.spend_limit_mode,
+        utc_offset_seconds: ctx.accounts.vault.utc_offset_seconds,
+        spend_velocity_limits,
+        calendar_period: ctx.accounts.vault.calendar_period,
+        spend_limit_window_calendar_periods: ctx.accounts.vault.spend_limit_window_calendar_periods,
+        is_time_locked: false,
+    });
 
     Ok(())
 }
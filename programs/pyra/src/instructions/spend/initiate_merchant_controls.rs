@@ -0,0 +1,121 @@
+use crate::{
+    config::{PyraError, ANCHOR_DISCRIMINATOR, VAULT_VERSION},
+    events::MerchantControlsInitiated,
+    state::{
+        GlobalConfig, MerchantControls, MerchantControlsOrder, TimeLock, Vault,
+        VaultMerchantControls,
+    },
+    utils::{allocate_time_lock_owner_payer, allocate_time_lock_program_payer},
+};
+use anchor_lang::{prelude::*, Discriminator};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitiateMerchantControls<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub merchant_controls_order: Signer<'info>,
+
+    // Created here so the order can always be fulfilled
+    #[account(
+        init_if_needed,
+        seeds = [b"vault_merchant_controls".as_ref(), vault.key().as_ref()],
+        bump,
+        payer = owner,
+        space = VaultMerchantControls::INIT_SPACE
+    )]
+    pub vault_merchant_controls: Box<Account<'info, VaultMerchantControls>>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Creates a time locked order to replace the vault's merchant controls, which can be fulfilled permissionlessly once the time lock has expired
+pub fn initiate_merchant_controls_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitiateMerchantControls<'info>>,
+    merchant_controls: MerchantControls,
+) -> Result<()> {
+    merchant_controls.validate()?;
+
+    let vault_merchant_controls = &mut ctx.accounts.vault_merchant_controls;
+    vault_merchant_controls.vault = ctx.accounts.vault.key();
+    vault_merchant_controls.bump = ctx.bumps.vault_merchant_controls;
+
+    let is_owner_payer = ctx
+        .accounts
+        .time_lock_rent_payer
+        .key()
+        .eq(&ctx.accounts.owner.key());
+
+    if is_owner_payer {
+        allocate_time_lock_owner_payer(
+            &ctx.accounts.owner,
+            &ctx.accounts.merchant_controls_order,
+            &ctx.accounts.system_program,
+            MerchantControlsOrder::INIT_SPACE,
+        )?;
+    } else {
+        allocate_time_lock_program_payer(
+            &ctx.accounts.time_lock_rent_payer.to_account_info(),
+            &ctx.accounts.merchant_controls_order,
+            &ctx.accounts.system_program,
+            MerchantControlsOrder::INIT_SPACE,
+        )?;
+    }
+
+    let current_slot = Clock::get()?.slot;
+    let release_slot = current_slot
+        .checked_add(ctx.accounts.global_config.time_lock_duration_slots)
+        .ok_or(PyraError::MathOverflow)?;
+
+    let merchant_controls_order_data = MerchantControlsOrder {
+        time_lock: TimeLock {
+            owner: ctx.accounts.owner.key(),
+            owner_epoch: ctx.accounts.vault.owner_epoch,
+            is_owner_payer,
+            release_slot,
+        },
+        vault: ctx.accounts.vault.key(),
+        merchant_controls: merchant_controls.clone(),
+    };
+    let merchant_controls_order_data_vec = merchant_controls_order_data.try_to_vec()?;
+
+    // Order data is shorter than the allocated space when the controls aren't full
+    let mut data = ctx.accounts.merchant_controls_order.try_borrow_mut_data()?;
+    data[..ANCHOR_DISCRIMINATOR].copy_from_slice(&MerchantControlsOrder::DISCRIMINATOR);
+    data[ANCHOR_DISCRIMINATOR..ANCHOR_DISCRIMINATOR + merchant_controls_order_data_vec.len()]
+        .copy_from_slice(&merchant_controls_order_data_vec[..]);
+    drop(data);
+
+    ctx.accounts.vault.add_open_order()?;
+
+    emit_cpi!(MerchantControlsInitiated {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        merchant_controls_order: ctx.accounts.merchant_controls_order.key(),
+        merchant_controls,
+        release_slot,
+    });
+
+    Ok(())
+}
@@ -1,19 +1,36 @@
 use crate::{
-    config::{PyraError, ANCHOR_DISCRIMINATOR, TIME_LOCK_DURATION_SLOTS},
-    state::{SpendLimitsOrder, TimeLock, Vault},
-    utils::{allocate_time_lock_owner_payer, allocate_time_lock_program_payer},
+    check,
+    config::{PyraError, ANCHOR_DISCRIMINATOR, VAULT_VERSION},
+    events::SpendLimitsInitiated,
+    state::{
+        DelegatePermission, GlobalConfig, SpendLimitModeParams, SpendLimitWindowParams,
+        SpendLimitsOrder, SpendVelocityLimits, TimeLock, Vault, VaultDelegates,
+    },
+    utils::{
+        allocate_time_lock_owner_payer, allocate_time_lock_program_payer,
+        validate_calendar_spend_limits, validate_delegate_spend_limit_timing,
+        validate_delegate_spend_limits, validate_vault_authority,
+    },
 };
 use anchor_lang::{prelude::*, Discriminator};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InitiateSpendLimits<'info> {
     #[account(
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
-        bump = vault.bump
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
     )]
     pub vault: Box<Account<'info, Vault>>,
 
-    pub owner: Signer<'info>,
+    /// CHECK: Checked by the vault's has_one constraint
+    pub owner: UncheckedAccount<'info>,
+
+    // Owner or a delegate with the required permission
+    pub authority: Signer<'info>,
 
     #[account(mut)]
     pub spend_limits_order: Signer<'info>,
@@ -23,16 +40,80 @@ pub struct InitiateSpendLimits<'info> {
     pub time_lock_rent_payer: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        seeds = [b"vault_delegates".as_ref(), vault.key().as_ref()],
+        bump = vault_delegates.bump
+    )]
+    pub vault_delegates: Option<Box<Account<'info, VaultDelegates>>>,
 }
 
 /// Creates a time locked spend limits order, which can be fulfilled permissionlessly once the time lock has expired. Time locks prevent edge cases of double spend with the Pyra card.
+#[allow(clippy::too_many_arguments)]
 pub fn initiate_spend_limits_handler<'info>(
     ctx: Context<'_, '_, '_, 'info, InitiateSpendLimits<'info>>,
     spend_limit_per_transaction: u64,
     spend_limit_per_timeframe: u64,
     timeframe_in_seconds: u64,
     next_timeframe_reset_timestamp: u64,
+    spend_limit_windows: Vec<SpendLimitWindowParams>,
+    spend_limit_mode_params: SpendLimitModeParams,
+    spend_velocity_limits: SpendVelocityLimits,
 ) -> Result<()> {
+    let SpendLimitModeParams {
+        spend_limit_mode,
+        utc_offset_seconds,
+        calendar_period,
+    } = spend_limit_mode_params;
+
+    let delegate = validate_vault_authority(
+        &ctx.accounts.vault,
+        &ctx.accounts.authority.key(),
+        &ctx.accounts.vault_delegates,
+        DelegatePermission::SpendLimits,
+    )?;
+    validate_delegate_spend_limits(
+        delegate,
+        spend_limit_per_transaction,
+        spend_limit_per_timeframe,
+        &spend_limit_windows,
+    )?;
+    validate_delegate_spend_limit_timing(
+        delegate,
+        &ctx.accounts.vault,
+        timeframe_in_seconds,
+        next_timeframe_reset_timestamp,
+        &spend_limit_windows,
+        &spend_velocity_limits,
+    )?;
+    if delegate.is_some() {
+        check!(
+            spend_limit_mode == ctx.accounts.vault.spend_limit_mode
+                && utc_offset_seconds == ctx.accounts.vault.utc_offset_seconds
+                && calendar_period == ctx.accounts.vault.calendar_period,
+            PyraError::IllegalDelegateSpendLimitChange
+        );
+    }
+
+    // Validate the windows now, so the order can't fail when it's fulfilled
+    ctx.accounts
+        .vault
+        .get_updated_spend_limit_windows(&spend_limit_windows)?;
+    validate_calendar_spend_limits(
+        spend_limit_mode,
+        timeframe_in_seconds,
+        calendar_period,
+        &spend_limit_windows,
+        utc_offset_seconds,
+    )?;
+
+    // Delegates use the program rent payer, as the owner receives the rent when the order is closed
     let is_owner_payer = ctx
         .accounts
         .time_lock_rent_payer
@@ -40,8 +121,12 @@ pub fn initiate_spend_limits_handler<'info>(
         .eq(&ctx.accounts.owner.key());
 
     if is_owner_payer {
+        check!(
+            ctx.accounts.authority.key().eq(&ctx.accounts.owner.key()),
+            PyraError::InvalidTimeLockRentPayer
+        );
         allocate_time_lock_owner_payer(
-            &ctx.accounts.owner,
+            &ctx.accounts.authority,
             &ctx.accounts.spend_limits_order,
             &ctx.accounts.system_program,
             SpendLimitsOrder::INIT_SPACE,
@@ -57,25 +142,55 @@ pub fn initiate_spend_limits_handler<'info>(
 
     let current_slot = Clock::get()?.slot;
     let release_slot = current_slot
-        .checked_add(TIME_LOCK_DURATION_SLOTS)
+        .checked_add(ctx.accounts.global_config.time_lock_duration_slots)
         .ok_or(PyraError::MathOverflow)?;
 
     let spend_limits_order_data = SpendLimitsOrder {
         time_lock: TimeLock {
             owner: ctx.accounts.owner.key(),
+            owner_epoch: ctx.accounts.vault.owner_epoch,
             is_owner_payer,
             release_slot,
         },
+        vault: ctx.accounts.vault.key(),
         spend_limit_per_transaction,
         spend_limit_per_timeframe,
         timeframe_in_seconds,
         next_timeframe_reset_timestamp,
+        spend_limit_windows: spend_limit_windows.clone(),
+        spend_limit_mode,
+        utc_offset_seconds,
+        spend_velocity_limits,
+        calendar_period,
+        is_delegate_order: delegate.is_some(),
     };
     let spend_limits_order_data_vec = spend_limits_order_data.try_to_vec()?;
 
+    // Order data is shorter than the allocated space when the windows aren't full
     let mut data = ctx.accounts.spend_limits_order.try_borrow_mut_data()?;
     data[..ANCHOR_DISCRIMINATOR].copy_from_slice(&SpendLimitsOrder::DISCRIMINATOR);
-    data[ANCHOR_DISCRIMINATOR..].copy_from_slice(&spend_limits_order_data_vec[..]);
+    data[ANCHOR_DISCRIMINATOR..ANCHOR_DISCRIMINATOR + spend_limits_order_data_vec.len()]
+        .copy_from_slice(&spend_limits_order_data_vec[..]);
+    drop(data);
+
+    ctx.accounts.vault.add_open_order()?;
+
+    emit_cpi!(SpendLimitsInitiated {
+        owner: ctx.accounts.owner.key(),
+        authority: ctx.accounts.authority.key(),
+        vault: ctx.accounts.vault.key(),
+        spend_limits_order: ctx.accounts.spend_limits_order.key(),
+        spend_limit_per_transaction,
+        spend_limit_per_timeframe,
+        timeframe_in_seconds,
+        next_timeframe_reset_timestamp,
+        spend_limit_windows,
+        spend_limit_mode,
+        utc_offset_seconds,
+        spend_velocity_limits,
+        calendar_period,
+        release_slot,
+    });
 
     Ok(())
 }
@@ -0,0 +1,99 @@
+use crate::{
+    check,
+    config::{PyraError, ANCHOR_DISCRIMINATOR, VAULT_VERSION},
+    events::UnfreezeCardInitiated,
+    state::{GlobalConfig, TimeLock, UnfreezeCardOrder, Vault},
+    utils::{allocate_time_lock_owner_payer, allocate_time_lock_program_payer},
+};
+use anchor_lang::{prelude::*, Discriminator};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitiateUnfreezeCard<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub unfreeze_card_order: Signer<'info>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Creates a time locked order to unfreeze the vault's card, which can be fulfilled permissionlessly once the time lock has expired
+pub fn initiate_unfreeze_card_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitiateUnfreezeCard<'info>>,
+) -> Result<()> {
+    check!(ctx.accounts.vault.is_card_frozen, PyraError::CardNotFrozen);
+
+    let is_owner_payer = ctx
+        .accounts
+        .time_lock_rent_payer
+        .key()
+        .eq(&ctx.accounts.owner.key());
+
+    if is_owner_payer {
+        allocate_time_lock_owner_payer(
+            &ctx.accounts.owner,
+            &ctx.accounts.unfreeze_card_order,
+            &ctx.accounts.system_program,
+            UnfreezeCardOrder::INIT_SPACE,
+        )?;
+    } else {
+        allocate_time_lock_program_payer(
+            &ctx.accounts.time_lock_rent_payer.to_account_info(),
+            &ctx.accounts.unfreeze_card_order,
+            &ctx.accounts.system_program,
+            UnfreezeCardOrder::INIT_SPACE,
+        )?;
+    }
+
+    let current_slot = Clock::get()?.slot;
+    let release_slot = current_slot
+        .checked_add(ctx.accounts.global_config.time_lock_duration_slots)
+        .ok_or(PyraError::MathOverflow)?;
+
+    let unfreeze_card_order_data = UnfreezeCardOrder {
+        time_lock: TimeLock {
+            owner: ctx.accounts.owner.key(),
+            owner_epoch: ctx.accounts.vault.owner_epoch,
+            is_owner_payer,
+            release_slot,
+        },
+        vault: ctx.accounts.vault.key(),
+    };
+    let unfreeze_card_order_data_vec = unfreeze_card_order_data.try_to_vec()?;
+
+    let mut data = ctx.accounts.unfreeze_card_order.try_borrow_mut_data()?;
+    data[..ANCHOR_DISCRIMINATOR].copy_from_slice(&UnfreezeCardOrder::DISCRIMINATOR);
+    data[ANCHOR_DISCRIMINATOR..].copy_from_slice(&unfreeze_card_order_data_vec[..]);
+    drop(data);
+
+    ctx.accounts.vault.add_open_order()?;
+
+    emit_cpi!(UnfreezeCardInitiated {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        unfreeze_card_order: ctx.accounts.unfreeze_card_order.key(),
+        release_slot,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,296 @@
+use crate::{
+    check,
+    config::{PyraError, USDC_MARKET_INDEX, USDC_MINT, VAULT_VERSION},
+    events::RefundReceived,
+    state::{GlobalConfig, ProtocolFeature, SpendRecord, Vault},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    close_account, CloseAccount, Mint, TokenAccount, TokenInterface,
+};
+use drift::{
+    cpi::accounts::Deposit as DriftDeposit, cpi::deposit as drift_deposit, program::Drift,
+};
+use message_transmitter::{
+    cpi::{accounts::ReceiveMessageContext, receive_message},
+    instructions::ReceiveMessageParams,
+    program::MessageTransmitter,
+};
+use token_messenger_minter::program::TokenMessengerMinter;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(message: Vec<u8>, attestation: Vec<u8>, spend_cctp_nonce: u64)]
+pub struct ReceiveRefund<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Can be any account, once it has a Vault
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = spend_caller.key().eq(&global_config.spend_caller) @ PyraError::InvalidSpendCaller
+    )]
+    pub spend_caller: Signer<'info>,
+
+    // Mint recipient of the refund burn on Base
+    #[account(
+        init_if_needed,
+        seeds = [b"refund_mule".as_ref(), vault.key().as_ref()],
+        bump,
+        payer = spend_caller,
+        token::mint = usdc_mint,
+        token::authority = vault
+    )]
+    pub mule: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Spend being refunded, only needed to restore its spend limit. Spends bridged before spends were recorded
+    // have none.
+    #[account(
+        mut,
+        seeds = [b"spend_record".as_ref(), vault.key().as_ref(), spend_cctp_nonce.to_le_bytes().as_ref()],
+        bump = spend_record.bump,
+        has_one = vault @ PyraError::InvalidSpendRecord
+    )]
+    pub spend_record: Option<Box<Account<'info, SpendRecord>>>,
+
+    #[account(mut)] // Mutable for mint
+    pub usdc_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: This account is passed through to the Circle CPI, which performs the security checks
+    pub message_transmitter: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Circle CPI, which performs the security checks
+    #[account(mut)]
+    pub used_nonces: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Circle CPI, which performs the security checks
+    pub message_transmitter_authority: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Circle CPI, which performs the security checks
+    pub message_transmitter_event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Circle CPI, which performs the security checks
+    pub token_messenger: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Circle CPI, which performs the security checks
+    pub remote_token_messenger: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Circle CPI, which performs the security checks
+    pub token_minter: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Circle CPI, which performs the security checks
+    #[account(mut)]
+    pub local_token: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Circle CPI, which performs the security checks
+    pub token_pair: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Circle CPI, which performs the security checks
+    #[account(mut)]
+    pub custody_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Circle CPI, which performs the security checks
+    pub token_messenger_event_authority: UncheckedAccount<'info>,
+
+    pub message_transmitter_program: Program<'info, MessageTransmitter>,
+
+    pub token_messenger_minter_program: Program<'info, TokenMessengerMinter>,
+
+    /// CHECK: Seeds ensure refunds go to the spend sub-account, the Drift CPI performs the remaining checks
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), vault.key().as_ref(), vault.spend_sub_account_id.to_le_bytes().as_ref()],
+        seeds::program = drift_program.key(),
+        bump
+    )]
+    pub drift_user: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    #[account(mut)]
+    pub drift_user_stats: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    #[account(mut)]
+    pub drift_state: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    #[account(mut)]
+    pub spot_market_vault: UncheckedAccount<'info>,
+
+    pub drift_program: Program<'info, Drift>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Receives a card refund bridged back from Base into the vault's refund mule and deposits it into the spend sub-account, optionally restoring the spend limit the original spend consumed, up to the amount of that spend not yet refunded
+pub fn receive_refund_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, ReceiveRefund<'info>>,
+    message: Vec<u8>,
+    attestation: Vec<u8>,
+    spend_cctp_nonce: u64,
+    restore_spend_limit: bool,
+) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::Deposits),
+        PyraError::ProtocolPaused
+    );
+
+    check!(
+        &ctx.accounts.usdc_mint.key().eq(&USDC_MINT),
+        PyraError::InvalidMint
+    );
+
+    // Receive the refund through Circle CPI, which mints USDC to the message's mint recipient
+    let mut receive_cpi_ctx = CpiContext::new(
+        ctx.accounts.message_transmitter_program.to_account_info(),
+        ReceiveMessageContext {
+            payer: ctx.accounts.spend_caller.to_account_info(),
+            caller: ctx.accounts.spend_caller.to_account_info(),
+            authority_pda: ctx.accounts.message_transmitter_authority.to_account_info(),
+            message_transmitter: ctx.accounts.message_transmitter.to_account_info(),
+            used_nonces: ctx.accounts.used_nonces.to_account_info(),
+            receiver: ctx
+                .accounts
+                .token_messenger_minter_program
+                .to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            event_authority: ctx
+                .accounts
+                .message_transmitter_event_authority
+                .to_account_info(),
+            program: ctx.accounts.message_transmitter_program.to_account_info(),
+        },
+    );
+
+    receive_cpi_ctx.remaining_accounts = vec![
+        ctx.accounts.token_messenger.to_account_info(),
+        ctx.accounts.remote_token_messenger.to_account_info(),
+        ctx.accounts.token_minter.to_account_info(),
+        ctx.accounts.local_token.to_account_info(),
+        ctx.accounts.token_pair.to_account_info(),
+        ctx.accounts.mule.to_account_info(),
+        ctx.accounts.custody_token_account.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts
+            .token_messenger_event_authority
+            .to_account_info(),
+        ctx.accounts
+            .token_messenger_minter_program
+            .to_account_info(),
+    ];
+
+    let mule_balance_before_receive = ctx.accounts.mule.amount;
+    receive_message(
+        receive_cpi_ctx,
+        ReceiveMessageParams {
+            message,
+            attestation,
+        },
+    )?;
+    ctx.accounts.mule.reload()?;
+
+    // Messages minting to any other recipient leave the mule unchanged
+    let amount_usdc_base_units = ctx
+        .accounts
+        .mule
+        .amount
+        .saturating_sub(mule_balance_before_receive);
+    check!(amount_usdc_base_units > 0, PyraError::InvalidRefund);
+
+    // Drift Deposit CPI
+    let vault_bump = ctx.accounts.vault.bump;
+    let owner = ctx.accounts.owner.key();
+    let vault_id = ctx.accounts.vault.vault_id;
+    let seeds_vault = &[b"vault", vault_id.as_ref(), &[vault_bump]];
+    let vault_signer = &[&seeds_vault[..]];
+
+    let mut cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.drift_program.to_account_info(),
+        DriftDeposit {
+            state: ctx.accounts.drift_state.to_account_info(),
+            user: ctx.accounts.drift_user.to_account_info(),
+            user_stats: ctx.accounts.drift_user_stats.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+            spot_market_vault: ctx.accounts.spot_market_vault.to_account_info(),
+            user_token_account: ctx.accounts.mule.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+        vault_signer,
+    );
+
+    cpi_ctx.remaining_accounts = ctx.remaining_accounts.to_vec();
+
+    // reduce_only = false, Drift repays any USDC borrow before crediting the remainder as collateral
+    let deposit_usdc_base_units = ctx.accounts.mule.amount;
+    drift_deposit(cpi_ctx, USDC_MARKET_INDEX, deposit_usdc_base_units, false)?;
+
+    // Close vault's mule
+    let cpi_ctx_close = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.mule.to_account_info(),
+            destination: ctx.accounts.spend_caller.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        },
+        vault_signer,
+    );
+    close_account(cpi_ctx_close)?;
+
+    // Only the unrefunded part of the spend is restored, to the timeframes still in the period it was spent in
+    let mut spend_limit_restored = 0;
+    if restore_spend_limit {
+        let spend_record = ctx
+            .accounts
+            .spend_record
+            .as_mut()
+            .ok_or(PyraError::InvalidSpendRecord)?;
+        check!(
+            spend_record.amount_usdc_base_units > 0,
+            PyraError::SpendAlreadyRefunded
+        );
+
+        let current_timestamp_signed = Clock::get()?.unix_timestamp;
+        check!(current_timestamp_signed > 0, PyraError::InvalidTimestamp);
+        let current_timestamp =
+            u64::try_from(current_timestamp_signed).map_err(|_| PyraError::MathOverflow)?;
+
+        let refunded_usdc_base_units = spend_record.refund(amount_usdc_base_units);
+        spend_limit_restored = ctx.accounts.vault.restore_spend_limit_since(
+            refunded_usdc_base_units,
+            spend_record.spend_timestamp,
+            spend_record.timeframe_reset_timestamp,
+            spend_record.window_reset_timestamps,
+            current_timestamp,
+        );
+    }
+
+    emit_cpi!(RefundReceived {
+        owner,
+        vault: ctx.accounts.vault.key(),
+        spend_cctp_nonce,
+        amount_usdc_base_units,
+        drift_deposit_usdc_base_units: deposit_usdc_base_units,
+        spend_limit_restored,
+        remaining_spend_limit_per_timeframe: ctx.accounts.vault.remaining_spend_limit_per_timeframe,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::SpendHoldReleased,
+    state::{GlobalConfig, SpendHold, Vault},
+    utils::release_spend_hold,
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReleaseSpendHold<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Can be any account, once it has a Vault
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"spend_hold".as_ref(), vault.key().as_ref(), spend_hold.authorization_id.to_le_bytes().as_ref()],
+        bump = spend_hold.bump,
+        has_one = vault @ PyraError::InvalidSpendHold,
+        close = rent_payer
+    )]
+    pub spend_hold: Box<Account<'info, SpendHold>>,
+
+    /// CHECK: Safe once address is correct
+    #[account(
+        mut,
+        address = spend_hold.rent_payer @ PyraError::InvalidSpendHold
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    /// CHECK: Checked in handler as the account doesn't need to exist, seeds ensure the vault's controls can't be skipped
+    #[account(
+        mut,
+        seeds = [b"vault_merchant_controls".as_ref(), vault.key().as_ref()],
+        bump
+    )]
+    pub vault_merchant_controls: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Releases an uncaptured spend hold back to the vault's spend limits. Permissionless once the hold has expired, the spend caller can release it earlier when the authorization is reversed.
+pub fn release_spend_hold_handler(ctx: Context<ReleaseSpendHold>) -> Result<()> {
+    let is_spend_caller = ctx
+        .accounts
+        .caller
+        .key()
+        .eq(&ctx.accounts.global_config.spend_caller);
+
+    let current_timestamp_signed = Clock::get()?.unix_timestamp;
+    check!(current_timestamp_signed > 0, PyraError::InvalidTimestamp);
+    let current_timestamp =
+        u64::try_from(current_timestamp_signed).map_err(|_| PyraError::MathOverflow)?;
+
+    if !is_spend_caller {
+        check!(
+            ctx.accounts.spend_hold.is_expired(current_timestamp),
+            PyraError::SpendHoldNotExpired
+        );
+    }
+
+    let released_usdc_base_units = ctx.accounts.spend_hold.amount_usdc_base_units;
+    let spend_limit_restored = release_spend_hold(
+        &mut ctx.accounts.spend_hold,
+        &mut ctx.accounts.vault,
+        &ctx.accounts.vault_merchant_controls.to_account_info(),
+        current_timestamp,
+    )?;
+    ctx.accounts.vault.remove_spend_hold();
+
+    emit_cpi!(SpendHoldReleased {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        spend_hold: ctx.accounts.spend_hold.key(),
+        caller: ctx.accounts.caller.key(),
+        authorization_id: ctx.accounts.spend_hold.authorization_id,
+        released_usdc_base_units,
+        spend_limit_restored,
+    });
+
+    Ok(())
+}
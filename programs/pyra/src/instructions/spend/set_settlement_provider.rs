@@ -0,0 +1,61 @@
+use crate::{
+    config::{PyraError, VAULT_VERSION},
+    events::SettlementProviderSet,
+    state::{GlobalConfig, SettlementProviderRegistry, Vault},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetSettlementProvider<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    pub owner: Signer<'info>,
+
+    // Co-signs so spends are only routed to a provider that has issued the vault's card
+    #[account(
+        constraint = spend_caller.key().eq(&global_config.spend_caller) @ PyraError::InvalidSpendCaller
+    )]
+    pub spend_caller: Signer<'info>,
+
+    #[account(
+        seeds = [b"settlement_provider_registry"],
+        bump = settlement_provider_registry.bump
+    )]
+    pub settlement_provider_registry: Box<Account<'info, SettlementProviderRegistry>>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Binds the vault to another settlement provider when its card is moved to a different issuer, signed by both the owner and the spend caller
+pub fn set_settlement_provider_handler(
+    ctx: Context<SetSettlementProvider>,
+    settlement_provider_id: u16,
+) -> Result<()> {
+    ctx.accounts
+        .settlement_provider_registry
+        .get_enabled_provider(settlement_provider_id)?;
+
+    let previous_settlement_provider_id = ctx.accounts.vault.settlement_provider_id;
+    ctx.accounts.vault.settlement_provider_id = settlement_provider_id;
+
+    emit_cpi!(SettlementProviderSet {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        previous_settlement_provider_id,
+        settlement_provider_id,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,312 @@
+use crate::{
+    check,
+    config::{PyraError, ANCHOR_DISCRIMINATOR, USDC_MARKET_INDEX, USDC_MINT, VAULT_VERSION},
+    events::CollateralSpendStarted,
+    state::{CollateralSpendLedger, DriftMarketRegistry, GlobalConfig, ProtocolFeature, Vault},
+    utils::get_drift_market,
+};
+use anchor_lang::{prelude::*, Discriminator};
+use anchor_spl::token_interface::{
+    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+    TransferChecked,
+};
+use drift::{
+    cpi::{accounts::Withdraw as DriftWithdraw, withdraw as drift_withdraw},
+    program::Drift,
+};
+use solana_program::{
+    instruction::{get_stack_height, Instruction},
+    sysvar::instructions::{self, load_current_index_checked, load_instruction_at_checked},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct StartCollateralSpend<'info> {
+    #[account(
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Can be any account, once it has a Vault
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = spend_caller.key().eq(&global_config.spend_caller) @ PyraError::InvalidSpendCaller
+    )]
+    pub spend_caller: Signer<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = spend_caller,
+        associated_token::token_program = token_program
+    )]
+    pub caller_spl: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        token::mint = usdc_mint,
+        token::authority = spend_caller
+    )]
+    pub caller_usdc: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"collateral_spend_mule".as_ref(), vault.key().as_ref(), mint.key().as_ref()],
+        bump,
+        payer = spend_caller,
+        token::mint = mint,
+        token::authority = vault
+    )]
+    pub mule: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub usdc_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Seeds ensure spends are funded by the spend sub-account, the Drift CPI performs the remaining checks
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), vault.key().as_ref(), vault.spend_sub_account_id.to_le_bytes().as_ref()],
+        seeds::program = drift_program.key(),
+        bump
+    )]
+    pub drift_user: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    #[account(mut)]
+    pub drift_user_stats: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    #[account(mut)]
+    pub drift_state: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    #[account(mut)]
+    pub spot_market_vault: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    pub drift_signer: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub drift_program: Program<'info, Drift>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Account is safe once address is correct
+    #[account(address = instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        seeds = [b"collateral_spend_ledger".as_ref(), vault.key().as_ref()],
+        bump,
+        payer = spend_caller,
+        space = CollateralSpendLedger::INIT_SPACE
+    )]
+    pub ledger: Box<Account<'info, CollateralSpendLedger>>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        seeds = [b"drift_market_registry"],
+        bump = drift_market_registry.bump
+    )]
+    pub drift_market_registry: Box<Account<'info, DriftMarketRegistry>>,
+}
+
+/// First collateral spend instruction, takes place before the swap. Withdraws collateral from the spend sub-account to the spend caller, who swaps it to the USDC that funds the following spend.
+pub fn start_collateral_spend_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, StartCollateralSpend<'info>>,
+    collateral_market_index: u16,
+    collateral_amount_base_units: u64,
+) -> Result<()> {
+    check!(
+        !ctx.accounts.global_config.is_paused(ProtocolFeature::Spend),
+        PyraError::ProtocolPaused
+    );
+
+    check!(!ctx.accounts.vault.is_card_frozen, PyraError::CardFrozen);
+
+    let index: usize =
+        load_current_index_checked(&ctx.accounts.instructions.to_account_info())?.into();
+    let current_instruction =
+        load_instruction_at_checked(index, &ctx.accounts.instructions.to_account_info())?;
+    let swap_instruction =
+        load_instruction_at_checked(index + 1, &ctx.accounts.instructions.to_account_info())?;
+    let deposit_instruction =
+        load_instruction_at_checked(index + 2, &ctx.accounts.instructions.to_account_info())?;
+    let start_spend_instruction =
+        load_instruction_at_checked(index + 3, &ctx.accounts.instructions.to_account_info())?;
+    validate_instruction_order(
+        &current_instruction,
+        &swap_instruction,
+        &deposit_instruction,
+        &start_spend_instruction,
+    )?;
+
+    // Deposit and spend must be for the same vault, so the swapped USDC funds this spend
+    let vault = ctx.accounts.vault.key();
+    check!(
+        deposit_instruction.accounts[0].pubkey.eq(&vault)
+            && start_spend_instruction.accounts[0].pubkey.eq(&vault),
+        PyraError::InvalidUserAccounts
+    );
+
+    check!(
+        &ctx.accounts.usdc_mint.key().eq(&USDC_MINT),
+        PyraError::InvalidMint
+    );
+
+    let collateral_market =
+        get_drift_market(&ctx.accounts.drift_market_registry, collateral_market_index)?;
+    check!(
+        collateral_market_index != USDC_MARKET_INDEX,
+        PyraError::InvalidCollateralSpendMarket
+    );
+    check!(
+        &ctx.accounts.mint.key().eq(&collateral_market.mint),
+        PyraError::InvalidMint
+    );
+
+    // Paranoia check to ensure the mule is empty before withdrawing for amount calculations
+    check!(
+        ctx.accounts.mule.amount == 0,
+        PyraError::InvalidStartingVaultBalance
+    );
+
+    // Drift Withdraw CPI
+    let vault_id = ctx.accounts.vault.vault_id;
+    let vault_seeds = &[b"vault", vault_id.as_ref(), &[ctx.accounts.vault.bump]];
+    let signer_seeds_vault = &[&vault_seeds[..]];
+
+    let mut cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.drift_program.to_account_info(),
+        DriftWithdraw {
+            state: ctx.accounts.drift_state.to_account_info(),
+            user: ctx.accounts.drift_user.to_account_info(),
+            user_stats: ctx.accounts.drift_user_stats.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+            spot_market_vault: ctx.accounts.spot_market_vault.to_account_info(),
+            drift_signer: ctx.accounts.drift_signer.to_account_info(),
+            user_token_account: ctx.accounts.mule.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+        signer_seeds_vault,
+    );
+
+    cpi_ctx.remaining_accounts = ctx.remaining_accounts.to_vec();
+
+    // reduce_only = true so only existing collateral is sold, the spend must not open a loan
+    drift_withdraw(
+        cpi_ctx,
+        collateral_market_index,
+        collateral_amount_base_units,
+        true,
+    )?;
+
+    ctx.accounts.mule.reload()?;
+    let true_amount_withdrawn = ctx.accounts.mule.amount;
+
+    // Transfer tokens from mule to caller's ATA for the swap
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.mule.to_account_info(),
+                to: ctx.accounts.caller_spl.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+            },
+            signer_seeds_vault,
+        ),
+        true_amount_withdrawn,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    // Close mule
+    let cpi_ctx_close = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.mule.to_account_info(),
+            destination: ctx.accounts.spend_caller.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        },
+        signer_seeds_vault,
+    );
+    close_account(cpi_ctx_close)?;
+
+    // Log the collateral sold and starting USDC balance, so the deposit can value the swap
+    let ledger = &mut ctx.accounts.ledger;
+    ledger.collateral_market_index = collateral_market_index;
+    ledger.collateral_amount_base_units = true_amount_withdrawn;
+    ledger.starting_usdc_balance = ctx.accounts.caller_usdc.amount;
+
+    emit_cpi!(CollateralSpendStarted {
+        owner: ctx.accounts.owner.key(),
+        vault,
+        caller: ctx.accounts.spend_caller.key(),
+        collateral_market_index,
+        collateral_amount_base_units: true_amount_withdrawn,
+        starting_usdc_balance: ctx.accounts.caller_usdc.amount,
+    });
+
+    Ok(())
+}
+
+#[inline(never)]
+fn validate_instruction_order(
+    current_instruction: &Instruction,
+    swap_instruction: &Instruction,
+    deposit_instruction: &Instruction,
+    start_spend_instruction: &Instruction,
+) -> Result<()> {
+    // Ensure we're not in a CPI (to validate introspection)
+    const TOP_LEVEL_STACK_HEIGHT: usize = 1;
+    check!(
+        get_stack_height() == TOP_LEVEL_STACK_HEIGHT,
+        PyraError::IllegalCollateralSpendInstructions
+    );
+    check!(
+        current_instruction.program_id.eq(&crate::id()),
+        PyraError::IllegalCollateralSpendInstructions
+    );
+
+    // 2nd instruction can be anything, once it's not a Pyra instruction (prevent reentrancy)
+    check!(
+        !swap_instruction.program_id.eq(&crate::id()),
+        PyraError::IllegalCollateralSpendInstructions
+    );
+
+    // 3rd instruction must be deposit_collateral_spend
+    check!(
+        deposit_instruction.program_id.eq(&crate::id()),
+        PyraError::IllegalCollateralSpendInstructions
+    );
+    check!(
+        deposit_instruction.data[..ANCHOR_DISCRIMINATOR]
+            .eq(&crate::instruction::DepositCollateralSpend::DISCRIMINATOR),
+        PyraError::IllegalCollateralSpendInstructions
+    );
+
+    // 4th instruction must be start_spend, which checks it's followed by complete_spend
+    check!(
+        start_spend_instruction.program_id.eq(&crate::id()),
+        PyraError::IllegalCollateralSpendInstructions
+    );
+    check!(
+        start_spend_instruction.data[..ANCHOR_DISCRIMINATOR]
+            .eq(&crate::instruction::StartSpend::DISCRIMINATOR),
+        PyraError::IllegalCollateralSpendInstructions
+    );
+
+    Ok(())
+}
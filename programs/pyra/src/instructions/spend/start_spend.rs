@@ -1,11 +1,12 @@
 use crate::{
     check,
     config::{
-        PyraError, ANCHOR_DISCRIMINATOR, SPEND_CALLER, SPEND_FEE_BPS, SPEND_FEE_DESTINATION,
-        USDC_MARKET_INDEX,
+        PyraError, ANCHOR_DISCRIMINATOR, MERCHANT_ID_HASH_SIZE, USDC_MARKET_INDEX, USDC_MINT,
+        VAULT_VERSION,
     },
-    state::Vault,
-    utils::{get_drift_market, validate_ata},
+    events::SpendStarted,
+    state::{GlobalConfig, ProtocolFeature, ReferrerRewards, Vault},
+    utils::{get_spot_borrow_base_units, process_spend_limits, validate_ata},
 };
 use anchor_lang::{
     prelude::*,
@@ -20,6 +21,7 @@ use anchor_spl::{
 };
 use drift::{
     cpi::accounts::Withdraw as DriftWithdraw, cpi::withdraw as drift_withdraw, program::Drift,
+    state::user::User as DriftUser,
 };
 use solana_program::instruction::{get_stack_height, Instruction};
 
@@ -28,8 +30,10 @@ use solana_program::instruction::{get_stack_height, Instruction};
 pub struct StartSpend<'info> {
     #[account(
         mut,
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
-        bump = vault.bump
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
     )]
     pub vault: Box<Account<'info, Vault>>,
 
@@ -38,20 +42,20 @@ pub struct StartSpend<'info> {
 
     #[account(
         mut,
-        constraint = spend_caller.key().eq(&SPEND_CALLER) @ PyraError::InvalidSpendCaller
+        constraint = spend_caller.key().eq(&global_config.spend_caller) @ PyraError::InvalidSpendCaller
     )]
     pub spend_caller: Signer<'info>,
 
     /// CHECK: Safe once address is correct
     #[account(
         mut,
-        constraint = spend_fee_destination.key().eq(&SPEND_FEE_DESTINATION) @ PyraError::InvalidSpendFeeDestination
+        constraint = spend_fee_destination.key().eq(&global_config.spend_fee_destination) @ PyraError::InvalidSpendFeeDestination
     )]
     pub spend_fee_destination: UncheckedAccount<'info>,
 
     #[account(
         init_if_needed,
-        seeds = [b"spend_mule".as_ref(), owner.key().as_ref()],
+        seeds = [b"spend_mule".as_ref(), vault.key().as_ref()],
         bump,
         payer = spend_caller,
         token::mint = usdc_mint,
@@ -61,9 +65,14 @@ pub struct StartSpend<'info> {
 
     pub usdc_mint: Box<InterfaceAccount<'info, Mint>>,
 
-    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
-    #[account(mut)]
-    pub drift_user: UncheckedAccount<'info>,
+    // Seeds ensure spends are funded by the spend sub-account, the Drift CPI performs the remaining checks
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), vault.key().as_ref(), vault.spend_sub_account_id.to_le_bytes().as_ref()],
+        seeds::program = drift_program.key(),
+        bump
+    )]
+    pub drift_user: AccountLoader<'info, DriftUser>,
 
     /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
     #[account(mut)]
@@ -102,14 +111,47 @@ pub struct StartSpend<'info> {
     /// CHECK: Checked in handler as the account doesn't need to exist
     #[account(mut)]
     pub deposit_address_usdc: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    // Only required when the vault has a referrer and a spend fee is taken
+    #[account(
+        mut,
+        seeds = [b"referrer_rewards".as_ref(), vault.referrer.as_ref()],
+        bump = referrer_rewards.bump
+    )]
+    pub referrer_rewards: Option<Box<Account<'info, ReferrerRewards>>>,
+
+    // Checked in handler, as the authority is optional
+    #[account(mut)]
+    pub referrer_rewards_usdc: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: Checked in handler as the account doesn't need to exist, seeds ensure the vault's controls can't be skipped
+    #[account(
+        mut,
+        seeds = [b"vault_merchant_controls".as_ref(), vault.key().as_ref()],
+        bump
+    )]
+    pub vault_merchant_controls: UncheckedAccount<'info>,
 }
 
 /// First spend instruction (split due to stack size limits), withdraws from vault and updates spend limits
 pub fn start_spend_handler<'info>(
-    mut ctx: Context<'_, '_, '_, 'info, StartSpend<'info>>,
+    mut ctx: Context<'_, '_, 'info, 'info, StartSpend<'info>>,
     amount_usdc_base_units: u64,
     spend_fee: bool,
+    merchant_category_code: u16,
+    merchant_id_hash: [u8; MERCHANT_ID_HASH_SIZE],
 ) -> Result<()> {
+    check!(
+        !ctx.accounts.global_config.is_paused(ProtocolFeature::Spend),
+        PyraError::ProtocolPaused
+    );
+
     let index: usize =
         load_current_index_checked(&ctx.accounts.instructions.to_account_info())?.into();
     let current_instruction =
@@ -118,10 +160,15 @@ pub fn start_spend_handler<'info>(
         load_instruction_at_checked(index + 1, &ctx.accounts.instructions.to_account_info())?;
     validate_complete_spend_ix(&ctx, &current_instruction, &complete_instruction)?;
 
+    // Captures settle a hold authorized before the card could have been frozen, and its limit is already reserved
+    let is_capture = is_spend_hold_capture(&ctx, index, amount_usdc_base_units)?;
+    if !is_capture {
+        check!(!ctx.accounts.vault.is_card_frozen, PyraError::CardFrozen);
+    }
+
     // Manually check mint in handler to avoid Anchor stack overflow
-    let drift_market = get_drift_market(USDC_MARKET_INDEX)?;
     check!(
-        &ctx.accounts.usdc_mint.key().eq(&drift_market.mint),
+        &ctx.accounts.usdc_mint.key().eq(&USDC_MINT),
         PyraError::InvalidMint
     );
 
@@ -134,7 +181,15 @@ pub fn start_spend_handler<'info>(
         PyraError::InvalidSpendFeeDestination
     );
 
-    process_spend_limits(&mut ctx, amount_usdc_base_units)?;
+    if !is_capture {
+        process_spend_limits(
+            &mut ctx.accounts.vault,
+            &ctx.accounts.vault_merchant_controls.to_account_info(),
+            amount_usdc_base_units,
+            merchant_category_code,
+            &merchant_id_hash,
+        )?;
+    }
 
     let deposit_address_usdc = validate_ata(
         &ctx.accounts.deposit_address_usdc.to_account_info(),
@@ -174,12 +229,19 @@ pub fn start_spend_handler<'info>(
     // Withdraw required funds remaining from Drift
     let vault_bump = ctx.accounts.vault.bump;
     let owner = ctx.accounts.owner.key();
-    let seeds_vault = &[b"vault", owner.as_ref(), &[vault_bump]];
+    let vault_id = ctx.accounts.vault.vault_id;
+    let seeds_vault = &[b"vault", vault_id.as_ref(), &[vault_bump]];
     let vault_signer = &[&seeds_vault[..]];
 
     let required_funds_remaining = amount_usdc_base_units.saturating_sub(idle_funds);
+    let mut borrowed_usdc_base_units = 0;
     if required_funds_remaining > 0 {
         let vault_lamports_before_cpi = ctx.accounts.vault.to_account_info().lamports();
+        let borrow_before_withdraw = get_spot_borrow_base_units(
+            &*ctx.accounts.drift_user.load()?,
+            ctx.remaining_accounts,
+            USDC_MARKET_INDEX,
+        )?;
 
         let mut cpi_ctx = CpiContext::new_with_signer(
             ctx.accounts.drift_program.to_account_info(),
@@ -208,23 +270,31 @@ pub fn start_spend_handler<'info>(
             vault_lamports_after_cpi >= vault_lamports_before_cpi,
             PyraError::IllegalVaultCPIModification
         );
+
+        // The withdraw first uses up any USDC collateral, the rest of it is borrowed
+        let borrow_after_withdraw = get_spot_borrow_base_units(
+            &*ctx.accounts.drift_user.load()?,
+            ctx.remaining_accounts,
+            USDC_MARKET_INDEX,
+        )?;
+        borrowed_usdc_base_units = borrow_after_withdraw.saturating_sub(borrow_before_withdraw);
     }
 
     // If taking a fee, transfer cut of amount from mule to spend caller
+    let mut fee_amount = 0;
+    let mut referrer_fee_amount = 0;
     if spend_fee {
-        // Sanity check on spend fee
-        const MAX_SPEND_FEE_BPS: u64 = 500;
-        check!(
-            SPEND_FEE_BPS <= MAX_SPEND_FEE_BPS,
-            PyraError::InvalidSpendFeeBPS
-        );
-
-        let fee_amount = amount_usdc_base_units
-            .checked_mul(SPEND_FEE_BPS)
+        // Spend fee BPS is validated against MAX_SPEND_FEE_BPS when the global config is set
+        fee_amount = amount_usdc_base_units
+            .checked_mul(ctx.accounts.global_config.spend_fee_bps)
             .ok_or(PyraError::MathOverflow)?
             .checked_div(10_000)
             .ok_or(PyraError::MathOverflow)?;
 
+        if !ctx.accounts.vault.referrer.eq(&Pubkey::default()) {
+            referrer_fee_amount = transfer_referrer_fee(&mut ctx, vault_signer, fee_amount)?;
+        }
+
         transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
@@ -236,11 +306,28 @@ pub fn start_spend_handler<'info>(
                 },
                 vault_signer,
             ),
-            fee_amount,
+            fee_amount
+                .checked_sub(referrer_fee_amount)
+                .ok_or(PyraError::MathOverflow)?,
             ctx.accounts.usdc_mint.decimals,
         )?;
     }
 
+    emit_cpi!(SpendStarted {
+        owner,
+        vault: ctx.accounts.vault.key(),
+        amount_usdc_base_units,
+        fee_usdc_base_units: fee_amount,
+        referrer_fee_usdc_base_units: referrer_fee_amount,
+        drift_sub_account_id: ctx.accounts.vault.spend_sub_account_id,
+        idle_funds_used: idle_funds,
+        drift_withdraw_usdc_base_units: required_funds_remaining,
+        borrowed_usdc_base_units,
+        remaining_spend_limit_per_timeframe: ctx.accounts.vault.remaining_spend_limit_per_timeframe,
+        merchant_category_code,
+        merchant_id_hash,
+    });
+
     Ok(())
 }
 
@@ -273,98 +360,112 @@ pub fn validate_complete_spend_ix<'info>(
     );
 
     // Validate state
-    let complete_owner = complete_spend.accounts[1].pubkey;
+    let complete_vault = complete_spend.accounts[0].pubkey;
     check!(
-        complete_owner.eq(&ctx.accounts.owner.key()),
+        complete_vault.eq(&ctx.accounts.vault.key()),
         PyraError::InvalidUserAccounts
     );
 
-    // Vault will be the same if owner is the same
-
     Ok(())
 }
 
-fn process_spend_limits<'info>(
-    ctx: &mut Context<'_, '_, '_, 'info, StartSpend<'info>>,
+/// True if the previous instruction is capture_spend for this vault's hold, capturing the amount being spent
+fn is_spend_hold_capture<'info>(
+    ctx: &Context<'_, '_, '_, 'info, StartSpend<'info>>,
+    index: usize,
     amount_usdc_base_units: u64,
-) -> Result<()> {
-    let current_timestamp_signed = Clock::get()?.unix_timestamp;
-    check!(current_timestamp_signed > 0, PyraError::InvalidTimestamp);
-    let current_timestamp =
-        u64::try_from(current_timestamp_signed).map_err(|_| PyraError::MathOverflow)?;
-
-    // Check transaction spend limit and timeframe
-    if ctx.accounts.vault.spend_limit_per_transaction < amount_usdc_base_units {
-        let error_code = PyraError::InsufficientTransactionSpendLimit;
-        anchor_lang::prelude::msg!(
-            "Error \"{}\" ({} < {}) thrown at {}:{}",
-            error_code,
-            ctx.accounts.vault.spend_limit_per_transaction,
-            amount_usdc_base_units,
-            file!(),
-            line!()
-        );
-        return Err(error_code.into());
+) -> Result<bool> {
+    if index == 0 {
+        return Ok(false);
     }
 
-    if ctx.accounts.vault.timeframe_in_seconds == 0 {
-        let error_code = PyraError::InsufficientTimeframeSpendLimit;
-        anchor_lang::prelude::msg!(
-            "Error \"{}\" (timeframe_in_seconds == 0) thrown at {}:{}",
-            error_code,
-            file!(),
-            line!()
+    let previous_instruction =
+        load_instruction_at_checked(index - 1, &ctx.accounts.instructions.to_account_info())?;
+    let is_capture = previous_instruction.program_id.eq(&crate::id())
+        && previous_instruction
+            .data
+            .get(..ANCHOR_DISCRIMINATOR)
+            .is_some_and(|discriminator| {
+                discriminator.eq(&crate::instruction::CaptureSpend::DISCRIMINATOR)
+            });
+
+    if is_capture {
+        // capture_spend's seeds tie the hold to its vault, so matching the vault pairs this spend with the hold
+        check!(
+            previous_instruction.accounts[0]
+                .pubkey
+                .eq(&ctx.accounts.vault.key()),
+            PyraError::InvalidUserAccounts
+        );
+
+        let captured_usdc_base_units = u64::from_le_bytes(
+            previous_instruction.data[ANCHOR_DISCRIMINATOR..ANCHOR_DISCRIMINATOR + 8]
+                .try_into()
+                .map_err(|_| PyraError::IllegalSpendInstructions)?,
+        );
+        check!(
+            captured_usdc_base_units == amount_usdc_base_units,
+            PyraError::IllegalSpendInstructions
         );
-        return Err(error_code.into());
     }
 
-    // If the timeframe has elapsed, incrememt it and reset spend limit
-    // New reset timestamp = old reset timestamp + the amount of timeframes required to reach a timestamp in the future
-    if current_timestamp > ctx.accounts.vault.next_timeframe_reset_timestamp {
-        let overflow = current_timestamp
-            .checked_sub(ctx.accounts.vault.next_timeframe_reset_timestamp)
-            .ok_or(PyraError::MathOverflow)?;
+    Ok(is_capture)
+}
 
-        // Can't divide by 0 as it's checked earlier
-        let overflow_in_timeframes = overflow / ctx.accounts.vault.timeframe_in_seconds;
+/// Sends the referrer's share of the spend fee to its rewards account, returning the amount sent
+fn transfer_referrer_fee<'info>(
+    ctx: &mut Context<'_, '_, '_, 'info, StartSpend<'info>>,
+    vault_signer: &[&[&[u8]]],
+    fee_amount: u64,
+) -> Result<u64> {
+    let (referrer_rewards, referrer_rewards_usdc) = match (
+        ctx.accounts.referrer_rewards.as_mut(),
+        ctx.accounts.referrer_rewards_usdc.as_ref(),
+    ) {
+        (Some(referrer_rewards), Some(referrer_rewards_usdc)) => {
+            (referrer_rewards, referrer_rewards_usdc)
+        }
+        _ => return Err(PyraError::MissingReferrerRewards.into()),
+    };
 
-        let seconds_to_add = overflow_in_timeframes
-            .checked_add(1) // Bring the next reset into the future
-            .ok_or(PyraError::MathOverflow)?
-            .checked_mul(ctx.accounts.vault.timeframe_in_seconds)
-            .ok_or(PyraError::MathOverflow)?;
+    check!(
+        referrer_rewards_usdc.owner.eq(&referrer_rewards.key())
+            && referrer_rewards_usdc.mint.eq(&ctx.accounts.usdc_mint.key()),
+        PyraError::MissingReferrerRewards
+    );
 
-        ctx.accounts.vault.next_timeframe_reset_timestamp = ctx
-            .accounts
-            .vault
-            .next_timeframe_reset_timestamp
-            .checked_add(seconds_to_add)
-            .ok_or(PyraError::MathOverflow)?;
-        ctx.accounts.vault.remaining_spend_limit_per_timeframe =
-            ctx.accounts.vault.spend_limit_per_timeframe;
-    }
+    // Referral fee share BPS is validated against MAX_REFERRAL_FEE_SHARE_BPS when the global config is set
+    let referrer_fee_amount = fee_amount
+        .checked_mul(ctx.accounts.global_config.referral_fee_share_bps)
+        .ok_or(PyraError::MathOverflow)?
+        .checked_div(10_000)
+        .ok_or(PyraError::MathOverflow)?;
 
-    // Check remaining spend limit
-    if ctx.accounts.vault.remaining_spend_limit_per_timeframe < amount_usdc_base_units {
-        let error_code = PyraError::InsufficientTimeframeSpendLimit;
-        anchor_lang::prelude::msg!(
-            "Error \"{}\" ({} < {}) thrown at {}:{}",
-            error_code,
-            ctx.accounts.vault.remaining_spend_limit_per_timeframe,
-            amount_usdc_base_units,
-            file!(),
-            line!()
-        );
-        return Err(error_code.into());
+    if referrer_fee_amount > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.mule.to_account_info(),
+                    to: referrer_rewards_usdc.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.usdc_mint.to_account_info(),
+                },
+                vault_signer,
+            ),
+            referrer_fee_amount,
+            ctx.accounts.usdc_mint.decimals,
+        )?;
     }
 
-    // Adjust remaining spend limit
-    ctx.accounts.vault.remaining_spend_limit_per_timeframe = ctx
-        .accounts
-        .vault
-        .remaining_spend_limit_per_timeframe
-        .checked_sub(amount_usdc_base_units)
-        .ok_or(PyraError::InsufficientTimeframeSpendLimit)?;
+    referrer_rewards.unclaimed_usdc_base_units = referrer_rewards
+        .unclaimed_usdc_base_units
+        .checked_add(referrer_fee_amount)
+        .ok_or(PyraError::MathOverflow)?;
+    referrer_rewards.total_earned_usdc_base_units = referrer_rewards
+        .total_earned_usdc_base_units
+        .checked_add(referrer_fee_amount)
+        .ok_or(PyraError::MathOverflow)?;
 
-    Ok(())
+    Ok(referrer_fee_amount)
 }
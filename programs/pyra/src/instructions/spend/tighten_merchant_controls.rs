@@ -0,0 +1,61 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::MerchantControlsUpdated,
+    state::{MerchantControls, Vault, VaultMerchantControls},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TightenMerchantControls<'info> {
+    #[account(
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"vault_merchant_controls".as_ref(), vault.key().as_ref()],
+        bump,
+        payer = owner,
+        space = VaultMerchantControls::INIT_SPACE
+    )]
+    pub vault_merchant_controls: Box<Account<'info, VaultMerchantControls>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Instantly replaces the vault's merchant controls, once every spend they allow was already allowed. Loosening goes through a time locked order.
+pub fn tighten_merchant_controls_handler(
+    ctx: Context<TightenMerchantControls>,
+    merchant_controls: MerchantControls,
+) -> Result<()> {
+    merchant_controls.validate()?;
+
+    let vault_merchant_controls = &mut ctx.accounts.vault_merchant_controls;
+    check!(
+        merchant_controls.is_tightening(&vault_merchant_controls.controls),
+        PyraError::MerchantControlsNotTightening
+    );
+
+    vault_merchant_controls.vault = ctx.accounts.vault.key();
+    vault_merchant_controls.bump = ctx.bumps.vault_merchant_controls;
+    vault_merchant_controls.set_controls(merchant_controls.clone());
+
+    emit_cpi!(MerchantControlsUpdated {
+        owner: ctx.accounts.owner.key(),
+        authority: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        merchant_controls,
+        is_time_locked: false,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+mod init_drift_sub_account;
+pub use init_drift_sub_account::*;
+
+mod close_drift_sub_account;
+pub use close_drift_sub_account::*;
+
+mod transfer_collateral;
+pub use transfer_collateral::*;
+
+mod initiate_collateral_transfer;
+pub use initiate_collateral_transfer::*;
+
+mod fulfil_collateral_transfer;
+pub use fulfil_collateral_transfer::*;
+
+mod cancel_collateral_transfer;
+pub use cancel_collateral_transfer::*;
+
+mod initiate_spend_sub_account;
+pub use initiate_spend_sub_account::*;
+
+mod fulfil_spend_sub_account;
+pub use fulfil_spend_sub_account::*;
+
+mod cancel_spend_sub_account;
+pub use cancel_spend_sub_account::*;
@@ -0,0 +1,70 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::SpendSubAccountCancelled,
+    state::{SpendSubAccountOrder, Vault},
+    utils::close_time_lock,
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelSpendSubAccount<'info> {
+    #[account(mut)]
+    pub spend_sub_account_order: Box<Account<'info, SpendSubAccountOrder>>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // No has_one, so orders left over from a previous owner can still be cancelled
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+/// Cancels a pending spend sub-account change, returning the order's rent
+pub fn cancel_spend_sub_account_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelSpendSubAccount<'info>>,
+) -> Result<()> {
+    check!(
+        ctx.accounts
+            .spend_sub_account_order
+            .time_lock
+            .owner
+            .eq(&ctx.accounts.owner.key()),
+        PyraError::InvalidTimeLockOwner
+    );
+
+    check!(
+        ctx.accounts
+            .spend_sub_account_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidSpendSubAccountOrder
+    );
+
+    close_time_lock(
+        &ctx.accounts.spend_sub_account_order,
+        &ctx.accounts.time_lock_rent_payer.to_account_info(),
+    )?;
+
+    ctx.accounts
+        .vault
+        .remove_open_order(&ctx.accounts.spend_sub_account_order.time_lock);
+
+    emit_cpi!(SpendSubAccountCancelled {
+        owner: ctx.accounts.owner.key(),
+        spend_sub_account_order: ctx.accounts.spend_sub_account_order.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::DriftSubAccountClosed,
+    state::Vault,
+};
+use anchor_lang::prelude::*;
+use drift::{
+    cpi::{accounts::DeleteUser, delete_user},
+    program::Drift,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(sub_account_id: u16)]
+pub struct CloseDriftSubAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Seeds ensure the sub_account_id matches, the Drift CPI performs the remaining checks
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), vault.key().as_ref(), sub_account_id.to_le_bytes().as_ref()],
+        seeds::program = drift_program.key(),
+        bump
+    )]
+    pub drift_user: UncheckedAccount<'info>,
+
+    /// CHECK: Passed into Drift CPI (which performs the security checks)
+    #[account(mut)]
+    pub drift_user_stats: UncheckedAccount<'info>,
+
+    /// CHECK: Passed into Drift CPI (which performs the security checks)
+    #[account(mut)]
+    pub drift_state: UncheckedAccount<'info>,
+
+    pub drift_program: Program<'info, Drift>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Closes one of the vault's Drift sub-accounts, returning its rent to the owner. The spend sub-account can only be closed by close_user. Never paused, as it only closes an empty account.
+pub fn close_drift_sub_account_handler(
+    ctx: Context<CloseDriftSubAccount>,
+    sub_account_id: u16,
+) -> Result<()> {
+    check!(
+        sub_account_id != ctx.accounts.vault.spend_sub_account_id,
+        PyraError::CannotCloseSpendSubAccount
+    );
+
+    let vault_bump = ctx.accounts.vault.bump;
+    let vault_id = ctx.accounts.vault.vault_id;
+    let seeds_vault = &[b"vault", vault_id.as_ref(), &[vault_bump]];
+    let signer_seeds_vault = &[&seeds_vault[..]];
+
+    let vault_lamports_before_cpi = ctx.accounts.vault.to_account_info().lamports();
+
+    let delete_user_cpi_context = CpiContext::new_with_signer(
+        ctx.accounts.drift_program.to_account_info(),
+        DeleteUser {
+            user: ctx.accounts.drift_user.to_account_info(),
+            user_stats: ctx.accounts.drift_user_stats.to_account_info(),
+            state: ctx.accounts.drift_state.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        },
+        signer_seeds_vault,
+    );
+    delete_user(delete_user_cpi_context)?;
+
+    // Check vault data to ensure it hasn't been drained by the Drift CPI
+    let vault_lamports_after_cpi = ctx.accounts.vault.to_account_info().lamports();
+    check!(
+        vault_lamports_after_cpi >= vault_lamports_before_cpi,
+        PyraError::IllegalVaultCPIModification
+    );
+
+    // Drift returns the sub-account's rent to the vault (as its authority), pass it on to the owner who paid it
+    let lamports_reclaimed = vault_lamports_after_cpi.saturating_sub(vault_lamports_before_cpi);
+    if lamports_reclaimed > 0 {
+        ctx.accounts.vault.sub_lamports(lamports_reclaimed)?;
+        ctx.accounts.owner.add_lamports(lamports_reclaimed)?;
+    }
+
+    ctx.accounts.vault.drift_sub_account_count = ctx
+        .accounts
+        .vault
+        .drift_sub_account_count
+        .checked_sub(1)
+        .ok_or(PyraError::MathOverflow)?;
+
+    emit_cpi!(DriftSubAccountClosed {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        sub_account_id,
+        drift_sub_account_count: ctx.accounts.vault.drift_sub_account_count,
+        lamports_reclaimed,
+    });
+
+    Ok(())
+}
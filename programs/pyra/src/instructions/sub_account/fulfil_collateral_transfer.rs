@@ -0,0 +1,168 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::CollateralTransferred,
+    state::{CollateralTransferOrder, GlobalConfig, ProtocolFeature, Vault},
+    utils::{close_time_lock, validate_vault_time_lock},
+};
+use anchor_lang::prelude::*;
+use drift::{
+    cpi::accounts::TransferDeposit as DriftTransferDeposit,
+    cpi::transfer_deposit as drift_transfer_deposit, program::Drift,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FulfilCollateralTransfer<'info> {
+    #[account(mut)]
+    pub collateral_transfer_order: Box<Account<'info, CollateralTransferOrder>>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Any account, once it has a vault (order checked in handler)
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: Seeds ensure the sub-account matches the order, the Drift CPI performs the remaining checks
+    #[account(
+        mut,
+        seeds = [
+            b"user".as_ref(),
+            vault.key().as_ref(),
+            collateral_transfer_order.from_sub_account_id.to_le_bytes().as_ref()
+        ],
+        seeds::program = drift_program.key(),
+        bump
+    )]
+    pub from_drift_user: UncheckedAccount<'info>,
+
+    /// CHECK: Seeds ensure the sub-account matches the order, the Drift CPI performs the remaining checks
+    #[account(
+        mut,
+        seeds = [
+            b"user".as_ref(),
+            vault.key().as_ref(),
+            collateral_transfer_order.to_sub_account_id.to_le_bytes().as_ref()
+        ],
+        seeds::program = drift_program.key(),
+        bump
+    )]
+    pub to_drift_user: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    #[account(mut)]
+    pub drift_user_stats: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    pub drift_state: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    pub spot_market_vault: UncheckedAccount<'info>,
+
+    pub drift_program: Program<'info, Drift>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Permissionless function to fulfil a collateral transfer order, moving collateral between the vault's Drift sub-accounts
+pub fn fulfil_collateral_transfer_handler<'info>(
+    mut ctx: Context<'_, '_, '_, 'info, FulfilCollateralTransfer<'info>>,
+) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::Withdrawals),
+        PyraError::ProtocolPaused
+    );
+
+    check!(
+        ctx.accounts
+            .collateral_transfer_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidCollateralTransferOrder
+    );
+
+    let (amount_base_units, drift_market_index, from_sub_account_id, to_sub_account_id) =
+        get_order_data(&mut ctx)?;
+
+    let vault_bump = ctx.accounts.vault.bump;
+    let vault_id = ctx.accounts.vault.vault_id;
+    let seeds_vault = &[b"vault", vault_id.as_ref(), &[vault_bump]];
+    let vault_signer = &[&seeds_vault[..]];
+
+    let mut cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.drift_program.to_account_info(),
+        DriftTransferDeposit {
+            from_user: ctx.accounts.from_drift_user.to_account_info(),
+            to_user: ctx.accounts.to_drift_user.to_account_info(),
+            user_stats: ctx.accounts.drift_user_stats.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+            state: ctx.accounts.drift_state.to_account_info(),
+            spot_market_vault: ctx.accounts.spot_market_vault.to_account_info(),
+        },
+        vault_signer,
+    );
+
+    cpi_ctx.remaining_accounts = ctx.remaining_accounts.to_vec();
+
+    drift_transfer_deposit(cpi_ctx, drift_market_index, amount_base_units)?;
+
+    ctx.accounts
+        .vault
+        .remove_open_order(&ctx.accounts.collateral_transfer_order.time_lock);
+
+    emit_cpi!(CollateralTransferred {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        drift_market_index,
+        amount_base_units,
+        from_sub_account_id,
+        to_sub_account_id,
+        collateral_transfer_order: Some(ctx.accounts.collateral_transfer_order.key()),
+    });
+
+    Ok(())
+}
+
+fn get_order_data(ctx: &mut Context<FulfilCollateralTransfer>) -> Result<(u64, u16, u16, u16)> {
+    validate_vault_time_lock(
+        &ctx.accounts.vault,
+        &ctx.accounts.collateral_transfer_order.time_lock,
+    )?;
+
+    let amount_base_units = ctx.accounts.collateral_transfer_order.amount_base_units;
+    let drift_market_index = ctx.accounts.collateral_transfer_order.drift_market_index;
+    let from_sub_account_id = ctx.accounts.collateral_transfer_order.from_sub_account_id;
+    let to_sub_account_id = ctx.accounts.collateral_transfer_order.to_sub_account_id;
+
+    close_time_lock(
+        &ctx.accounts.collateral_transfer_order,
+        &ctx.accounts.time_lock_rent_payer.to_account_info(),
+    )?;
+
+    Ok((
+        amount_base_units,
+        drift_market_index,
+        from_sub_account_id,
+        to_sub_account_id,
+    ))
+}
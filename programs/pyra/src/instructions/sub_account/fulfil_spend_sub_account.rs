@@ -0,0 +1,102 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::SpendSubAccountUpdated,
+    state::{GlobalConfig, ProtocolFeature, SpendSubAccountOrder, Vault},
+    utils::{close_time_lock, validate_vault_time_lock},
+};
+use anchor_lang::prelude::*;
+use drift::{program::Drift, state::user::User as DriftUser};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FulfilSpendSubAccount<'info> {
+    #[account(mut)]
+    pub spend_sub_account_order: Box<Account<'info, SpendSubAccountOrder>>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Any account, once it has a vault (order checked in handler)
+    pub owner: UncheckedAccount<'info>,
+
+    // Ensures the sub-account hasn't been closed since the order was created
+    #[account(
+        seeds = [
+            b"user".as_ref(),
+            vault.key().as_ref(),
+            spend_sub_account_order.spend_sub_account_id.to_le_bytes().as_ref()
+        ],
+        seeds::program = drift_program.key(),
+        bump
+    )]
+    pub drift_user: AccountLoader<'info, DriftUser>,
+
+    pub drift_program: Program<'info, Drift>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Permissionless function to fulfil a spend sub-account order, switching the Drift sub-account that funds card spends
+pub fn fulfil_spend_sub_account_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, FulfilSpendSubAccount<'info>>,
+) -> Result<()> {
+    check!(
+        !ctx.accounts.global_config.is_paused(ProtocolFeature::Spend),
+        PyraError::ProtocolPaused
+    );
+
+    check!(
+        ctx.accounts
+            .spend_sub_account_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidSpendSubAccountOrder
+    );
+
+    validate_vault_time_lock(
+        &ctx.accounts.vault,
+        &ctx.accounts.spend_sub_account_order.time_lock,
+    )?;
+
+    let spend_sub_account_id = ctx.accounts.spend_sub_account_order.spend_sub_account_id;
+
+    close_time_lock(
+        &ctx.accounts.spend_sub_account_order,
+        &ctx.accounts.time_lock_rent_payer.to_account_info(),
+    )?;
+
+    let previous_spend_sub_account_id = ctx.accounts.vault.spend_sub_account_id;
+    ctx.accounts.vault.spend_sub_account_id = spend_sub_account_id;
+
+    ctx.accounts
+        .vault
+        .remove_open_order(&ctx.accounts.spend_sub_account_order.time_lock);
+
+    emit_cpi!(SpendSubAccountUpdated {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        previous_spend_sub_account_id,
+        spend_sub_account_id,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::DriftSubAccountInitialized,
+    state::{GlobalConfig, ProtocolFeature, Vault},
+};
+use anchor_lang::prelude::*;
+use drift::{
+    cpi::accounts::InitializeUser as InitializeUserDrift,
+    cpi::initialize_user as initialize_user_drift, program::Drift,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitDriftSubAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Passed into Drift CPI (which performs the security checks)
+    #[account(mut)]
+    pub drift_user: UncheckedAccount<'info>,
+
+    /// CHECK: Passed into Drift CPI (which performs the security checks)
+    #[account(mut)]
+    pub drift_user_stats: UncheckedAccount<'info>,
+
+    /// CHECK: Passed into Drift CPI (which performs the security checks)
+    #[account(mut)]
+    pub drift_state: UncheckedAccount<'info>,
+
+    pub drift_program: Program<'info, Drift>,
+
+    pub rent: Sysvar<'info, Rent>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Opens an additional Drift sub-account for the vault, paid for by the owner. Drift requires sub-account ids to be created in order.
+pub fn init_drift_sub_account_handler(
+    ctx: Context<InitDriftSubAccount>,
+    sub_account_id: u16,
+) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::UserInit),
+        PyraError::ProtocolPaused
+    );
+
+    let vault_bump = ctx.accounts.vault.bump;
+    let vault_id = ctx.accounts.vault.vault_id;
+    let seeds_vault = &[b"vault", vault_id.as_ref(), &[vault_bump]];
+    let signer_seeds_vault = &[&seeds_vault[..]];
+
+    let vault_lamports_before_cpi = ctx.accounts.vault.to_account_info().lamports();
+
+    let create_user_cpi_context = CpiContext::new_with_signer(
+        ctx.accounts.drift_program.to_account_info(),
+        InitializeUserDrift {
+            user: ctx.accounts.drift_user.to_account_info(),
+            user_stats: ctx.accounts.drift_user_stats.to_account_info(),
+            state: ctx.accounts.drift_state.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+            payer: ctx.accounts.owner.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        },
+        signer_seeds_vault,
+    );
+    initialize_user_drift(create_user_cpi_context, sub_account_id, [0; 32])?;
+
+    // Check vault data to ensure it hasn't been drained by the Drift CPI
+    let vault_lamports_after_cpi = ctx.accounts.vault.to_account_info().lamports();
+    check!(
+        vault_lamports_after_cpi >= vault_lamports_before_cpi,
+        PyraError::IllegalVaultCPIModification
+    );
+
+    ctx.accounts.vault.drift_sub_account_count = ctx
+        .accounts
+        .vault
+        .drift_sub_account_count
+        .checked_add(1)
+        .ok_or(PyraError::MathOverflow)?;
+
+    emit_cpi!(DriftSubAccountInitialized {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        sub_account_id,
+        drift_sub_account_count: ctx.accounts.vault.drift_sub_account_count,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use crate::{
+    check,
+    config::{PyraError, ANCHOR_DISCRIMINATOR, VAULT_VERSION},
+    events::CollateralTransferInitiated,
+    state::{CollateralTransferOrder, GlobalConfig, ProtocolFeature, TimeLock, Vault},
+    utils::{allocate_time_lock_owner_payer, allocate_time_lock_program_payer},
+};
+use anchor_lang::{prelude::*, Discriminator};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitiateCollateralTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub collateral_transfer_order: Signer<'info>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Creates a time locked order to move collateral between the vault's Drift sub-accounts, which can be fulfilled permissionlessly once the time lock has expired. Required for transfers out of the spend sub-account, for the same reason as withdraws.
+pub fn initiate_collateral_transfer_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitiateCollateralTransfer<'info>>,
+    amount_base_units: u64,
+    drift_market_index: u16,
+    from_sub_account_id: u16,
+    to_sub_account_id: u16,
+) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::Withdrawals),
+        PyraError::ProtocolPaused
+    );
+
+    check!(
+        from_sub_account_id != to_sub_account_id,
+        PyraError::IdenticalDriftSubAccounts
+    );
+
+    let is_owner_payer = ctx
+        .accounts
+        .time_lock_rent_payer
+        .key()
+        .eq(&ctx.accounts.owner.key());
+
+    if is_owner_payer {
+        allocate_time_lock_owner_payer(
+            &ctx.accounts.owner,
+            &ctx.accounts.collateral_transfer_order,
+            &ctx.accounts.system_program,
+            CollateralTransferOrder::INIT_SPACE,
+        )?;
+    } else {
+        allocate_time_lock_program_payer(
+            &ctx.accounts.time_lock_rent_payer.to_account_info(),
+            &ctx.accounts.collateral_transfer_order,
+            &ctx.accounts.system_program,
+            CollateralTransferOrder::INIT_SPACE,
+        )?;
+    }
+
+    let current_slot = Clock::get()?.slot;
+    let release_slot = current_slot
+        .checked_add(ctx.accounts.global_config.time_lock_duration_slots)
+        .ok_or(PyraError::MathOverflow)?;
+
+    let collateral_transfer_order_data = CollateralTransferOrder {
+        time_lock: TimeLock {
+            owner: ctx.accounts.owner.key(),
+            owner_epoch: ctx.accounts.vault.owner_epoch,
+            is_owner_payer,
+            release_slot,
+        },
+        vault: ctx.accounts.vault.key(),
+        amount_base_units,
+        drift_market_index,
+        from_sub_account_id,
+        to_sub_account_id,
+    };
+    let collateral_transfer_order_data_vec = collateral_transfer_order_data.try_to_vec()?;
+
+    let mut data = ctx
+        .accounts
+        .collateral_transfer_order
+        .try_borrow_mut_data()?;
+    data[..ANCHOR_DISCRIMINATOR].copy_from_slice(&CollateralTransferOrder::DISCRIMINATOR);
+    data[ANCHOR_DISCRIMINATOR..].copy_from_slice(&collateral_transfer_order_data_vec[..]);
+    drop(data);
+
+    ctx.accounts.vault.add_open_order()?;
+
+    emit_cpi!(CollateralTransferInitiated {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        collateral_transfer_order: ctx.accounts.collateral_transfer_order.key(),
+        drift_market_index,
+        amount_base_units,
+        from_sub_account_id,
+        to_sub_account_id,
+        release_slot,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use crate::{
+    check,
+    config::{PyraError, ANCHOR_DISCRIMINATOR, VAULT_VERSION},
+    events::SpendSubAccountInitiated,
+    state::{GlobalConfig, SpendSubAccountOrder, TimeLock, Vault},
+    utils::{allocate_time_lock_owner_payer, allocate_time_lock_program_payer},
+};
+use anchor_lang::{prelude::*, Discriminator};
+use drift::{program::Drift, state::user::User as DriftUser};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(spend_sub_account_id: u16)]
+pub struct InitiateSpendSubAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub spend_sub_account_order: Signer<'info>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // Ensures the sub-account has been opened
+    #[account(
+        seeds = [b"user".as_ref(), vault.key().as_ref(), spend_sub_account_id.to_le_bytes().as_ref()],
+        seeds::program = drift_program.key(),
+        bump
+    )]
+    pub drift_user: AccountLoader<'info, DriftUser>,
+
+    pub drift_program: Program<'info, Drift>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Creates a time locked order to change which Drift sub-account funds card spends. Time locked as switching sub-account changes the funds available to the card.
+pub fn initiate_spend_sub_account_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitiateSpendSubAccount<'info>>,
+    spend_sub_account_id: u16,
+) -> Result<()> {
+    check!(
+        spend_sub_account_id != ctx.accounts.vault.spend_sub_account_id,
+        PyraError::SpendSubAccountAlreadySet
+    );
+
+    let is_owner_payer = ctx
+        .accounts
+        .time_lock_rent_payer
+        .key()
+        .eq(&ctx.accounts.owner.key());
+
+    if is_owner_payer {
+        allocate_time_lock_owner_payer(
+            &ctx.accounts.owner,
+            &ctx.accounts.spend_sub_account_order,
+            &ctx.accounts.system_program,
+            SpendSubAccountOrder::INIT_SPACE,
+        )?;
+    } else {
+        allocate_time_lock_program_payer(
+            &ctx.accounts.time_lock_rent_payer.to_account_info(),
+            &ctx.accounts.spend_sub_account_order,
+            &ctx.accounts.system_program,
+            SpendSubAccountOrder::INIT_SPACE,
+        )?;
+    }
+
+    let current_slot = Clock::get()?.slot;
+    let release_slot = current_slot
+        .checked_add(ctx.accounts.global_config.time_lock_duration_slots)
+        .ok_or(PyraError::MathOverflow)?;
+
+    let spend_sub_account_order_data = SpendSubAccountOrder {
+        time_lock: TimeLock {
+            owner: ctx.accounts.owner.key(),
+            owner_epoch: ctx.accounts.vault.owner_epoch,
+            is_owner_payer,
+            release_slot,
+        },
+        vault: ctx.accounts.vault.key(),
+        spend_sub_account_id,
+    };
+    let spend_sub_account_order_data_vec = spend_sub_account_order_data.try_to_vec()?;
+
+    let mut data = ctx.accounts.spend_sub_account_order.try_borrow_mut_data()?;
+    data[..ANCHOR_DISCRIMINATOR].copy_from_slice(&SpendSubAccountOrder::DISCRIMINATOR);
+    data[ANCHOR_DISCRIMINATOR..].copy_from_slice(&spend_sub_account_order_data_vec[..]);
+    drop(data);
+
+    ctx.accounts.vault.add_open_order()?;
+
+    emit_cpi!(SpendSubAccountInitiated {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        spend_sub_account_order: ctx.accounts.spend_sub_account_order.key(),
+        spend_sub_account_id,
+        release_slot,
+    });
+
+    Ok(())
+}
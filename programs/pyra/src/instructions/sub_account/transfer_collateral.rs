@@ -0,0 +1,127 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::CollateralTransferred,
+    state::{GlobalConfig, ProtocolFeature, Vault},
+};
+use anchor_lang::prelude::*;
+use drift::{
+    cpi::accounts::TransferDeposit as DriftTransferDeposit,
+    cpi::transfer_deposit as drift_transfer_deposit, program::Drift,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(
+    amount_base_units: u64,
+    drift_market_index: u16,
+    from_sub_account_id: u16,
+    to_sub_account_id: u16
+)]
+pub struct TransferCollateral<'info> {
+    #[account(
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Seeds ensure the sub_account_id matches, the Drift CPI performs the remaining checks
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), vault.key().as_ref(), from_sub_account_id.to_le_bytes().as_ref()],
+        seeds::program = drift_program.key(),
+        bump
+    )]
+    pub from_drift_user: UncheckedAccount<'info>,
+
+    /// CHECK: Seeds ensure the sub_account_id matches, the Drift CPI performs the remaining checks
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), vault.key().as_ref(), to_sub_account_id.to_le_bytes().as_ref()],
+        seeds::program = drift_program.key(),
+        bump
+    )]
+    pub to_drift_user: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    #[account(mut)]
+    pub drift_user_stats: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    pub drift_state: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    pub spot_market_vault: UncheckedAccount<'info>,
+
+    pub drift_program: Program<'info, Drift>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Instantly moves collateral between two of the vault's Drift sub-accounts. Transfers out of the spend sub-account reduce the funds available to the card, so must use a time locked collateral transfer order instead.
+pub fn transfer_collateral_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, TransferCollateral<'info>>,
+    amount_base_units: u64,
+    drift_market_index: u16,
+    from_sub_account_id: u16,
+    to_sub_account_id: u16,
+) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::Withdrawals),
+        PyraError::ProtocolPaused
+    );
+
+    check!(
+        from_sub_account_id != to_sub_account_id,
+        PyraError::IdenticalDriftSubAccounts
+    );
+
+    check!(
+        from_sub_account_id != ctx.accounts.vault.spend_sub_account_id,
+        PyraError::SpendSubAccountTransferTimeLocked
+    );
+
+    let vault_bump = ctx.accounts.vault.bump;
+    let vault_id = ctx.accounts.vault.vault_id;
+    let seeds_vault = &[b"vault", vault_id.as_ref(), &[vault_bump]];
+    let vault_signer = &[&seeds_vault[..]];
+
+    let mut cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.drift_program.to_account_info(),
+        DriftTransferDeposit {
+            from_user: ctx.accounts.from_drift_user.to_account_info(),
+            to_user: ctx.accounts.to_drift_user.to_account_info(),
+            user_stats: ctx.accounts.drift_user_stats.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+            state: ctx.accounts.drift_state.to_account_info(),
+            spot_market_vault: ctx.accounts.spot_market_vault.to_account_info(),
+        },
+        vault_signer,
+    );
+
+    cpi_ctx.remaining_accounts = ctx.remaining_accounts.to_vec();
+
+    drift_transfer_deposit(cpi_ctx, drift_market_index, amount_base_units)?;
+
+    emit_cpi!(CollateralTransferred {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        drift_market_index,
+        amount_base_units,
+        from_sub_account_id,
+        to_sub_account_id,
+        collateral_transfer_order: None,
+    });
+
+    Ok(())
+}
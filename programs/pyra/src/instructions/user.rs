@@ -4,5 +4,53 @@ pub use init_user::*;
 mod close_user;
 pub use close_user::*;
 
+mod sweep_deposit_address;
+pub use sweep_deposit_address::*;
+
+mod initiate_settle_and_close;
+pub use initiate_settle_and_close::*;
+
+mod settle_withdraw;
+pub use settle_withdraw::*;
+
+mod cancel_settle_and_close;
+pub use cancel_settle_and_close::*;
+
 mod upgrade_vault;
 pub use upgrade_vault::*;
+
+mod attest_no_uncounted_orders;
+pub use attest_no_uncounted_orders::*;
+
+mod initiate_owner_transfer;
+pub use initiate_owner_transfer::*;
+
+mod accept_owner_transfer;
+pub use accept_owner_transfer::*;
+
+mod cancel_owner_transfer;
+pub use cancel_owner_transfer::*;
+
+mod set_vault_delegate;
+pub use set_vault_delegate::*;
+
+mod revoke_vault_delegate;
+pub use revoke_vault_delegate::*;
+
+mod close_vault_delegates;
+pub use close_vault_delegates::*;
+
+mod set_vault_guardians;
+pub use set_vault_guardians::*;
+
+mod remove_vault_guardians;
+pub use remove_vault_guardians::*;
+
+mod initiate_recovery;
+pub use initiate_recovery::*;
+
+mod veto_recovery;
+pub use veto_recovery::*;
+
+mod complete_recovery;
+pub use complete_recovery::*;
@@ -0,0 +1,78 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::OwnerTransferAccepted,
+    state::{OwnerTransferOrder, Vault},
+    utils::{close_time_lock, validate_vault_time_lock},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptOwnerTransfer<'info> {
+    #[account(mut)]
+    pub owner_transfer_order: Box<Account<'info, OwnerTransferOrder>>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub new_owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Any account, once it has a vault (order checked in handler)
+    pub owner: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accepts an owner transfer once the time lock has expired. Orders become invalid if the owner changes before they're accepted. Never paused, as the transfer can already be cancelled by the owner during its time lock.
+pub fn accept_owner_transfer_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, AcceptOwnerTransfer<'info>>,
+) -> Result<()> {
+    validate_vault_time_lock(
+        &ctx.accounts.vault,
+        &ctx.accounts.owner_transfer_order.time_lock,
+    )?;
+
+    check!(
+        ctx.accounts
+            .owner_transfer_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidOwnerTransferOrder
+    );
+    check!(
+        ctx.accounts
+            .owner_transfer_order
+            .new_owner
+            .eq(&ctx.accounts.new_owner.key()),
+        PyraError::InvalidOwnerTransferOrder
+    );
+
+    close_time_lock(
+        &ctx.accounts.owner_transfer_order,
+        &ctx.accounts.time_lock_rent_payer.to_account_info(),
+    )?;
+
+    let previous_owner = ctx.accounts.vault.owner;
+    ctx.accounts
+        .vault
+        .change_owner(ctx.accounts.new_owner.key())?;
+
+    emit_cpi!(OwnerTransferAccepted {
+        previous_owner,
+        new_owner: ctx.accounts.new_owner.key(),
+        vault: ctx.accounts.vault.key(),
+    });
+
+    Ok(())
+}
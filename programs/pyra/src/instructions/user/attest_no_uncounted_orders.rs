@@ -0,0 +1,33 @@
+use crate::{
+    config::{PyraError, VAULT_VERSION},
+    events::UncountedOrdersAttested,
+    state::Vault,
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AttestNoUncountedOrders<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    pub owner: Signer<'info>,
+}
+
+/// Owner attests that every order opened before the vault was upgraded from an unversioned layout has been fulfilled or cancelled, allowing the vault to close
+pub fn attest_no_uncounted_orders_handler(ctx: Context<AttestNoUncountedOrders>) -> Result<()> {
+    ctx.accounts.vault.may_have_uncounted_orders = false;
+
+    emit_cpi!(UncountedOrdersAttested {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::OwnerTransferCancelled,
+    state::{OwnerTransferOrder, Vault},
+    utils::close_time_lock,
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelOwnerTransfer<'info> {
+    #[account(mut)]
+    pub owner_transfer_order: Box<Account<'info, OwnerTransferOrder>>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // No has_one, so orders left over from a previous owner can still be cancelled
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+/// Cancels a pending owner transfer, returning the order's rent
+pub fn cancel_owner_transfer_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelOwnerTransfer<'info>>,
+) -> Result<()> {
+    check!(
+        ctx.accounts
+            .owner_transfer_order
+            .time_lock
+            .owner
+            .eq(&ctx.accounts.owner.key()),
+        PyraError::InvalidTimeLockOwner
+    );
+
+    check!(
+        ctx.accounts
+            .owner_transfer_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidOwnerTransferOrder
+    );
+
+    close_time_lock(
+        &ctx.accounts.owner_transfer_order,
+        &ctx.accounts.time_lock_rent_payer.to_account_info(),
+    )?;
+
+    ctx.accounts
+        .vault
+        .remove_open_order(&ctx.accounts.owner_transfer_order.time_lock);
+
+    emit_cpi!(OwnerTransferCancelled {
+        owner: ctx.accounts.owner.key(),
+        owner_transfer_order: ctx.accounts.owner_transfer_order.key(),
+    });
+
+    Ok(())
+}
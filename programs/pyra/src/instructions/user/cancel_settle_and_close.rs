@@ -0,0 +1,67 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::SettleAndCloseCancelled,
+    state::{SettleAndCloseOrder, Vault},
+    utils::close_time_lock,
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelSettleAndClose<'info> {
+    #[account(mut)]
+    pub settle_and_close_order: Box<Account<'info, SettleAndCloseOrder>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // No has_one, so orders left over from a previous owner can still be cancelled
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+/// Cancels a pending settle and close, returning the order's rent. The card stays frozen, so unfreezing it still requires the unfreeze_card time lock.
+pub fn cancel_settle_and_close_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelSettleAndClose<'info>>,
+) -> Result<()> {
+    check!(
+        ctx.accounts
+            .settle_and_close_order
+            .time_lock
+            .owner
+            .eq(&ctx.accounts.owner.key()),
+        PyraError::InvalidTimeLockOwner
+    );
+
+    check!(
+        ctx.accounts
+            .settle_and_close_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidSettleAndCloseOrder
+    );
+
+    close_time_lock(
+        &ctx.accounts.settle_and_close_order,
+        &ctx.accounts.owner.to_account_info(),
+    )?;
+
+    ctx.accounts
+        .vault
+        .remove_open_order(&ctx.accounts.settle_and_close_order.time_lock);
+
+    emit_cpi!(SettleAndCloseCancelled {
+        owner: ctx.accounts.owner.key(),
+        settle_and_close_order: ctx.accounts.settle_and_close_order.key(),
+    });
+
+    Ok(())
+}
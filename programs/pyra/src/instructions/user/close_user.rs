@@ -1,8 +1,12 @@
 use crate::{
     check,
-    config::{PyraError, INIT_ACCOUNT_RENT_FEE},
-    state::Vault,
-    utils::validate_account_fresh,
+    config::{PyraError, DEPOSIT_ADDRESS_SPACE, INIT_ACCOUNT_RENT_FEE, VAULT_VERSION},
+    events::UserClosed,
+    state::{DriftMarketRegistry, RentPayer, RentTreasury, SettleAndCloseOrder, Vault},
+    utils::{
+        close_program_account, close_time_lock, has_spot_borrows, sweep_token_accounts,
+        validate_account_fresh, validate_vault_time_lock,
+    },
 };
 use anchor_lang::{
     prelude::*,
@@ -11,16 +15,20 @@ use anchor_lang::{
 use drift::{
     cpi::{accounts::DeleteUser, delete_user},
     program::Drift,
+    state::user::User as DriftUser,
 };
 use solana_program::{program::invoke_signed, system_instruction};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CloseUser<'info> {
     #[account(
         mut,
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
         bump = vault.bump,
-        close = init_rent_payer
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        close = init_rent_payer,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
     )]
     pub vault: Box<Account<'info, Vault>>,
 
@@ -35,9 +43,14 @@ pub struct CloseUser<'info> {
     )]
     pub init_rent_payer: UncheckedAccount<'info>,
 
-    /// CHECK: Passed into Drift CPI (which performs the security checks)
-    #[account(mut)]
-    pub drift_user: UncheckedAccount<'info>,
+    // Seeds ensure this is the spend sub-account (the last one open), the Drift CPI performs the remaining checks
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), vault.key().as_ref(), vault.spend_sub_account_id.to_le_bytes().as_ref()],
+        seeds::program = drift_program.key(),
+        bump
+    )]
+    pub drift_user: AccountLoader<'info, DriftUser>,
 
     /// CHECK: Passed into Drift CPI (which performs the security checks)
     #[account(mut)]
@@ -58,13 +71,105 @@ pub struct CloseUser<'info> {
         bump
     )]
     pub deposit_address: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"rent_treasury"],
+        bump = rent_treasury.bump
+    )]
+    pub rent_treasury: Box<Account<'info, RentTreasury>>,
+
+    #[account(
+        seeds = [b"drift_market_registry"],
+        bump = drift_market_registry.bump
+    )]
+    pub drift_market_registry: Box<Account<'info, DriftMarketRegistry>>,
+
+    // Only required when closing through a settle and close order, which is consumed here
+    #[account(mut)]
+    pub settle_and_close_order: Option<Box<Account<'info, SettleAndCloseOrder>>>,
+
+    /// CHECK: Checked in handler against the vault, only required for sponsored vaults
+    #[account(mut)]
+    pub sponsor: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Closed in handler if it exists, seeds ensure it's the vault's delegates
+    #[account(
+        mut,
+        seeds = [b"vault_delegates".as_ref(), vault.key().as_ref()],
+        bump
+    )]
+    pub vault_delegates: UncheckedAccount<'info>,
+
+    /// CHECK: Closed in handler if it exists, seeds ensure it's the vault's merchant controls
+    #[account(
+        mut,
+        seeds = [b"vault_merchant_controls".as_ref(), vault.key().as_ref()],
+        bump
+    )]
+    pub vault_merchant_controls: UncheckedAccount<'info>,
+
+    /// CHECK: Closed in handler if it exists, seeds ensure it's the vault's guardians
+    #[account(
+        mut,
+        seeds = [b"vault_guardians".as_ref(), vault.key().as_ref()],
+        bump
+    )]
+    pub vault_guardians: UncheckedAccount<'info>,
 }
 
-/// Close user account, repaying init fee
-pub fn close_user_handler(ctx: Context<CloseUser>) -> Result<()> {
+/// Close user account, repaying init fee to the owner (or the sponsor if it paid it). The vault's delegates, merchant controls and guardians are closed with it, returning their rent to the owner. Token accounts held by the deposit address or vault are swept to the owner and closed, passed as remaining accounts in groups of [token_account, mint, token_program, owner_token_account]. The deposit address ATA of every registry market and USDC must be passed, even if it was never created, unless it was swept by a recent sweep_deposit_address.
+pub fn close_user_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CloseUser<'info>>,
+) -> Result<()> {
+    check!(
+        ctx.accounts.vault.drift_sub_account_count == 1,
+        PyraError::DriftSubAccountsStillOpen
+    );
+
+    let settle_and_close_order = match ctx.accounts.settle_and_close_order.as_ref() {
+        Some(settle_and_close_order) => {
+            check!(
+                settle_and_close_order.vault.eq(&ctx.accounts.vault.key()),
+                PyraError::InvalidSettleAndCloseOrder
+            );
+            validate_vault_time_lock(&ctx.accounts.vault, &settle_and_close_order.time_lock)?;
+            close_time_lock(
+                settle_and_close_order,
+                &ctx.accounts.owner.to_account_info(),
+            )?;
+            ctx.accounts
+                .vault
+                .remove_open_order(&settle_and_close_order.time_lock);
+            Some(settle_and_close_order.key())
+        }
+        None => None,
+    };
+
+    check!(
+        ctx.accounts.vault.open_order_count == 0,
+        PyraError::OpenOrdersRemaining
+    );
+
+    check!(
+        ctx.accounts.vault.open_spend_hold_count == 0,
+        PyraError::OpenSpendHoldsRemaining
+    );
+
+    check!(
+        !ctx.accounts.vault.may_have_uncounted_orders,
+        PyraError::UncountedOrdersNotAttested
+    );
+
+    check!(
+        !has_spot_borrows(&*ctx.accounts.drift_user.load()?),
+        PyraError::SpotBorrowsRemaining
+    );
+
     let vault_bump = ctx.accounts.vault.bump;
     let owner = ctx.accounts.owner.key();
-    let seeds_vault = &[b"vault", owner.as_ref(), &[vault_bump]];
+    let vault_id = ctx.accounts.vault.vault_id;
+    let seeds_vault = &[b"vault", vault_id.as_ref(), &[vault_bump]];
     let signer_seeds_vault = &[&seeds_vault[..]];
 
     let vault_lamports_before_cpi = ctx.accounts.vault.to_account_info().lamports();
@@ -90,6 +195,19 @@ pub fn close_user_handler(ctx: Context<CloseUser>) -> Result<()> {
         PyraError::IllegalVaultCPIModification
     );
 
+    // Drift returns the user's rent to the vault. Sub-account 0 was paid for by the init rent payer, which
+    // receives it when the vault closes, any other spend sub-account was paid for by the owner.
+    let drift_rent_reclaimed = if ctx.accounts.vault.spend_sub_account_id == 0 {
+        0
+    } else {
+        let lamports_reclaimed = vault_lamports_after_cpi.saturating_sub(vault_lamports_before_cpi);
+        if lamports_reclaimed > 0 {
+            ctx.accounts.vault.sub_lamports(lamports_reclaimed)?;
+            ctx.accounts.owner.add_lamports(lamports_reclaimed)?;
+        }
+        lamports_reclaimed
+    };
+
     // Close deposit address
     let deposit_address_bump = ctx.bumps.deposit_address;
     let vault = &ctx.accounts.vault.key();
@@ -100,6 +218,56 @@ pub fn close_user_handler(ctx: Context<CloseUser>) -> Result<()> {
     ];
     let signer_seeds_deposit_address = &[&seeds_deposit_address[..]];
 
+    let (token_accounts_swept, deposit_address_mints_swept) = sweep_token_accounts(
+        ctx.remaining_accounts,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.deposit_address.to_account_info(),
+        &ctx.accounts.vault.to_account_info(),
+        signer_seeds_deposit_address,
+        signer_seeds_vault,
+    )?;
+
+    // Deposit address ATAs of supported mints must be swept here or by a recent sweep_deposit_address, so none are
+    // left behind when the deposit address closes
+    let swept_deposit_mint_flags = ctx
+        .accounts
+        .vault
+        .get_swept_deposit_mint_flags(Clock::get()?.slot);
+    let is_fully_swept = ctx
+        .accounts
+        .drift_market_registry
+        .get_deposit_mints()
+        .iter()
+        .enumerate()
+        .all(|(position, mint)| {
+            swept_deposit_mint_flags & (1 << position) != 0
+                || deposit_address_mints_swept.contains(mint)
+        });
+    check!(is_fully_swept, PyraError::MissingSweepTokenAccount);
+
+    // Idle SOL goes to the owner, only the rent paid at init goes back to the init rent payer
+    let rent = Rent::get()?;
+    let deposit_address_rent = rent.minimum_balance(DEPOSIT_ADDRESS_SPACE);
+    let idle_lamports_swept = ctx
+        .accounts
+        .deposit_address
+        .lamports()
+        .saturating_sub(deposit_address_rent);
+
+    if idle_lamports_swept > 0 {
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.deposit_address.to_account_info(),
+                    to: ctx.accounts.owner.to_account_info(),
+                },
+                signer_seeds_deposit_address,
+            ),
+            idle_lamports_swept,
+        )?;
+    }
+
     system_program::transfer(
         CpiContext::new_with_signer(
             ctx.accounts.system_program.to_account_info(),
@@ -114,7 +282,34 @@ pub fn close_user_handler(ctx: Context<CloseUser>) -> Result<()> {
 
     validate_account_fresh(&ctx.accounts.deposit_address.to_account_info())?;
 
-    // Repay user the init rent fee
+    // Accounts seeded by the vault are closed with it, so a vault re-created at the same address doesn't inherit them
+    let mut vault_accounts_closed = Vec::new();
+    for vault_account in [
+        &ctx.accounts.vault_delegates,
+        &ctx.accounts.vault_merchant_controls,
+        &ctx.accounts.vault_guardians,
+    ] {
+        if vault_account.owner.eq(&crate::ID) {
+            close_program_account(
+                &vault_account.to_account_info(),
+                &ctx.accounts.owner.to_account_info(),
+            )?;
+            vault_accounts_closed.push(vault_account.key());
+        }
+    }
+
+    // Repay the init rent fee to whoever paid it
+    let init_rent_fee_recipient = if ctx.accounts.vault.sponsor.eq(&Pubkey::default()) {
+        ctx.accounts.owner.to_account_info()
+    } else {
+        match ctx.accounts.sponsor.as_ref() {
+            Some(sponsor) if sponsor.key().eq(&ctx.accounts.vault.sponsor) => {
+                sponsor.to_account_info()
+            }
+            _ => return Err(PyraError::InvalidSponsor.into()),
+        }
+    };
+
     let init_rent_payer_bump = ctx.bumps.init_rent_payer;
     let seeds_init_rent_payer = &[b"init_rent_payer".as_ref(), &[init_rent_payer_bump]];
     let signer_seeds_init_rent_payer = &[&seeds_init_rent_payer[..]];
@@ -122,16 +317,32 @@ pub fn close_user_handler(ctx: Context<CloseUser>) -> Result<()> {
     invoke_signed(
         &system_instruction::transfer(
             ctx.accounts.init_rent_payer.key,
-            ctx.accounts.owner.key,
+            init_rent_fee_recipient.key,
             INIT_ACCOUNT_RENT_FEE,
         ),
         &[
             ctx.accounts.init_rent_payer.to_account_info(),
-            ctx.accounts.owner.to_account_info(),
+            init_rent_fee_recipient.clone(),
             ctx.accounts.system_program.to_account_info(),
         ],
         signer_seeds_init_rent_payer,
     )?;
 
+    ctx.accounts
+        .rent_treasury
+        .release_obligations(RentPayer::Init, INIT_ACCOUNT_RENT_FEE);
+
+    emit_cpi!(UserClosed {
+        owner,
+        vault: ctx.accounts.vault.key(),
+        init_rent_fee_refunded: INIT_ACCOUNT_RENT_FEE,
+        init_rent_fee_recipient: init_rent_fee_recipient.key(),
+        drift_rent_reclaimed,
+        idle_lamports_swept,
+        token_accounts_swept,
+        settle_and_close_order,
+        vault_accounts_closed,
+    });
+
     Ok(())
 }
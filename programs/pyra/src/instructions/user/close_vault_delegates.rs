@@ -0,0 +1,39 @@
+use crate::{
+    config::{PyraError, VAULT_VERSION},
+    events::VaultDelegatesClosed,
+    state::{Vault, VaultDelegates},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseVaultDelegates<'info> {
+    #[account(
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_delegates".as_ref(), vault.key().as_ref()],
+        bump = vault_delegates.bump,
+        close = owner
+    )]
+    pub vault_delegates: Box<Account<'info, VaultDelegates>>,
+}
+
+/// Revokes every delegate on the vault at once, returning the registry's rent to the owner
+pub fn close_vault_delegates_handler(ctx: Context<CloseVaultDelegates>) -> Result<()> {
+    emit_cpi!(VaultDelegatesClosed {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+    });
+
+    Ok(())
+}
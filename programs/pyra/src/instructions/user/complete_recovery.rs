@@ -0,0 +1,84 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::VaultRecovered,
+    state::{RecoveryOrder, Vault, VaultGuardians},
+    utils::{close_time_lock, validate_vault_time_lock},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CompleteRecovery<'info> {
+    #[account(mut)]
+    pub recovery_order: Box<Account<'info, RecoveryOrder>>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub new_owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Any account, once it has a vault (order checked in handler)
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_guardians".as_ref(), vault.key().as_ref()],
+        bump = vault_guardians.bump
+    )]
+    pub vault_guardians: Box<Account<'info, VaultGuardians>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Completes a guardian recovery once the time lock has expired, making the new owner sign so the key is known to be usable. The guardian set carries over to the new owner. Never paused, so an incident can't keep a vault with a lost key locked.
+pub fn complete_recovery_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CompleteRecovery<'info>>,
+) -> Result<()> {
+    validate_vault_time_lock(&ctx.accounts.vault, &ctx.accounts.recovery_order.time_lock)?;
+
+    check!(
+        ctx.accounts
+            .recovery_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidRecoveryOrder
+    );
+    check!(
+        ctx.accounts
+            .recovery_order
+            .new_owner
+            .eq(&ctx.accounts.new_owner.key()),
+        PyraError::InvalidRecoveryOrder
+    );
+
+    close_time_lock(
+        &ctx.accounts.recovery_order,
+        &ctx.accounts.time_lock_rent_payer.to_account_info(),
+    )?;
+
+    let previous_owner = ctx.accounts.vault.owner;
+    ctx.accounts
+        .vault
+        .change_owner(ctx.accounts.new_owner.key())?;
+    ctx.accounts.vault_guardians.owner = ctx.accounts.new_owner.key();
+
+    emit_cpi!(VaultRecovered {
+        previous_owner,
+        new_owner: ctx.accounts.new_owner.key(),
+        vault: ctx.accounts.vault.key(),
+        recovery_order: ctx.accounts.recovery_order.key(),
+    });
+
+    Ok(())
+}
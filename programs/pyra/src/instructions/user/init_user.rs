@@ -1,7 +1,15 @@
 use crate::{
     check,
-    config::{PyraError, ANCHOR_DISCRIMINATOR, DEPOSIT_ADDRESS_SPACE, INIT_ACCOUNT_RENT_FEE},
-    state::Vault,
+    config::{
+        PyraError, ANCHOR_DISCRIMINATOR, DEPOSIT_ADDRESS_SPACE, INIT_ACCOUNT_RENT_FEE,
+        MAX_SPEND_LIMIT_WINDOWS, VAULT_RESERVED_SPACE, VAULT_VERSION,
+    },
+    events::UserInitialized,
+    state::{
+        CalendarPeriod, GlobalConfig, ProtocolFeature, RecentSpends, ReferrerRewards, RentPayer,
+        RentTreasury, SettlementProviderRegistry, SpendLimitBoost, SpendLimitMode,
+        SpendLimitWindow, SpendVelocityLimits, Vault,
+    },
     utils::validate_account_fresh,
 };
 use anchor_lang::{
@@ -21,12 +29,14 @@ use drift::{
 use solana_program::program::invoke;
 use solana_program::system_instruction;
 
+#[event_cpi]
 #[derive(Accounts)]
+#[instruction(vault_id: Pubkey)]
 pub struct InitUser<'info> {
     /// CHECK: Safe once address is correct
     #[account(
         mut,
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
+        seeds = [b"vault".as_ref(), vault_id.as_ref()],
         bump,
     )]
     pub vault: UncheckedAccount<'info>,
@@ -67,19 +77,57 @@ pub struct InitUser<'info> {
         bump
     )]
     pub deposit_address: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"rent_treasury"],
+        bump = rent_treasury.bump
+    )]
+    pub rent_treasury: Box<Account<'info, RentTreasury>>,
+
+    #[account(
+        seeds = [b"settlement_provider_registry"],
+        bump = settlement_provider_registry.bump
+    )]
+    pub settlement_provider_registry: Box<Account<'info, SettlementProviderRegistry>>,
+
+    // Pays the init fee instead of the owner, and is refunded it when the vault is closed
+    #[account(mut)]
+    pub sponsor: Option<Signer<'info>>,
+
+    #[account(
+        seeds = [b"referrer_rewards".as_ref(), referrer_rewards.referrer.as_ref()],
+        bump = referrer_rewards.bump
+    )]
+    pub referrer_rewards: Option<Box<Account<'info, ReferrerRewards>>>,
 }
 
-/// Create a user account. Initializes vault, deposit address, and Drift accounts. User only pays 0.035 SOL, the rest is covered by the init_rent_payer.
+/// Create a user account bound to a settlement provider, at the vault id chosen by the owner. Vault ids aren't tied to the owner, so owners can hold several vaults and keep them through owner transfers. Initializes vault, deposit address, and Drift accounts. User only pays 0.035 SOL (or an optional sponsor pays it), the rest is covered by the init_rent_payer. Optionally records the referrer of a registered ReferrerRewards.
 pub fn init_user_handler(
     ctx: Context<InitUser>,
+    vault_id: Pubkey,
     spend_limit_per_transaction: u64,
     spend_limit_per_timeframe: u64,
     timeframe_in_seconds: u64,
     next_timeframe_reset_timestamp: u64,
+    settlement_provider_id: u16,
 ) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::UserInit),
+        PyraError::ProtocolPaused
+    );
+
     let vault_bump = ctx.bumps.vault;
     let owner = ctx.accounts.owner.key();
-    let seeds_vault = &[b"vault", owner.as_ref(), &[vault_bump]];
+    let seeds_vault = &[b"vault", vault_id.as_ref(), &[vault_bump]];
 
     let init_rent_payer_bump = ctx.bumps.init_rent_payer;
     let init_rent_payer_seeds = &[b"init_rent_payer".as_ref(), &[init_rent_payer_bump]];
@@ -89,41 +137,82 @@ pub fn init_user_handler(
     validate_account_fresh(&ctx.accounts.vault.to_account_info())?;
     validate_account_fresh(&ctx.accounts.deposit_address.to_account_info())?;
 
+    ctx.accounts
+        .settlement_provider_registry
+        .get_enabled_provider(settlement_provider_id)?;
+
+    if let Some(referrer_rewards) = ctx.accounts.referrer_rewards.as_ref() {
+        check!(
+            !referrer_rewards.referrer.eq(&owner),
+            PyraError::InvalidReferrer
+        );
+    }
+
     // Pay init_rent_payer the init fee
+    let fee_payer = match ctx.accounts.sponsor.as_ref() {
+        Some(sponsor) => sponsor.to_account_info(),
+        None => ctx.accounts.owner.to_account_info(),
+    };
     invoke(
         &system_instruction::transfer(
-            ctx.accounts.owner.key,
+            fee_payer.key,
             ctx.accounts.init_rent_payer.key,
             INIT_ACCOUNT_RENT_FEE,
         ),
         &[
-            ctx.accounts.owner.to_account_info(),
+            fee_payer,
             ctx.accounts.init_rent_payer.to_account_info(),
             ctx.accounts.system_program.to_account_info(),
         ],
     )?;
 
+    ctx.accounts
+        .rent_treasury
+        .add_obligations(RentPayer::Init, INIT_ACCOUNT_RENT_FEE)?;
+
     init_vault(
         &ctx,
         signer_seeds,
+        vault_id,
         spend_limit_per_transaction,
         spend_limit_per_timeframe,
         timeframe_in_seconds,
         next_timeframe_reset_timestamp,
+        settlement_provider_id,
     )?;
 
     init_drift_accounts(&ctx, signer_seeds)?;
 
+    emit_cpi!(UserInitialized {
+        owner,
+        vault: ctx.accounts.vault.key(),
+        deposit_address: ctx.accounts.deposit_address.key(),
+        spend_limit_per_transaction,
+        spend_limit_per_timeframe,
+        timeframe_in_seconds,
+        next_timeframe_reset_timestamp,
+        settlement_provider_id,
+        sponsor: ctx.accounts.sponsor.as_ref().map(|sponsor| sponsor.key()),
+        referrer: ctx
+            .accounts
+            .referrer_rewards
+            .as_ref()
+            .map(|referrer_rewards| referrer_rewards.referrer),
+    });
+
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn init_vault(
     ctx: &Context<InitUser>,
     signer_seeds: &[&[&[u8]]],
+    vault_id: Pubkey,
     spend_limit_per_transaction: u64,
     spend_limit_per_timeframe: u64,
     timeframe_in_seconds: u64,
     next_timeframe_reset_timestamp: u64,
+    settlement_provider_id: u16,
 ) -> Result<()> {
     // Init vault space
     let rent = Rent::get()?;
@@ -152,6 +241,40 @@ fn init_vault(
         remaining_spend_limit_per_timeframe: spend_limit_per_timeframe,
         next_timeframe_reset_timestamp,
         timeframe_in_seconds,
+        version: VAULT_VERSION,
+        vault_id,
+        spend_sub_account_id: 0,
+        drift_sub_account_count: 1,
+        is_card_frozen: false,
+        open_order_count: 0,
+        sponsor: ctx
+            .accounts
+            .sponsor
+            .as_ref()
+            .map_or(Pubkey::default(), |sponsor| sponsor.key()),
+        referrer: ctx
+            .accounts
+            .referrer_rewards
+            .as_ref()
+            .map_or(Pubkey::default(), |referrer_rewards| {
+                referrer_rewards.referrer
+            }),
+        settlement_provider_id,
+        spend_limit_windows: [SpendLimitWindow::default(); MAX_SPEND_LIMIT_WINDOWS],
+        spend_limit_mode: SpendLimitMode::Fixed,
+        recent_spends: RecentSpends::default(),
+        utc_offset_seconds: 0,
+        spend_velocity_limits: SpendVelocityLimits::default(),
+        spend_count_per_timeframe: 0,
+        spend_limit_boost: SpendLimitBoost::default(),
+        calendar_period: CalendarPeriod::Day,
+        spend_limit_window_calendar_periods: [CalendarPeriod::Day; MAX_SPEND_LIMIT_WINDOWS],
+        owner_epoch: 0,
+        may_have_uncounted_orders: false,
+        swept_deposit_mint_flags: 0,
+        deposit_sweep_slot: 0,
+        open_spend_hold_count: 0,
+        reserved: [0; VAULT_RESERVED_SPACE],
     };
     let vault_data_vec = vault_data.try_to_vec()?;
 
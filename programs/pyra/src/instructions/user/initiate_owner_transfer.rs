@@ -0,0 +1,107 @@
+use crate::{
+    check,
+    config::{PyraError, ANCHOR_DISCRIMINATOR, VAULT_VERSION},
+    events::OwnerTransferInitiated,
+    state::{GlobalConfig, OwnerTransferOrder, TimeLock, Vault},
+    utils::{allocate_time_lock_owner_payer, allocate_time_lock_program_payer},
+};
+use anchor_lang::{prelude::*, Discriminator};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitiateOwnerTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub owner_transfer_order: Signer<'info>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Can be any account, must sign to accept the transfer
+    pub new_owner: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Creates a time locked order to transfer the vault to a new owner, which the new owner can accept once the time lock has expired. The vault address, deposit address, and Drift accounts are unchanged by the transfer.
+pub fn initiate_owner_transfer_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitiateOwnerTransfer<'info>>,
+) -> Result<()> {
+    check!(
+        !ctx.accounts.new_owner.key().eq(&ctx.accounts.owner.key()),
+        PyraError::InvalidNewOwner
+    );
+
+    let is_owner_payer = ctx
+        .accounts
+        .time_lock_rent_payer
+        .key()
+        .eq(&ctx.accounts.owner.key());
+
+    if is_owner_payer {
+        allocate_time_lock_owner_payer(
+            &ctx.accounts.owner,
+            &ctx.accounts.owner_transfer_order,
+            &ctx.accounts.system_program,
+            OwnerTransferOrder::INIT_SPACE,
+        )?;
+    } else {
+        allocate_time_lock_program_payer(
+            &ctx.accounts.time_lock_rent_payer.to_account_info(),
+            &ctx.accounts.owner_transfer_order,
+            &ctx.accounts.system_program,
+            OwnerTransferOrder::INIT_SPACE,
+        )?;
+    }
+
+    let current_slot = Clock::get()?.slot;
+    let release_slot = current_slot
+        .checked_add(ctx.accounts.global_config.time_lock_duration_slots)
+        .ok_or(PyraError::MathOverflow)?;
+
+    let owner_transfer_order_data = OwnerTransferOrder {
+        time_lock: TimeLock {
+            owner: ctx.accounts.owner.key(),
+            owner_epoch: ctx.accounts.vault.owner_epoch,
+            is_owner_payer,
+            release_slot,
+        },
+        vault: ctx.accounts.vault.key(),
+        new_owner: ctx.accounts.new_owner.key(),
+    };
+    let owner_transfer_order_data_vec = owner_transfer_order_data.try_to_vec()?;
+
+    let mut data = ctx.accounts.owner_transfer_order.try_borrow_mut_data()?;
+    data[..ANCHOR_DISCRIMINATOR].copy_from_slice(&OwnerTransferOrder::DISCRIMINATOR);
+    data[ANCHOR_DISCRIMINATOR..].copy_from_slice(&owner_transfer_order_data_vec[..]);
+    drop(data);
+
+    ctx.accounts.vault.add_open_order()?;
+
+    emit_cpi!(OwnerTransferInitiated {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        owner_transfer_order: ctx.accounts.owner_transfer_order.key(),
+        new_owner: ctx.accounts.new_owner.key(),
+        release_slot,
+    });
+
+    Ok(())
+}
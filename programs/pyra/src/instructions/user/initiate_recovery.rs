@@ -0,0 +1,112 @@
+use crate::{
+    check,
+    config::{PyraError, ANCHOR_DISCRIMINATOR, VAULT_VERSION},
+    events::RecoveryInitiated,
+    state::{RecoveryOrder, TimeLock, Vault, VaultGuardians},
+    utils::allocate_pda_time_lock_program_payer,
+};
+use anchor_lang::{prelude::*, Discriminator};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitiateRecovery<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Checked by the vault's has_one constraint, doesn't sign as the key may be lost
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"vault_guardians".as_ref(), vault.key().as_ref()],
+        bump = vault_guardians.bump
+    )]
+    pub vault_guardians: Box<Account<'info, VaultGuardians>>,
+
+    /// CHECK: Seeds allow one recovery order per vault, allocated in the handler
+    #[account(
+        mut,
+        seeds = [b"recovery_order".as_ref(), vault.key().as_ref()],
+        bump
+    )]
+    pub recovery_order: UncheckedAccount<'info>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Can be any account, must sign to complete the recovery
+    pub new_owner: UncheckedAccount<'info>,
+}
+
+/// Creates a time locked order to recover the vault to a new owner, at most one per vault, signed by at least the threshold of guardians (passed as remaining accounts). The current owner can veto it until the time lock expires.
+pub fn initiate_recovery_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitiateRecovery<'info>>,
+) -> Result<()> {
+    check!(
+        !ctx.accounts.new_owner.key().eq(&ctx.accounts.owner.key()),
+        PyraError::InvalidNewOwner
+    );
+
+    let approving_guardians = ctx
+        .accounts
+        .vault_guardians
+        .validate_guardian_signers(&ctx.accounts.owner.key(), ctx.remaining_accounts)?;
+
+    // Guardians don't receive the rent back when the owner vetoes, so the program pays, for at most one order per vault
+    let vault_key = ctx.accounts.vault.key();
+    let recovery_order_seeds: &[&[u8]] = &[
+        b"recovery_order",
+        vault_key.as_ref(),
+        &[ctx.bumps.recovery_order],
+    ];
+    allocate_pda_time_lock_program_payer(
+        &ctx.accounts.time_lock_rent_payer.to_account_info(),
+        &ctx.accounts.recovery_order.to_account_info(),
+        recovery_order_seeds,
+        &ctx.accounts.system_program,
+        RecoveryOrder::INIT_SPACE,
+    )?;
+
+    let current_slot = Clock::get()?.slot;
+    let release_slot = current_slot
+        .checked_add(ctx.accounts.vault_guardians.recovery_delay_slots)
+        .ok_or(PyraError::MathOverflow)?;
+
+    let recovery_order_data = RecoveryOrder {
+        time_lock: TimeLock {
+            owner: ctx.accounts.owner.key(),
+            owner_epoch: ctx.accounts.vault.owner_epoch,
+            is_owner_payer: false,
+            release_slot,
+        },
+        vault: ctx.accounts.vault.key(),
+        new_owner: ctx.accounts.new_owner.key(),
+    };
+    let recovery_order_data_vec = recovery_order_data.try_to_vec()?;
+
+    let mut data = ctx.accounts.recovery_order.try_borrow_mut_data()?;
+    data[..ANCHOR_DISCRIMINATOR].copy_from_slice(&RecoveryOrder::DISCRIMINATOR);
+    data[ANCHOR_DISCRIMINATOR..].copy_from_slice(&recovery_order_data_vec[..]);
+    drop(data);
+
+    ctx.accounts.vault.add_open_order()?;
+
+    emit_cpi!(RecoveryInitiated {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        recovery_order: ctx.accounts.recovery_order.key(),
+        new_owner: ctx.accounts.new_owner.key(),
+        approving_guardians,
+        release_slot,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use crate::{
+    config::{PyraError, ANCHOR_DISCRIMINATOR, VAULT_VERSION},
+    events::SettleAndCloseInitiated,
+    state::{GlobalConfig, SettleAndCloseOrder, TimeLock, Vault},
+    utils::allocate_time_lock_owner_payer,
+};
+use anchor_lang::{prelude::*, Discriminator};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitiateSettleAndClose<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub settle_and_close_order: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Creates a time locked order to settle and close the vault, freezing the card. While it's time locked, the vault's loans can be repaid with settle repays (collateral repays passed the order). Once released, the owner withdraws the remaining collateral with settle_withdraw and passes the order to close_user in one bundle, both of which check no loans remain.
+pub fn initiate_settle_and_close_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitiateSettleAndClose<'info>>,
+) -> Result<()> {
+    // The order is consumed by close_user, where the owner receives the rent back along with the vault's
+    allocate_time_lock_owner_payer(
+        &ctx.accounts.owner,
+        &ctx.accounts.settle_and_close_order,
+        &ctx.accounts.system_program,
+        SettleAndCloseOrder::INIT_SPACE,
+    )?;
+
+    let current_slot = Clock::get()?.slot;
+    let release_slot = current_slot
+        .checked_add(ctx.accounts.global_config.time_lock_duration_slots)
+        .ok_or(PyraError::MathOverflow)?;
+
+    let settle_and_close_order_data = SettleAndCloseOrder {
+        time_lock: TimeLock {
+            owner: ctx.accounts.owner.key(),
+            owner_epoch: ctx.accounts.vault.owner_epoch,
+            is_owner_payer: true,
+            release_slot,
+        },
+        vault: ctx.accounts.vault.key(),
+    };
+    let settle_and_close_order_data_vec = settle_and_close_order_data.try_to_vec()?;
+
+    let mut data = ctx.accounts.settle_and_close_order.try_borrow_mut_data()?;
+    data[..ANCHOR_DISCRIMINATOR].copy_from_slice(&SettleAndCloseOrder::DISCRIMINATOR);
+    data[ANCHOR_DISCRIMINATOR..].copy_from_slice(&settle_and_close_order_data_vec[..]);
+    drop(data);
+
+    // No new spends once the owner has asked to close, as the collateral will be withdrawn
+    ctx.accounts.vault.is_card_frozen = true;
+    ctx.accounts.vault.add_open_order()?;
+
+    emit_cpi!(SettleAndCloseInitiated {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        settle_and_close_order: ctx.accounts.settle_and_close_order.key(),
+        release_slot,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use crate::{
+    config::{PyraError, VAULT_VERSION},
+    events::VaultGuardiansRemoved,
+    state::{Vault, VaultGuardians},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RemoveVaultGuardians<'info> {
+    #[account(
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_guardians".as_ref(), vault.key().as_ref()],
+        bump = vault_guardians.bump,
+        close = owner
+    )]
+    pub vault_guardians: Box<Account<'info, VaultGuardians>>,
+}
+
+/// Removes the vault's guardian set, disabling social recovery and returning the rent to the owner
+pub fn remove_vault_guardians_handler(ctx: Context<RemoveVaultGuardians>) -> Result<()> {
+    emit_cpi!(VaultGuardiansRemoved {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use crate::{
+    config::{PyraError, VAULT_VERSION},
+    events::VaultDelegateRevoked,
+    state::{Vault, VaultDelegates},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevokeVaultDelegate<'info> {
+    #[account(
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_delegates".as_ref(), vault.key().as_ref()],
+        bump = vault_delegates.bump
+    )]
+    pub vault_delegates: Box<Account<'info, VaultDelegates>>,
+}
+
+/// Instantly removes all of a delegate's permissions on the vault
+pub fn revoke_vault_delegate_handler(
+    ctx: Context<RevokeVaultDelegate>,
+    delegate: Pubkey,
+) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+    ctx.accounts
+        .vault_delegates
+        .revoke_delegate(&owner, &delegate)?;
+
+    emit_cpi!(VaultDelegateRevoked {
+        owner,
+        vault: ctx.accounts.vault.key(),
+        delegate,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::VaultDelegateSet,
+    state::{DelegatePermission, Vault, VaultDelegate, VaultDelegates},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetVaultDelegate<'info> {
+    #[account(
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"vault_delegates".as_ref(), vault.key().as_ref()],
+        bump,
+        payer = owner,
+        space = VaultDelegates::INIT_SPACE
+    )]
+    pub vault_delegates: Box<Account<'info, VaultDelegates>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grants a delegate scoped permissions on the vault, replacing any permissions it already has
+pub fn set_vault_delegate_handler(
+    ctx: Context<SetVaultDelegate>,
+    delegate: Pubkey,
+    permissions: Vec<DelegatePermission>,
+    max_spend_limit_per_transaction: u64,
+    max_spend_limit_per_timeframe: u64,
+) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+    check!(!delegate.eq(&owner), PyraError::InvalidDelegate);
+
+    let permission_flags = DelegatePermission::to_flags(&permissions);
+
+    let vault_delegates = &mut ctx.accounts.vault_delegates;
+    vault_delegates.vault = ctx.accounts.vault.key();
+    vault_delegates.bump = ctx.bumps.vault_delegates;
+    vault_delegates.set_delegate(
+        owner,
+        VaultDelegate {
+            delegate,
+            permissions: permission_flags,
+            max_spend_limit_per_transaction,
+            max_spend_limit_per_timeframe,
+        },
+    )?;
+
+    emit_cpi!(VaultDelegateSet {
+        owner,
+        vault: ctx.accounts.vault.key(),
+        delegate,
+        permissions: permission_flags,
+        max_spend_limit_per_transaction,
+        max_spend_limit_per_timeframe,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use crate::{
+    config::{PyraError, VAULT_VERSION},
+    events::VaultGuardiansSet,
+    state::{Vault, VaultGuardians},
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetVaultGuardians<'info> {
+    #[account(
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"vault_guardians".as_ref(), vault.key().as_ref()],
+        bump,
+        payer = owner,
+        space = VaultGuardians::INIT_SPACE
+    )]
+    pub vault_guardians: Box<Account<'info, VaultGuardians>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Sets the guardians that can recover the vault to a new owner, replacing any existing guardian set
+pub fn set_vault_guardians_handler(
+    ctx: Context<SetVaultGuardians>,
+    guardians: Vec<Pubkey>,
+    threshold: u8,
+    recovery_delay_slots: u64,
+) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+
+    let vault_guardians = &mut ctx.accounts.vault_guardians;
+    vault_guardians.vault = ctx.accounts.vault.key();
+    vault_guardians.bump = ctx.bumps.vault_guardians;
+    vault_guardians.set_guardians(owner, guardians.clone(), threshold, recovery_delay_slots)?;
+
+    emit_cpi!(VaultGuardiansSet {
+        owner,
+        vault: ctx.accounts.vault.key(),
+        guardians,
+        threshold,
+        recovery_delay_slots,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,207 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION, WSOL_MINT},
+    events::SettleWithdrawn,
+    state::{DriftMarketRegistry, GlobalConfig, ProtocolFeature, SettleAndCloseOrder, Vault},
+    utils::{get_drift_market, has_spot_borrows, validate_vault_time_lock},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+    TransferChecked,
+};
+use drift::{
+    cpi::accounts::Withdraw as DriftWithdraw, cpi::withdraw as drift_withdraw, program::Drift,
+    state::user::User as DriftUser,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleWithdraw<'info> {
+    pub settle_and_close_order: Box<Account<'info, SettleAndCloseOrder>>,
+
+    #[account(
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"withdraw_mule".as_ref(), vault.key().as_ref(), mint.key().as_ref()],
+        bump,
+        payer = owner,
+        token::mint = mint,
+        token::authority = vault
+    )]
+    pub mule: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // Seeds ensure this is the spend sub-account (the last one open at close), the Drift CPI performs the remaining checks
+    #[account(
+        mut,
+        seeds = [b"user".as_ref(), vault.key().as_ref(), vault.spend_sub_account_id.to_le_bytes().as_ref()],
+        seeds::program = drift_program.key(),
+        bump
+    )]
+    pub drift_user: AccountLoader<'info, DriftUser>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    #[account(mut)]
+    pub drift_user_stats: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    #[account(mut)]
+    pub drift_state: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    #[account(mut)]
+    pub spot_market_vault: UncheckedAccount<'info>,
+
+    /// CHECK: This account is passed through to the Drift CPI, which performs the security checks
+    pub drift_signer: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub drift_program: Program<'info, Drift>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    pub owner_spl: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        seeds = [b"drift_market_registry"],
+        bump = drift_market_registry.bump
+    )]
+    pub drift_market_registry: Box<Account<'info, DriftMarketRegistry>>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+/// Withdraws the vault's full deposit in a Drift market to the owner, once its settle and close order has been released. Every loan must have been repaid with settle repays first.
+pub fn settle_withdraw_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleWithdraw<'info>>,
+    drift_market_index: u16,
+) -> Result<()> {
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::Withdrawals),
+        PyraError::ProtocolPaused
+    );
+
+    check!(
+        ctx.accounts
+            .settle_and_close_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidSettleAndCloseOrder
+    );
+
+    // The order stays open until close_user, so it can be used for every market
+    validate_vault_time_lock(
+        &ctx.accounts.vault,
+        &ctx.accounts.settle_and_close_order.time_lock,
+    )?;
+
+    // Other sub-accounts can't be closed with loans, so only the spend sub-account can still have any
+    check!(
+        !has_spot_borrows(&*ctx.accounts.drift_user.load()?),
+        PyraError::SpotBorrowsRemaining
+    );
+
+    let drift_market = get_drift_market(&ctx.accounts.drift_market_registry, drift_market_index)?;
+    check!(
+        &ctx.accounts.mint.key().eq(&drift_market.mint),
+        PyraError::InvalidMint
+    );
+    let is_sol = ctx.accounts.mint.key().eq(&WSOL_MINT);
+
+    let vault_bump = ctx.accounts.vault.bump;
+    let vault_id = ctx.accounts.vault.vault_id;
+    let seeds_vault = &[b"vault", vault_id.as_ref(), &[vault_bump]];
+    let vault_signer = &[&seeds_vault[..]];
+
+    let mut cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.drift_program.to_account_info(),
+        DriftWithdraw {
+            state: ctx.accounts.drift_state.to_account_info(),
+            user: ctx.accounts.drift_user.to_account_info(),
+            user_stats: ctx.accounts.drift_user_stats.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+            spot_market_vault: ctx.accounts.spot_market_vault.to_account_info(),
+            drift_signer: ctx.accounts.drift_signer.to_account_info(),
+            user_token_account: ctx.accounts.mule.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+        vault_signer,
+    );
+
+    cpi_ctx.remaining_accounts = ctx.remaining_accounts.to_vec();
+
+    // Drift caps reduce only withdraws at the deposit balance, so this takes the full deposit without borrowing
+    drift_withdraw(cpi_ctx, drift_market_index, u64::MAX, true)?;
+
+    ctx.accounts.mule.reload()?;
+    let amount_base_units = ctx.accounts.mule.amount;
+
+    // wSOL is unwrapped by closing the mule to the owner, other tokens are sent to the owner's ATA first
+    if !is_sol {
+        let owner_spl = match ctx.accounts.owner_spl.as_ref() {
+            Some(owner_spl) => owner_spl,
+            None => return Err(PyraError::MissingDestinationSpl.into()),
+        };
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.mule.to_account_info(),
+                    to: owner_spl.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                },
+                vault_signer,
+            ),
+            amount_base_units,
+            ctx.accounts.mint.decimals,
+        )?;
+    }
+
+    close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.mule.to_account_info(),
+            destination: ctx.accounts.owner.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        },
+        vault_signer,
+    ))?;
+
+    emit_cpi!(SettleWithdrawn {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        settle_and_close_order: ctx.accounts.settle_and_close_order.key(),
+        drift_market_index,
+        mint: ctx.accounts.mint.key(),
+        amount_base_units,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use crate::{
+    config::{PyraError, VAULT_VERSION},
+    events::DepositAddressSwept,
+    state::{DriftMarketRegistry, Vault},
+    utils::sweep_token_accounts,
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SweepDepositAddress<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ PyraError::InvalidVaultOwner,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Safe once seeds are correct, deposit address is the pubkey anyone can send tokens to for deposits
+    #[account(
+        seeds = [b"deposit_address".as_ref(), vault.key().as_ref()],
+        bump
+    )]
+    pub deposit_address: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"drift_market_registry"],
+        bump = drift_market_registry.bump
+    )]
+    pub drift_market_registry: Box<Account<'info, DriftMarketRegistry>>,
+}
+
+/// Sweeps token accounts held by the deposit address or vault to the owner in batches before close_user, as the deposit address ATAs of every supported mint don't fit in one transaction. Accounts are passed as remaining accounts in the same groups as close_user. Also rescues mules left at the owner-seeded addresses used before mules were seeded by the vault, as the vault still holds them.
+pub fn sweep_deposit_address_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SweepDepositAddress<'info>>,
+) -> Result<()> {
+    let vault_bump = ctx.accounts.vault.bump;
+    let vault_id = ctx.accounts.vault.vault_id;
+    let seeds_vault = &[b"vault", vault_id.as_ref(), &[vault_bump]];
+    let signer_seeds_vault = &[&seeds_vault[..]];
+
+    let deposit_address_bump = ctx.bumps.deposit_address;
+    let vault = &ctx.accounts.vault.key();
+    let seeds_deposit_address = &[
+        b"deposit_address".as_ref(),
+        vault.as_ref(),
+        &[deposit_address_bump],
+    ];
+    let signer_seeds_deposit_address = &[&seeds_deposit_address[..]];
+
+    let (token_accounts_swept, deposit_address_mints_swept) = sweep_token_accounts(
+        ctx.remaining_accounts,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.deposit_address.to_account_info(),
+        &ctx.accounts.vault.to_account_info(),
+        signer_seeds_deposit_address,
+        signer_seeds_vault,
+    )?;
+
+    let swept_deposit_mint_flags = ctx
+        .accounts
+        .drift_market_registry
+        .get_deposit_mints()
+        .iter()
+        .enumerate()
+        .filter(|(_, mint)| deposit_address_mints_swept.contains(mint))
+        .fold(0, |flags, (position, _)| flags | (1 << position));
+
+    let current_slot = Clock::get()?.slot;
+    ctx.accounts
+        .vault
+        .record_deposit_sweep(swept_deposit_mint_flags, current_slot);
+
+    emit_cpi!(DepositAddressSwept {
+        owner: ctx.accounts.owner.key(),
+        vault: ctx.accounts.vault.key(),
+        token_accounts_swept,
+        swept_deposit_mint_flags: ctx.accounts.vault.swept_deposit_mint_flags,
+        deposit_sweep_slot: ctx.accounts.vault.deposit_sweep_slot,
+    });
+
+    Ok(())
+}
@@ -1,16 +1,19 @@
 use crate::check;
-use crate::config::{PyraError, ANCHOR_DISCRIMINATOR, PUBKEY_SIZE};
-use crate::state::Vault;
+use crate::config::{PyraError, ANCHOR_DISCRIMINATOR, VAULT_VERSION};
+use crate::events::VaultUpgraded;
+use crate::state::{
+    get_vault_version, migrate_vault, GlobalConfig, ProtocolFeature, Vault, VaultMigrationParams,
+};
 use anchor_lang::{prelude::*, Discriminator};
 use solana_program::{program::invoke_signed, system_instruction};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpgradeVault<'info> {
-    /// CHECK: Safe once address is correct
+    /// CHECK: Address and layout are checked in handler, as the seeds depend on the vault version
     #[account(
         mut,
-        seeds = [b"vault".as_ref(), owner.key().as_ref()],
-        bump
+        owner = crate::ID @ PyraError::InvalidVaultAccount
     )]
     pub vault: UncheckedAccount<'info>,
 
@@ -26,9 +29,15 @@ pub struct UpgradeVault<'info> {
     pub init_rent_payer: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
 }
 
-/// Upgrade vault to new version (adding spend limit data)
+/// Migrate the vault from an unversioned layout to the current layout. Spend limits are only used when migrating from v1, which predates spend limits.
 pub fn upgrade_vault_handler(
     ctx: Context<UpgradeVault>,
     spend_limit_per_transaction: u64,
@@ -36,50 +45,54 @@ pub fn upgrade_vault_handler(
     timeframe_in_seconds: u64,
     next_timeframe_reset_timestamp: u64,
 ) -> Result<()> {
-    // Get current Vault data
-    let existing_vault = &ctx.accounts.vault;
-    let (vault_owner, vault_bump) = {
-        let data = existing_vault.data.borrow();
-        let discriminator = &data[..ANCHOR_DISCRIMINATOR];
-        check!(
-            discriminator.eq(&Vault::DISCRIMINATOR),
-            PyraError::InvalidVaultAccount
-        );
-
-        let bump_start_bytes = ANCHOR_DISCRIMINATOR + PUBKEY_SIZE;
-        let owner_bytes = &data[ANCHOR_DISCRIMINATOR..bump_start_bytes];
-        let owner = Pubkey::new_from_array(
-            owner_bytes
-                .try_into()
-                .map_err(|_| PyraError::FailedToDeserializeVaultBytes)?,
-        );
-
-        (owner, data[bump_start_bytes])
-    };
+    check!(
+        !ctx.accounts
+            .global_config
+            .is_paused(ProtocolFeature::UserInit),
+        PyraError::ProtocolPaused
+    );
 
-    // Validate accounts
+    let existing_vault = &ctx.accounts.vault;
+    let from_version = get_vault_version(&existing_vault.data.borrow())?;
     check!(
-        vault_owner.eq(&ctx.accounts.owner.key()),
-        PyraError::InvalidVaultOwner
+        from_version < VAULT_VERSION,
+        PyraError::VaultAlreadyUpToDate
     );
 
-    // Get new vault data and required size
-    let new_vault = Vault {
-        owner: ctx.accounts.owner.key(),
-        bump: vault_bump,
+    let params = VaultMigrationParams {
         spend_limit_per_transaction,
         spend_limit_per_timeframe,
-        remaining_spend_limit_per_timeframe: spend_limit_per_timeframe,
-        next_timeframe_reset_timestamp,
         timeframe_in_seconds,
+        next_timeframe_reset_timestamp,
     };
+    let new_vault = migrate_vault(&existing_vault.data.borrow(), &params)?;
+
+    // Migrations preserve the owner, vault_id and bump, so they can be checked on the migrated vault
+    let expected_vault = Pubkey::create_program_address(
+        &[
+            b"vault".as_ref(),
+            new_vault.vault_id.as_ref(),
+            &[new_vault.bump],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| PyraError::InvalidVaultAccount)?;
+    check!(
+        existing_vault.key().eq(&expected_vault),
+        PyraError::InvalidVaultAccount
+    );
+    check!(
+        new_vault.owner.eq(&ctx.accounts.owner.key()),
+        PyraError::InvalidVaultOwner
+    );
+
     let new_vault_vec = new_vault.try_to_vec()?;
 
+    // Top up rent for the new size
     let rent = Rent::get()?;
     let new_minimum_balance = rent.minimum_balance(Vault::INIT_SPACE);
     let lamports_diff = new_minimum_balance.saturating_sub(existing_vault.lamports());
 
-    // Extend the vault size
     if lamports_diff > 0 {
         let init_rent_payer_bump = ctx.bumps.init_rent_payer;
         let seeds = &[b"init_rent_payer".as_ref(), &[init_rent_payer_bump]];
@@ -100,10 +113,19 @@ pub fn upgrade_vault_handler(
         )?;
     }
 
-    // Reallocate data
+    // Reallocate data, the discriminator is unchanged between versions
     existing_vault.realloc(Vault::INIT_SPACE, false)?;
     let mut vault_data = existing_vault.try_borrow_mut_data()?;
+    vault_data[..ANCHOR_DISCRIMINATOR].copy_from_slice(&Vault::DISCRIMINATOR);
     vault_data[ANCHOR_DISCRIMINATOR..].copy_from_slice(&new_vault_vec[..]);
+    drop(vault_data);
+
+    emit_cpi!(VaultUpgraded {
+        owner: ctx.accounts.owner.key(),
+        vault: existing_vault.key(),
+        from_version,
+        to_version: new_vault.version,
+    });
 
     Ok(())
 }
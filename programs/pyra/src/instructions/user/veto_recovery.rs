@@ -0,0 +1,70 @@
+use crate::{
+    check,
+    config::{PyraError, VAULT_VERSION},
+    events::RecoveryVetoed,
+    state::{RecoveryOrder, Vault},
+    utils::close_time_lock,
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct VetoRecovery<'info> {
+    #[account(mut)]
+    pub recovery_order: Box<Account<'info, RecoveryOrder>>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Checked in handler
+    #[account(mut)]
+    pub time_lock_rent_payer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // No has_one, so orders left over from a previous owner can still be cancelled
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), vault.vault_id.as_ref()],
+        bump = vault.bump,
+        constraint = vault.version == VAULT_VERSION @ PyraError::VaultUpgradeRequired
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+/// Lets the current owner veto a guardian recovery before its time lock expires
+pub fn veto_recovery_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, VetoRecovery<'info>>,
+) -> Result<()> {
+    check!(
+        ctx.accounts
+            .recovery_order
+            .time_lock
+            .owner
+            .eq(&ctx.accounts.owner.key()),
+        PyraError::InvalidTimeLockOwner
+    );
+
+    check!(
+        ctx.accounts
+            .recovery_order
+            .vault
+            .eq(&ctx.accounts.vault.key()),
+        PyraError::InvalidRecoveryOrder
+    );
+
+    close_time_lock(
+        &ctx.accounts.recovery_order,
+        &ctx.accounts.time_lock_rent_payer.to_account_info(),
+    )?;
+
+    ctx.accounts
+        .vault
+        .remove_open_order(&ctx.accounts.recovery_order.time_lock);
+
+    emit_cpi!(RecoveryVetoed {
+        owner: ctx.accounts.owner.key(),
+        recovery_order: ctx.accounts.recovery_order.key(),
+    });
+
+    Ok(())
+}
@@ -4,10 +4,16 @@
 use anchor_lang::prelude::*;
 
 mod config;
+mod events;
 mod instructions;
 mod state;
 mod utils;
+use config::MERCHANT_ID_HASH_SIZE;
 use instructions::*;
+use state::{
+    AdminAction, DelegatePermission, GlobalConfigParams, MerchantControls, ProtocolFeature,
+    RentPayer, SpendLimitModeParams, SpendLimitWindowParams, SpendVelocityLimits,
+};
 
 declare_id!("6JjHXLheGSNvvexgzMthEcgjkcirDrGduc3HAKB2P1v2");
 
@@ -29,6 +35,48 @@ pub mod pyra {
 
     // Admin
 
+    pub fn init_global_config(
+        ctx: Context<InitGlobalConfig>,
+        guardian: Pubkey,
+        pauser: Pubkey,
+        params: GlobalConfigParams,
+    ) -> Result<()> {
+        init_global_config_handler(ctx, guardian, pauser, params)
+    }
+
+    pub fn init_drift_market_registry(ctx: Context<InitDriftMarketRegistry>) -> Result<()> {
+        init_drift_market_registry_handler(ctx)
+    }
+
+    pub fn init_settlement_provider_registry(
+        ctx: Context<InitSettlementProviderRegistry>,
+    ) -> Result<()> {
+        init_settlement_provider_registry_handler(ctx)
+    }
+
+    pub fn propose_admin_action(
+        ctx: Context<ProposeAdminAction>,
+        action: AdminAction,
+    ) -> Result<()> {
+        propose_admin_action_handler(ctx, action)
+    }
+
+    pub fn execute_admin_action(ctx: Context<ExecuteAdminAction>) -> Result<()> {
+        execute_admin_action_handler(ctx)
+    }
+
+    pub fn cancel_admin_action(ctx: Context<CancelAdminAction>) -> Result<()> {
+        cancel_admin_action_handler(ctx)
+    }
+
+    pub fn pause(ctx: Context<Pause>, feature: ProtocolFeature) -> Result<()> {
+        pause_handler(ctx, feature)
+    }
+
+    pub fn unpause(ctx: Context<Unpause>, feature: ProtocolFeature) -> Result<()> {
+        unpause_handler(ctx, feature)
+    }
+
     pub fn reclaim_bridge_rent(
         ctx: Context<ReclaimBridgeRent>,
         attestation: Vec<u8>,
@@ -36,28 +84,88 @@ pub mod pyra {
         reclaim_bridge_rent_handler(ctx, attestation)
     }
 
+    pub fn init_rent_treasury(
+        ctx: Context<InitRentTreasury>,
+        refundable_init_fees: u64,
+        live_bridge_rent: u64,
+    ) -> Result<()> {
+        init_rent_treasury_handler(ctx, refundable_init_fees, live_bridge_rent)
+    }
+
+    pub fn init_time_lock_rent_payer(
+        ctx: Context<InitTimeLockRentPayer>,
+        live_time_lock_rent: u64,
+    ) -> Result<()> {
+        init_time_lock_rent_payer_handler(ctx, live_time_lock_rent)
+    }
+
+    pub fn fund_rent_payer(
+        ctx: Context<FundRentPayer>,
+        rent_payer: RentPayer,
+        amount_lamports: u64,
+    ) -> Result<()> {
+        fund_rent_payer_handler(ctx, rent_payer, amount_lamports)
+    }
+
+    pub fn withdraw_rent_surplus(
+        ctx: Context<WithdrawRentSurplus>,
+        rent_payer: RentPayer,
+        amount_lamports: u64,
+    ) -> Result<()> {
+        withdraw_rent_surplus_handler(ctx, rent_payer, amount_lamports)
+    }
+
     // User
 
     pub fn init_user(
         ctx: Context<InitUser>,
+        vault_id: Pubkey,
         spend_limit_per_transaction: u64,
         spend_limit_per_timeframe: u64,
         timeframe_in_seconds: u64,
         next_timeframe_reset_timestamp: u64,
+        settlement_provider_id: u16,
     ) -> Result<()> {
         init_user_handler(
             ctx,
+            vault_id,
             spend_limit_per_transaction,
             spend_limit_per_timeframe,
             timeframe_in_seconds,
             next_timeframe_reset_timestamp,
+            settlement_provider_id,
         )
     }
 
-    pub fn close_user(ctx: Context<CloseUser>) -> Result<()> {
+    pub fn close_user<'info>(ctx: Context<'_, '_, 'info, 'info, CloseUser<'info>>) -> Result<()> {
         close_user_handler(ctx)
     }
 
+    pub fn sweep_deposit_address<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepDepositAddress<'info>>,
+    ) -> Result<()> {
+        sweep_deposit_address_handler(ctx)
+    }
+
+    pub fn initiate_settle_and_close<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitiateSettleAndClose<'info>>,
+    ) -> Result<()> {
+        initiate_settle_and_close_handler(ctx)
+    }
+
+    pub fn settle_withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleWithdraw<'info>>,
+        drift_market_index: u16,
+    ) -> Result<()> {
+        settle_withdraw_handler(ctx, drift_market_index)
+    }
+
+    pub fn cancel_settle_and_close<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelSettleAndClose<'info>>,
+    ) -> Result<()> {
+        cancel_settle_and_close_handler(ctx)
+    }
+
     pub fn upgrade_vault(
         ctx: Context<UpgradeVault>,
         spend_limit_per_transaction: u64,
@@ -74,6 +182,165 @@ pub mod pyra {
         )
     }
 
+    pub fn attest_no_uncounted_orders(ctx: Context<AttestNoUncountedOrders>) -> Result<()> {
+        attest_no_uncounted_orders_handler(ctx)
+    }
+
+    pub fn initiate_owner_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitiateOwnerTransfer<'info>>,
+    ) -> Result<()> {
+        initiate_owner_transfer_handler(ctx)
+    }
+
+    pub fn accept_owner_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptOwnerTransfer<'info>>,
+    ) -> Result<()> {
+        accept_owner_transfer_handler(ctx)
+    }
+
+    pub fn cancel_owner_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelOwnerTransfer<'info>>,
+    ) -> Result<()> {
+        cancel_owner_transfer_handler(ctx)
+    }
+
+    pub fn set_vault_delegate(
+        ctx: Context<SetVaultDelegate>,
+        delegate: Pubkey,
+        permissions: Vec<DelegatePermission>,
+        max_spend_limit_per_transaction: u64,
+        max_spend_limit_per_timeframe: u64,
+    ) -> Result<()> {
+        set_vault_delegate_handler(
+            ctx,
+            delegate,
+            permissions,
+            max_spend_limit_per_transaction,
+            max_spend_limit_per_timeframe,
+        )
+    }
+
+    pub fn revoke_vault_delegate(
+        ctx: Context<RevokeVaultDelegate>,
+        delegate: Pubkey,
+    ) -> Result<()> {
+        revoke_vault_delegate_handler(ctx, delegate)
+    }
+
+    pub fn close_vault_delegates(ctx: Context<CloseVaultDelegates>) -> Result<()> {
+        close_vault_delegates_handler(ctx)
+    }
+
+    pub fn set_vault_guardians(
+        ctx: Context<SetVaultGuardians>,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+        recovery_delay_slots: u64,
+    ) -> Result<()> {
+        set_vault_guardians_handler(ctx, guardians, threshold, recovery_delay_slots)
+    }
+
+    pub fn remove_vault_guardians(ctx: Context<RemoveVaultGuardians>) -> Result<()> {
+        remove_vault_guardians_handler(ctx)
+    }
+
+    pub fn initiate_recovery<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitiateRecovery<'info>>,
+    ) -> Result<()> {
+        initiate_recovery_handler(ctx)
+    }
+
+    pub fn veto_recovery<'info>(
+        ctx: Context<'_, '_, '_, 'info, VetoRecovery<'info>>,
+    ) -> Result<()> {
+        veto_recovery_handler(ctx)
+    }
+
+    pub fn complete_recovery<'info>(
+        ctx: Context<'_, '_, '_, 'info, CompleteRecovery<'info>>,
+    ) -> Result<()> {
+        complete_recovery_handler(ctx)
+    }
+
+    // Sub Accounts
+
+    pub fn init_drift_sub_account(
+        ctx: Context<InitDriftSubAccount>,
+        sub_account_id: u16,
+    ) -> Result<()> {
+        init_drift_sub_account_handler(ctx, sub_account_id)
+    }
+
+    pub fn close_drift_sub_account(
+        ctx: Context<CloseDriftSubAccount>,
+        sub_account_id: u16,
+    ) -> Result<()> {
+        close_drift_sub_account_handler(ctx, sub_account_id)
+    }
+
+    pub fn transfer_collateral<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferCollateral<'info>>,
+        amount_base_units: u64,
+        drift_market_index: u16,
+        from_sub_account_id: u16,
+        to_sub_account_id: u16,
+    ) -> Result<()> {
+        transfer_collateral_handler(
+            ctx,
+            amount_base_units,
+            drift_market_index,
+            from_sub_account_id,
+            to_sub_account_id,
+        )
+    }
+
+    pub fn initiate_collateral_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitiateCollateralTransfer<'info>>,
+        amount_base_units: u64,
+        drift_market_index: u16,
+        from_sub_account_id: u16,
+        to_sub_account_id: u16,
+    ) -> Result<()> {
+        initiate_collateral_transfer_handler(
+            ctx,
+            amount_base_units,
+            drift_market_index,
+            from_sub_account_id,
+            to_sub_account_id,
+        )
+    }
+
+    pub fn fulfil_collateral_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, FulfilCollateralTransfer<'info>>,
+    ) -> Result<()> {
+        fulfil_collateral_transfer_handler(ctx)
+    }
+
+    pub fn cancel_collateral_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelCollateralTransfer<'info>>,
+    ) -> Result<()> {
+        cancel_collateral_transfer_handler(ctx)
+    }
+
+    pub fn initiate_spend_sub_account<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitiateSpendSubAccount<'info>>,
+        spend_sub_account_id: u16,
+    ) -> Result<()> {
+        initiate_spend_sub_account_handler(ctx, spend_sub_account_id)
+    }
+
+    pub fn fulfil_spend_sub_account<'info>(
+        ctx: Context<'_, '_, '_, 'info, FulfilSpendSubAccount<'info>>,
+    ) -> Result<()> {
+        fulfil_spend_sub_account_handler(ctx)
+    }
+
+    pub fn cancel_spend_sub_account<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelSpendSubAccount<'info>>,
+    ) -> Result<()> {
+        cancel_spend_sub_account_handler(ctx)
+    }
+
     // Balance
 
     pub fn fulfil_deposit<'info>(
@@ -94,8 +361,15 @@ pub mod pyra {
         amount_base_units: u64,
         drift_market_index: u16,
         reduce_only: bool,
+        drift_sub_account_id: u16,
     ) -> Result<()> {
-        initiate_withdraw_handler(ctx, amount_base_units, drift_market_index, reduce_only)
+        initiate_withdraw_handler(
+            ctx,
+            amount_base_units,
+            drift_market_index,
+            reduce_only,
+            drift_sub_account_id,
+        )
     }
 
     pub fn fulfil_withdraw<'info>(
@@ -116,8 +390,50 @@ pub mod pyra {
         ctx: Context<'_, '_, 'info, 'info, StartSpend<'info>>,
         amount_usdc_base_units: u64,
         spend_fee: bool,
+        merchant_category_code: u16,
+        merchant_id_hash: [u8; MERCHANT_ID_HASH_SIZE],
+    ) -> Result<()> {
+        start_spend_handler(
+            ctx,
+            amount_usdc_base_units,
+            spend_fee,
+            merchant_category_code,
+            merchant_id_hash,
+        )
+    }
+
+    pub fn authorize_spend<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AuthorizeSpend<'info>>,
+        authorization_id: u64,
+        amount_usdc_base_units: u64,
+        hold_duration_in_seconds: u64,
+        merchant_category_code: u16,
+        merchant_id_hash: [u8; MERCHANT_ID_HASH_SIZE],
     ) -> Result<()> {
-        start_spend_handler(ctx, amount_usdc_base_units, spend_fee)
+        authorize_spend_handler(
+            ctx,
+            authorization_id,
+            amount_usdc_base_units,
+            hold_duration_in_seconds,
+            merchant_category_code,
+            merchant_id_hash,
+        )
+    }
+
+    pub fn capture_spend<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CaptureSpend<'info>>,
+        amount_usdc_base_units: u64,
+        is_final_capture: bool,
+    ) -> Result<()> {
+        capture_spend_handler(ctx, amount_usdc_base_units, is_final_capture)
+    }
+
+    pub fn release_spend_hold(ctx: Context<ReleaseSpendHold>) -> Result<()> {
+        release_spend_hold_handler(ctx)
+    }
+
+    pub fn close_spend_record(ctx: Context<CloseSpendRecord>) -> Result<()> {
+        close_spend_record_handler(ctx)
     }
 
     pub fn complete_spend<'info>(
@@ -126,12 +442,53 @@ pub mod pyra {
         complete_spend_handler(ctx)
     }
 
+    pub fn start_collateral_spend<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StartCollateralSpend<'info>>,
+        collateral_market_index: u16,
+        collateral_amount_base_units: u64,
+    ) -> Result<()> {
+        start_collateral_spend_handler(ctx, collateral_market_index, collateral_amount_base_units)
+    }
+
+    pub fn deposit_collateral_spend<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DepositCollateralSpend<'info>>,
+    ) -> Result<()> {
+        deposit_collateral_spend_handler(ctx)
+    }
+
+    pub fn receive_refund<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReceiveRefund<'info>>,
+        message: Vec<u8>,
+        attestation: Vec<u8>,
+        spend_cctp_nonce: u64,
+        restore_spend_limit: bool,
+    ) -> Result<()> {
+        receive_refund_handler(
+            ctx,
+            message,
+            attestation,
+            spend_cctp_nonce,
+            restore_spend_limit,
+        )
+    }
+
+    pub fn set_settlement_provider(
+        ctx: Context<SetSettlementProvider>,
+        settlement_provider_id: u16,
+    ) -> Result<()> {
+        set_settlement_provider_handler(ctx, settlement_provider_id)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn initiate_spend_limits<'info>(
         ctx: Context<'_, '_, 'info, 'info, InitiateSpendLimits<'info>>,
         spend_limit_per_transaction: u64,
         spend_limit_per_timeframe: u64,
         timeframe_in_seconds: u64,
         next_timeframe_reset_timestamp: u64,
+        spend_limit_windows: Vec<SpendLimitWindowParams>,
+        spend_limit_mode_params: SpendLimitModeParams,
+        spend_velocity_limits: SpendVelocityLimits,
     ) -> Result<()> {
         initiate_spend_limits_handler(
             ctx,
@@ -139,6 +496,9 @@ pub mod pyra {
             spend_limit_per_timeframe,
             timeframe_in_seconds,
             next_timeframe_reset_timestamp,
+            spend_limit_windows,
+            spend_limit_mode_params,
+            spend_velocity_limits,
         )
     }
 
@@ -154,6 +514,8 @@ pub mod pyra {
         spend_limit_per_timeframe: u64,
         timeframe_in_seconds: u64,
         next_timeframe_reset_timestamp: u64,
+        spend_limit_windows: Vec<SpendLimitWindowParams>,
+        spend_velocity_limits: SpendVelocityLimits,
     ) -> Result<()> {
         increase_spend_limits_handler(
             ctx,
@@ -161,9 +523,67 @@ pub mod pyra {
             spend_limit_per_timeframe,
             timeframe_in_seconds,
             next_timeframe_reset_timestamp,
+            spend_limit_windows,
+            spend_velocity_limits,
         )
     }
 
+    pub fn boost_spend_limits<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BoostSpendLimits<'info>>,
+        additional_spend_limit_per_transaction: u64,
+        additional_spend_limit: u64,
+        duration_in_seconds: u64,
+    ) -> Result<()> {
+        boost_spend_limits_handler(
+            ctx,
+            additional_spend_limit_per_transaction,
+            additional_spend_limit,
+            duration_in_seconds,
+        )
+    }
+
+    pub fn freeze_card(ctx: Context<FreezeCard>) -> Result<()> {
+        freeze_card_handler(ctx)
+    }
+
+    pub fn initiate_unfreeze_card<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitiateUnfreezeCard<'info>>,
+    ) -> Result<()> {
+        initiate_unfreeze_card_handler(ctx)
+    }
+
+    pub fn fulfil_unfreeze_card<'info>(
+        ctx: Context<'_, '_, '_, 'info, FulfilUnfreezeCard<'info>>,
+    ) -> Result<()> {
+        fulfil_unfreeze_card_handler(ctx)
+    }
+
+    pub fn cancel_unfreeze_card<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelUnfreezeCard<'info>>,
+    ) -> Result<()> {
+        cancel_unfreeze_card_handler(ctx)
+    }
+
+    pub fn tighten_merchant_controls(
+        ctx: Context<TightenMerchantControls>,
+        merchant_controls: MerchantControls,
+    ) -> Result<()> {
+        tighten_merchant_controls_handler(ctx, merchant_controls)
+    }
+
+    pub fn initiate_merchant_controls<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitiateMerchantControls<'info>>,
+        merchant_controls: MerchantControls,
+    ) -> Result<()> {
+        initiate_merchant_controls_handler(ctx, merchant_controls)
+    }
+
+    pub fn fulfil_merchant_controls<'info>(
+        ctx: Context<'_, '_, '_, 'info, FulfilMerchantControls<'info>>,
+    ) -> Result<()> {
+        fulfil_merchant_controls_handler(ctx)
+    }
+
     // Collateral Repay
 
     pub fn start_collateral_repay<'info>(
@@ -175,14 +595,26 @@ pub mod pyra {
     pub fn deposit_collateral_repay<'info>(
         ctx: Context<'_, '_, 'info, 'info, DepositCollateralRepay<'info>>,
         deposit_market_index: u16,
+        drift_sub_account_id: u16,
     ) -> Result<()> {
-        deposit_collateral_repay_handler(ctx, deposit_market_index)
+        deposit_collateral_repay_handler(ctx, deposit_market_index, drift_sub_account_id)
     }
 
     pub fn withdraw_collateral_repay<'info>(
         ctx: Context<'_, '_, 'info, 'info, WithdrawCollateralRepay<'info>>,
         withdraw_market_index: u16,
+        drift_sub_account_id: u16,
     ) -> Result<()> {
-        withdraw_collateral_repay_handler(ctx, withdraw_market_index)
+        withdraw_collateral_repay_handler(ctx, withdraw_market_index, drift_sub_account_id)
+    }
+
+    // Referral
+
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        register_referrer_handler(ctx)
+    }
+
+    pub fn claim_referrer_rewards(ctx: Context<ClaimReferrerRewards>) -> Result<()> {
+        claim_referrer_rewards_handler(ctx)
     }
 }
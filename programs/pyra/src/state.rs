@@ -4,5 +4,38 @@ pub use vault::*;
 mod collateral_repay_ledger;
 pub use collateral_repay_ledger::*;
 
+mod collateral_spend_ledger;
+pub use collateral_spend_ledger::*;
+
+mod spend_hold;
+pub use spend_hold::*;
+
+mod spend_record;
+pub use spend_record::*;
+
 mod time_lock;
 pub use time_lock::*;
+
+mod global_config;
+pub use global_config::*;
+
+mod drift_market_registry;
+pub use drift_market_registry::*;
+
+mod settlement_provider_registry;
+pub use settlement_provider_registry::*;
+
+mod vault_delegates;
+pub use vault_delegates::*;
+
+mod vault_guardians;
+pub use vault_guardians::*;
+
+mod vault_merchant_controls;
+pub use vault_merchant_controls::*;
+
+mod referrer_rewards;
+pub use referrer_rewards::*;
+
+mod rent_treasury;
+pub use rent_treasury::*;
@@ -0,0 +1,14 @@
+use crate::config::{ANCHOR_DISCRIMINATOR, U16_SIZE, U64_SIZE};
+use anchor_lang::prelude::*;
+
+/// Ledger for tracking the collateral sold and the caller's USDC balance during the swap instruction of collateral spend
+#[account]
+pub struct CollateralSpendLedger {
+    pub collateral_market_index: u16,
+    pub collateral_amount_base_units: u64,
+    pub starting_usdc_balance: u64,
+}
+
+impl Space for CollateralSpendLedger {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR + U16_SIZE + U64_SIZE + U64_SIZE;
+}
@@ -0,0 +1,189 @@
+use crate::{
+    check,
+    config::{
+        PyraError, ANCHOR_DISCRIMINATOR, MAX_DRIFT_MARKETS, PUBKEY_SIZE, U16_SIZE, U1_SIZE,
+        U64_SIZE, U8_SIZE, USDC_MINT, VEC_LENGTH_PREFIX_SIZE,
+    },
+};
+use anchor_lang::prelude::*;
+
+/// Drift spot market supported by Pyra as collateral
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct DriftMarket {
+    pub market_index: u16,
+    pub mint: Pubkey,
+    pub pyth_feed: [u8; 32],
+    pub base_units_per_token: u64,
+
+    // Disabled markets block new deposits, but still allow withdrawals and repays
+    pub is_disabled: bool,
+}
+
+impl Space for DriftMarket {
+    const INIT_SPACE: usize = U16_SIZE + PUBKEY_SIZE + PUBKEY_SIZE + U64_SIZE + U1_SIZE;
+}
+
+/// Registry of supported Drift markets, changed through time locked AdminProposals
+#[account]
+pub struct DriftMarketRegistry {
+    pub bump: u8,
+    pub markets: Vec<DriftMarket>,
+}
+
+impl Space for DriftMarketRegistry {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR
+        + U8_SIZE
+        + VEC_LENGTH_PREFIX_SIZE
+        + (DriftMarket::INIT_SPACE * MAX_DRIFT_MARKETS);
+}
+
+impl DriftMarketRegistry {
+    pub fn add_market(&mut self, market: DriftMarket) -> Result<()> {
+        check!(
+            !self
+                .markets
+                .iter()
+                .any(|existing| existing.market_index == market.market_index
+                    || existing.mint == market.mint),
+            PyraError::DriftMarketAlreadyExists
+        );
+        check!(
+            self.markets.len() < MAX_DRIFT_MARKETS,
+            PyraError::DriftMarketRegistryFull
+        );
+
+        self.markets.push(market);
+
+        Ok(())
+    }
+
+    fn get_market_mut(&mut self, market_index: u16) -> Result<&mut DriftMarket> {
+        Ok(self
+            .markets
+            .iter_mut()
+            .find(|market| market.market_index == market_index)
+            .ok_or(PyraError::InvalidMarketIndex)?)
+    }
+
+    pub fn update_market(&mut self, market_index: u16, pyth_feed: [u8; 32]) -> Result<()> {
+        self.get_market_mut(market_index)?.pyth_feed = pyth_feed;
+
+        Ok(())
+    }
+
+    /// Disabling a market only blocks new deposits, so users can still withdraw or repay with it
+    pub fn set_market_disabled(&mut self, market_index: u16, is_disabled: bool) -> Result<()> {
+        self.get_market_mut(market_index)?.is_disabled = is_disabled;
+
+        Ok(())
+    }
+
+    /// Removes a market from the registry. Users must withdraw their positions before it's delisted, as the market can no longer be used in any instruction.
+    pub fn delist_market(&mut self, market_index: u16) -> Result<()> {
+        let position = self
+            .markets
+            .iter()
+            .position(|market| market.market_index == market_index)
+            .ok_or(PyraError::InvalidMarketIndex)?;
+
+        self.markets.remove(position);
+
+        Ok(())
+    }
+
+    /// Mints whose deposit address ATA must be swept before a vault closes, which are every market's mint and USDC
+    pub fn get_deposit_mints(&self) -> Vec<Pubkey> {
+        let mut deposit_mints: Vec<Pubkey> =
+            self.markets.iter().map(|market| market.mint).collect();
+        if !deposit_mints.contains(&USDC_MINT) {
+            deposit_mints.push(USDC_MINT);
+        }
+
+        deposit_mints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_market_rejects_a_duplicate_index_or_mint() {
+        let usdc = DriftMarket {
+            market_index: 0,
+            mint: Pubkey::new_unique(),
+            pyth_feed: [0; 32],
+            base_units_per_token: 1_000_000,
+            is_disabled: false,
+        };
+        let mut registry = DriftMarketRegistry {
+            bump: 255,
+            markets: vec![usdc.clone()],
+        };
+
+        assert_eq!(
+            registry
+                .add_market(DriftMarket {
+                    mint: Pubkey::new_unique(),
+                    ..usdc.clone()
+                })
+                .expect_err("duplicate index"),
+            PyraError::DriftMarketAlreadyExists.into()
+        );
+        assert_eq!(
+            registry
+                .add_market(DriftMarket {
+                    market_index: 1,
+                    ..usdc.clone()
+                })
+                .expect_err("duplicate mint"),
+            PyraError::DriftMarketAlreadyExists.into()
+        );
+
+        registry
+            .add_market(DriftMarket {
+                market_index: 1,
+                mint: Pubkey::new_unique(),
+                ..usdc
+            })
+            .expect("new market");
+        assert_eq!(registry.markets.len(), 2);
+    }
+
+    #[test]
+    fn markets_are_updated_and_delisted_by_index() {
+        let markets: Vec<DriftMarket> = (0..3)
+            .map(|market_index| DriftMarket {
+                market_index,
+                mint: Pubkey::new_unique(),
+                pyth_feed: [0; 32],
+                base_units_per_token: 1_000_000,
+                is_disabled: false,
+            })
+            .collect();
+        let mut registry = DriftMarketRegistry { bump: 255, markets };
+
+        registry.update_market(1, [7; 32]).expect("listed market");
+        registry
+            .set_market_disabled(1, true)
+            .expect("listed market");
+        assert_eq!(registry.markets[1].pyth_feed, [7; 32]);
+        assert!(registry.markets[1].is_disabled);
+        assert!(!registry.markets[0].is_disabled);
+
+        registry.delist_market(1).expect("listed market");
+        assert_eq!(registry.markets.len(), 2);
+        assert_eq!(registry.markets[1].market_index, 2);
+
+        assert_eq!(
+            registry
+                .update_market(1, [7; 32])
+                .expect_err("delisted market"),
+            PyraError::InvalidMarketIndex.into()
+        );
+        assert_eq!(
+            registry.delist_market(1).expect_err("delisted market"),
+            PyraError::InvalidMarketIndex.into()
+        );
+    }
+}
@@ -0,0 +1,285 @@
+use crate::{
+    check,
+    config::{
+        PyraError, ANCHOR_DISCRIMINATOR, GLOBAL_CONFIG_RESERVED_SPACE, HARD_MAX_SLIPPAGE_BPS,
+        MAX_HEALTH_PERCENT, MAX_REFERRAL_FEE_SHARE_BPS, MAX_SPEND_FEE_BPS,
+        MIN_GOVERNANCE_DELAY_SLOTS, PUBKEY_SIZE, U16_SIZE, U64_SIZE, U8_SIZE,
+    },
+};
+use anchor_lang::prelude::*;
+
+/// Protocol wide settings, stored on-chain so they can be changed by the admin without a program upgrade.
+/// Changes go through time locked AdminProposals, which the guardian can cancel.
+#[account]
+pub struct GlobalConfig {
+    pub admin: Pubkey,
+    pub bump: u8,
+
+    pub spend_caller: Pubkey,
+    pub spend_fee_destination: Pubkey,
+    pub spend_fee_bps: u64,
+    pub rent_reclaimer: Pubkey,
+
+    pub auto_repay_max_slippage_bps: u16,
+    pub auto_repay_max_health_result_percent: u8,
+    pub pyth_max_price_age_seconds: u64,
+
+    pub time_lock_duration_slots: u64,
+
+    pub guardian: Pubkey,
+    pub governance_delay_slots: u64,
+
+    pub pauser: Pubkey,
+    // Bitmask of paused ProtocolFeatures, set instantly by the pauser
+    pub paused_features: u8,
+
+    // Share of a referred vault's spend fee that goes to its referrer
+    pub referral_fee_share_bps: u64,
+
+    // Slippage allowed when swapping collateral to USDC to fund a spend
+    pub collateral_spend_max_slippage_bps: u16,
+
+    // New fields should be taken from the reserved space, so the config doesn't need a realloc
+    pub reserved: [u8; GLOBAL_CONFIG_RESERVED_SPACE],
+}
+
+impl Space for GlobalConfig {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR
+        + PUBKEY_SIZE
+        + U8_SIZE
+        + PUBKEY_SIZE
+        + PUBKEY_SIZE
+        + U64_SIZE
+        + PUBKEY_SIZE
+        + U16_SIZE
+        + U8_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + PUBKEY_SIZE
+        + U64_SIZE
+        + PUBKEY_SIZE
+        + U8_SIZE
+        + U64_SIZE
+        + U16_SIZE
+        + GLOBAL_CONFIG_RESERVED_SPACE;
+}
+
+impl GlobalConfig {
+    /// Validates the params against the protocol's sanity bounds before writing them to the config
+    pub fn set_params(&mut self, params: GlobalConfigParams) -> Result<()> {
+        params.validate()?;
+
+        self.spend_caller = params.spend_caller;
+        self.spend_fee_destination = params.spend_fee_destination;
+        self.spend_fee_bps = params.spend_fee_bps;
+        self.rent_reclaimer = params.rent_reclaimer;
+        self.auto_repay_max_slippage_bps = params.auto_repay_max_slippage_bps;
+        self.auto_repay_max_health_result_percent = params.auto_repay_max_health_result_percent;
+        self.pyth_max_price_age_seconds = params.pyth_max_price_age_seconds;
+        self.time_lock_duration_slots = params.time_lock_duration_slots;
+        self.governance_delay_slots = params.governance_delay_slots;
+        self.referral_fee_share_bps = params.referral_fee_share_bps;
+        self.collateral_spend_max_slippage_bps = params.collateral_spend_max_slippage_bps;
+
+        Ok(())
+    }
+
+    /// A feature is paused if either it or the whole protocol (ProtocolFeature::All) is paused
+    pub fn is_paused(&self, feature: ProtocolFeature) -> bool {
+        let flags = ProtocolFeature::All.flag() | feature.flag();
+        self.paused_features & flags != 0
+    }
+
+    pub fn set_paused(&mut self, feature: ProtocolFeature, is_paused: bool) {
+        if is_paused {
+            self.paused_features |= feature.flag();
+        } else {
+            self.paused_features &= !feature.flag();
+        }
+    }
+}
+
+/// Groups of instructions that can be paused by the pauser during an incident. User-protective instructions (cancelling orders, rescuing deposits, closing accounts, changing owner) are never paused.
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub enum ProtocolFeature {
+    All,
+    Spend,
+    Deposits,
+    Withdrawals,
+    CollateralRepay,
+    UserInit,
+}
+
+impl ProtocolFeature {
+    fn flag(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+/// Admin-settable values of the GlobalConfig
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct GlobalConfigParams {
+    pub spend_caller: Pubkey,
+    pub spend_fee_destination: Pubkey,
+    pub spend_fee_bps: u64,
+    pub rent_reclaimer: Pubkey,
+    pub auto_repay_max_slippage_bps: u16,
+    pub auto_repay_max_health_result_percent: u8,
+    pub pyth_max_price_age_seconds: u64,
+    pub time_lock_duration_slots: u64,
+    pub governance_delay_slots: u64,
+    pub referral_fee_share_bps: u64,
+    pub collateral_spend_max_slippage_bps: u16,
+}
+
+impl Space for GlobalConfigParams {
+    const INIT_SPACE: usize = PUBKEY_SIZE
+        + PUBKEY_SIZE
+        + U64_SIZE
+        + PUBKEY_SIZE
+        + U16_SIZE
+        + U8_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U16_SIZE;
+}
+
+impl GlobalConfigParams {
+    pub fn validate(&self) -> Result<()> {
+        check!(
+            self.spend_fee_bps <= MAX_SPEND_FEE_BPS,
+            PyraError::InvalidSpendFeeBPS
+        );
+        check!(
+            self.referral_fee_share_bps <= MAX_REFERRAL_FEE_SHARE_BPS,
+            PyraError::InvalidReferralFeeShareBPS
+        );
+        check!(
+            self.auto_repay_max_slippage_bps <= HARD_MAX_SLIPPAGE_BPS,
+            PyraError::InvalidSlippageBPS
+        );
+        check!(
+            self.auto_repay_max_health_result_percent <= MAX_HEALTH_PERCENT,
+            PyraError::InvalidHealthResultPercent
+        );
+        check!(
+            self.collateral_spend_max_slippage_bps <= HARD_MAX_SLIPPAGE_BPS,
+            PyraError::InvalidSlippageBPS
+        );
+        check!(
+            self.pyth_max_price_age_seconds > 0,
+            PyraError::InvalidPythMaxPriceAge
+        );
+        check!(
+            self.time_lock_duration_slots > 0,
+            PyraError::InvalidTimeLockDuration
+        );
+        check!(
+            self.governance_delay_slots >= MIN_GOVERNANCE_DELAY_SLOTS,
+            PyraError::InvalidGovernanceDelay
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_accepts_params_up_to_their_limits() {
+        let params = GlobalConfigParams {
+            spend_caller: Pubkey::new_unique(),
+            spend_fee_destination: Pubkey::new_unique(),
+            spend_fee_bps: MAX_SPEND_FEE_BPS,
+            referral_fee_share_bps: MAX_REFERRAL_FEE_SHARE_BPS,
+            rent_reclaimer: Pubkey::new_unique(),
+            auto_repay_max_slippage_bps: HARD_MAX_SLIPPAGE_BPS,
+            auto_repay_max_health_result_percent: MAX_HEALTH_PERCENT,
+            collateral_spend_max_slippage_bps: HARD_MAX_SLIPPAGE_BPS,
+            pyth_max_price_age_seconds: 1,
+            time_lock_duration_slots: 1,
+            governance_delay_slots: MIN_GOVERNANCE_DELAY_SLOTS,
+        };
+        params.validate().expect("params at their limits");
+
+        let params = GlobalConfigParams {
+            spend_fee_bps: MAX_SPEND_FEE_BPS + 1,
+            ..params
+        };
+        assert_eq!(
+            params.validate().expect_err("spend fee above the maximum"),
+            PyraError::InvalidSpendFeeBPS.into()
+        );
+
+        let params = GlobalConfigParams {
+            spend_fee_bps: MAX_SPEND_FEE_BPS,
+            referral_fee_share_bps: MAX_REFERRAL_FEE_SHARE_BPS + 1,
+            ..params
+        };
+        assert_eq!(
+            params
+                .validate()
+                .expect_err("referral share above the maximum"),
+            PyraError::InvalidReferralFeeShareBPS.into()
+        );
+
+        let params = GlobalConfigParams {
+            referral_fee_share_bps: MAX_REFERRAL_FEE_SHARE_BPS,
+            collateral_spend_max_slippage_bps: HARD_MAX_SLIPPAGE_BPS + 1,
+            ..params
+        };
+        assert_eq!(
+            params
+                .validate()
+                .expect_err("collateral spend slippage above the maximum"),
+            PyraError::InvalidSlippageBPS.into()
+        );
+    }
+
+    #[test]
+    fn validate_rejects_disabled_price_age_and_delays() {
+        let params = GlobalConfigParams {
+            spend_caller: Pubkey::new_unique(),
+            spend_fee_destination: Pubkey::new_unique(),
+            spend_fee_bps: 0,
+            referral_fee_share_bps: 0,
+            rent_reclaimer: Pubkey::new_unique(),
+            auto_repay_max_slippage_bps: 0,
+            auto_repay_max_health_result_percent: 0,
+            collateral_spend_max_slippage_bps: 0,
+            pyth_max_price_age_seconds: 0,
+            time_lock_duration_slots: 1,
+            governance_delay_slots: MIN_GOVERNANCE_DELAY_SLOTS,
+        };
+        assert_eq!(
+            params.validate().expect_err("zero price age"),
+            PyraError::InvalidPythMaxPriceAge.into()
+        );
+
+        let params = GlobalConfigParams {
+            pyth_max_price_age_seconds: 60,
+            time_lock_duration_slots: 0,
+            ..params
+        };
+        assert_eq!(
+            params.validate().expect_err("zero time lock"),
+            PyraError::InvalidTimeLockDuration.into()
+        );
+
+        let params = GlobalConfigParams {
+            time_lock_duration_slots: 1,
+            governance_delay_slots: MIN_GOVERNANCE_DELAY_SLOTS - 1,
+            ..params
+        };
+        assert_eq!(
+            params
+                .validate()
+                .expect_err("governance delay below the minimum"),
+            PyraError::InvalidGovernanceDelay.into()
+        );
+    }
+}
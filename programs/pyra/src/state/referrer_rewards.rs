@@ -0,0 +1,17 @@
+use crate::config::{ANCHOR_DISCRIMINATOR, PUBKEY_SIZE, U64_SIZE, U8_SIZE};
+use anchor_lang::prelude::*;
+
+/// Rewards ledger for a referrer, which earns a share of the spend fees of every vault it referred. The USDC
+/// is held in the ATA of this account until the referrer claims it.
+#[account]
+pub struct ReferrerRewards {
+    pub referrer: Pubkey,
+    pub bump: u8,
+
+    pub unclaimed_usdc_base_units: u64,
+    pub total_earned_usdc_base_units: u64,
+}
+
+impl Space for ReferrerRewards {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR + PUBKEY_SIZE + U8_SIZE + U64_SIZE + U64_SIZE;
+}
@@ -0,0 +1,126 @@
+use crate::{
+    check,
+    config::{PyraError, ANCHOR_DISCRIMINATOR, TIME_LOCK_RENT_PAYER_SEEDS, U64_SIZE, U8_SIZE},
+};
+use anchor_lang::prelude::*;
+
+/// PDAs that pay rent on behalf of users, each holding the SOL for one flow
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub enum RentPayer {
+    Init,
+    Bridge,
+    TimeLock,
+}
+
+impl RentPayer {
+    pub fn seeds(self) -> &'static [u8] {
+        match self {
+            Self::Init => b"init_rent_payer",
+            Self::Bridge => b"bridge_rent_payer",
+            Self::TimeLock => TIME_LOCK_RENT_PAYER_SEEDS,
+        }
+    }
+
+    /// Data held by the rent payer, which it must stay rent exempt for
+    pub fn space(self) -> usize {
+        match self {
+            Self::Init | Self::Bridge => 0,
+            Self::TimeLock => TimeLockRentPayer::INIT_SPACE,
+        }
+    }
+}
+
+/// Accounting for the rent payer PDAs. Init fees are owed out of the init rent payer when vaults close, so
+/// only those are held back when withdrawing surplus. Bridge rent is held in live accounts and returns to its
+/// payer when they close, so it's tracked to show where the treasury's SOL is. Time lock rent works the same
+/// way, but is tracked by the time lock rent payer itself, so order instructions don't all write to the treasury.
+#[account]
+pub struct RentTreasury {
+    pub bump: u8,
+
+    pub refundable_init_fees: u64,
+    pub live_bridge_rent: u64,
+}
+
+impl Space for RentTreasury {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR + U8_SIZE + U64_SIZE + U64_SIZE;
+}
+
+impl RentTreasury {
+    /// Time lock rent is read from the time lock rent payer, which tracks it
+    pub fn obligations(
+        &self,
+        rent_payer: RentPayer,
+        rent_payer_account: &AccountInfo,
+    ) -> Result<u64> {
+        match rent_payer {
+            RentPayer::Init => Ok(self.refundable_init_fees),
+            RentPayer::Bridge => Ok(self.live_bridge_rent),
+            RentPayer::TimeLock => {
+                Ok(TimeLockRentPayer::load(rent_payer_account)?.live_time_lock_rent)
+            }
+        }
+    }
+
+    pub fn add_obligations(&mut self, rent_payer: RentPayer, lamports: u64) -> Result<()> {
+        if let Some(obligations) = self.obligations_mut(rent_payer) {
+            *obligations = obligations
+                .checked_add(lamports)
+                .ok_or(PyraError::MathOverflow)?;
+        }
+
+        Ok(())
+    }
+
+    /// Saturates, as accounts opened before the treasury was initialized may not have been counted
+    pub fn release_obligations(&mut self, rent_payer: RentPayer, lamports: u64) {
+        if let Some(obligations) = self.obligations_mut(rent_payer) {
+            *obligations = obligations.saturating_sub(lamports);
+        }
+    }
+
+    /// Lamports that must stay in the rent payer, on top of its rent exempt minimum
+    pub fn reserved_lamports(&self, rent_payer: RentPayer) -> u64 {
+        match rent_payer {
+            RentPayer::Init => self.refundable_init_fees,
+            RentPayer::Bridge | RentPayer::TimeLock => 0,
+        }
+    }
+
+    fn obligations_mut(&mut self, rent_payer: RentPayer) -> Option<&mut u64> {
+        match rent_payer {
+            RentPayer::Init => Some(&mut self.refundable_init_fees),
+            RentPayer::Bridge => Some(&mut self.live_bridge_rent),
+            RentPayer::TimeLock => None,
+        }
+    }
+}
+
+/// Data of the time lock rent payer. It tracks the rent it has lent to open program-paid time locks, as it's
+/// already written by every instruction that opens or closes one.
+#[account]
+pub struct TimeLockRentPayer {
+    pub bump: u8,
+
+    pub live_time_lock_rent: u64,
+}
+
+impl Space for TimeLockRentPayer {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR + U8_SIZE + U64_SIZE;
+}
+
+impl TimeLockRentPayer {
+    /// The rent payer is passed as an unchecked account, as it only holds data for program-paid time locks
+    pub fn load(account: &AccountInfo) -> Result<Self> {
+        check!(
+            account.owner.eq(&crate::ID),
+            PyraError::InvalidTimeLockRentPayer
+        );
+
+        Self::try_deserialize(&mut &account.try_borrow_data()?[..])
+    }
+
+    pub fn save(&self, account: &AccountInfo) -> Result<()> {
+        self.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])
+    }
+}
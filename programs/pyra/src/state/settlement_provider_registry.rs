@@ -0,0 +1,105 @@
+use crate::{
+    check,
+    config::{
+        PyraError, ANCHOR_DISCRIMINATOR, MAX_SETTLEMENT_PROVIDERS, PUBKEY_SIZE, U16_SIZE, U1_SIZE,
+        U32_SIZE, U8_SIZE, VEC_LENGTH_PREFIX_SIZE,
+    },
+};
+use anchor_lang::prelude::*;
+
+/// CCTP route that card spend is bridged through to settle with a card provider
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SettlementProvider {
+    pub provider_id: u16,
+    pub destination_domain: u32,
+
+    // EVM addresses are left-padded to 32 bytes
+    pub mint_recipient: Pubkey,
+    pub destination_caller: Pubkey,
+
+    // Disabled providers block new spends, vaults bound to them must move to another provider
+    pub is_disabled: bool,
+}
+
+impl Space for SettlementProvider {
+    const INIT_SPACE: usize = U16_SIZE + U32_SIZE + PUBKEY_SIZE + PUBKEY_SIZE + U1_SIZE;
+}
+
+/// Registry of card provider settlement routes, changed through time locked AdminProposals
+#[account]
+pub struct SettlementProviderRegistry {
+    pub bump: u8,
+    pub providers: Vec<SettlementProvider>,
+}
+
+impl Space for SettlementProviderRegistry {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR
+        + U8_SIZE
+        + VEC_LENGTH_PREFIX_SIZE
+        + (SettlementProvider::INIT_SPACE * MAX_SETTLEMENT_PROVIDERS);
+}
+
+impl SettlementProviderRegistry {
+    pub fn add_provider(&mut self, provider: SettlementProvider) -> Result<()> {
+        check!(
+            !self
+                .providers
+                .iter()
+                .any(|existing| existing.provider_id == provider.provider_id),
+            PyraError::SettlementProviderAlreadyExists
+        );
+        check!(
+            self.providers.len() < MAX_SETTLEMENT_PROVIDERS,
+            PyraError::SettlementProviderRegistryFull
+        );
+
+        self.providers.push(provider);
+
+        Ok(())
+    }
+
+    pub fn get_provider(&self, provider_id: u16) -> Result<&SettlementProvider> {
+        Ok(self
+            .providers
+            .iter()
+            .find(|provider| provider.provider_id == provider_id)
+            .ok_or(PyraError::InvalidSettlementProvider)?)
+    }
+
+    fn get_provider_mut(&mut self, provider_id: u16) -> Result<&mut SettlementProvider> {
+        Ok(self
+            .providers
+            .iter_mut()
+            .find(|provider| provider.provider_id == provider_id)
+            .ok_or(PyraError::InvalidSettlementProvider)?)
+    }
+
+    pub fn update_provider(
+        &mut self,
+        provider_id: u16,
+        destination_domain: u32,
+        mint_recipient: Pubkey,
+        destination_caller: Pubkey,
+    ) -> Result<()> {
+        let provider = self.get_provider_mut(provider_id)?;
+        provider.destination_domain = destination_domain;
+        provider.mint_recipient = mint_recipient;
+        provider.destination_caller = destination_caller;
+
+        Ok(())
+    }
+
+    pub fn set_provider_disabled(&mut self, provider_id: u16, is_disabled: bool) -> Result<()> {
+        self.get_provider_mut(provider_id)?.is_disabled = is_disabled;
+
+        Ok(())
+    }
+
+    /// Returns the provider if vaults can currently spend through it
+    pub fn get_enabled_provider(&self, provider_id: u16) -> Result<&SettlementProvider> {
+        let provider = self.get_provider(provider_id)?;
+        check!(!provider.is_disabled, PyraError::SettlementProviderDisabled);
+
+        Ok(provider)
+    }
+}
@@ -0,0 +1,395 @@
+use crate::{
+    config::{
+        PyraError, ANCHOR_DISCRIMINATOR, MAX_SPEND_LIMIT_WINDOWS, PUBKEY_SIZE, U16_SIZE, U64_SIZE,
+        U8_SIZE,
+    },
+    state::{Vault, VaultMerchantControls},
+};
+use anchor_lang::prelude::*;
+
+/// Spend limit reserved by a card authorization, drawn down by captures and released once it expires
+#[account]
+pub struct SpendHold {
+    pub vault: Pubkey,
+    pub bump: u8,
+
+    // Card authorization the hold was created for, part of the PDA seeds
+    pub authorization_id: u64,
+
+    // Amount still held, which is reduced by each capture
+    pub amount_usdc_base_units: u64,
+
+    pub expiry_timestamp: u64,
+
+    pub authorization_timestamp: u64,
+
+    // Reset timestamps of the primary timeframe and each window when authorized. The held limit is only restored
+    // to the timeframes still in the period it was reserved from.
+    pub timeframe_reset_timestamp: u64,
+    pub window_reset_timestamps: [u64; MAX_SPEND_LIMIT_WINDOWS],
+
+    // Part of the held amount drawn from the spend limit boost, and the expiry of that boost
+    pub boost_usdc_base_units: u64,
+    pub boost_expiry_timestamp: u64,
+
+    // Category the authorization was counted against in the vault's merchant controls
+    pub merchant_category_code: u16,
+
+    // Spend caller that paid the rent, and receives it when the hold is closed
+    pub rent_payer: Pubkey,
+}
+
+impl Space for SpendHold {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR
+        + PUBKEY_SIZE
+        + U8_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + (U64_SIZE * MAX_SPEND_LIMIT_WINDOWS)
+        + U64_SIZE
+        + U64_SIZE
+        + U16_SIZE
+        + PUBKEY_SIZE;
+}
+
+impl SpendHold {
+    pub fn is_expired(&self, current_timestamp: u64) -> bool {
+        current_timestamp >= self.expiry_timestamp
+    }
+
+    /// Draws a capture from the hold, returning true if it closes the hold
+    pub fn capture(&mut self, amount_usdc_base_units: u64, is_final_capture: bool) -> Result<bool> {
+        self.amount_usdc_base_units = self
+            .amount_usdc_base_units
+            .checked_sub(amount_usdc_base_units)
+            .ok_or(PyraError::InsufficientSpendHold)?;
+
+        Ok(is_final_capture || self.amount_usdc_base_units == 0)
+    }
+
+    /// Returns the uncaptured amount to the vault's spend limits and merchant category spend, for each timeframe
+    /// still in the period it was reserved from. Returns the amount restored to the primary timeframe.
+    pub fn release(
+        &mut self,
+        vault: &mut Vault,
+        vault_merchant_controls: Option<&mut VaultMerchantControls>,
+        current_timestamp: u64,
+    ) -> u64 {
+        let amount_usdc_base_units = self.amount_usdc_base_units;
+        self.amount_usdc_base_units = 0;
+
+        // The whole authorization was counted against its category, including any part covered by the boost
+        if let Some(vault_merchant_controls) = vault_merchant_controls {
+            vault_merchant_controls.release_spend(
+                self.merchant_category_code,
+                amount_usdc_base_units,
+                self.timeframe_reset_timestamp,
+            );
+        }
+
+        // The boost covered the part of the authorization beyond the limits, so it's returned first
+        let boost_usdc_base_units = amount_usdc_base_units.min(self.boost_usdc_base_units);
+        self.boost_usdc_base_units = 0;
+        vault.restore_spend_limit_boost(boost_usdc_base_units, self.boost_expiry_timestamp);
+        let amount_usdc_base_units = amount_usdc_base_units.saturating_sub(boost_usdc_base_units);
+
+        vault.restore_spend_limit_since(
+            amount_usdc_base_units,
+            self.authorization_timestamp,
+            self.timeframe_reset_timestamp,
+            self.window_reset_timestamps,
+            current_timestamp,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{
+        vault::tests::{test_vault, DAY, NOW},
+        CalendarPeriod, MerchantCategoryLimit, MerchantCategorySpend, MerchantControls,
+        MerchantListMode, SpendLimitBoost, SpendLimitMode, SpendLimitWindow,
+    };
+
+    const WEEK: u64 = 7 * DAY;
+
+    fn with_week_window(mut vault: Vault) -> Vault {
+        vault.spend_limit_windows[0] = SpendLimitWindow {
+            spend_limit_per_timeframe: 5_000,
+            remaining_spend_limit_per_timeframe: 5_000,
+            next_timeframe_reset_timestamp: NOW + WEEK,
+            timeframe_in_seconds: WEEK,
+        };
+        vault
+    }
+
+    /// Reserves the amount as authorize_spend does, without a boost
+    fn authorize(vault: &mut Vault, amount_usdc_base_units: u64) -> SpendHold {
+        vault.remaining_spend_limit_per_timeframe -= amount_usdc_base_units;
+        for window in vault
+            .spend_limit_windows
+            .iter_mut()
+            .filter(|window| window.is_active())
+        {
+            window.remaining_spend_limit_per_timeframe -= amount_usdc_base_units;
+        }
+        let longest_timeframe_in_seconds = vault.get_longest_timeframe_in_seconds();
+        vault
+            .recent_spends
+            .record_spend(amount_usdc_base_units, NOW, longest_timeframe_in_seconds)
+            .expect("records spend");
+
+        SpendHold {
+            vault: Pubkey::new_unique(),
+            bump: 255,
+            authorization_id: 1,
+            amount_usdc_base_units,
+            expiry_timestamp: NOW + DAY,
+            authorization_timestamp: NOW,
+            timeframe_reset_timestamp: vault.next_timeframe_reset_timestamp,
+            window_reset_timestamps: vault
+                .spend_limit_windows
+                .map(|window| window.next_timeframe_reset_timestamp),
+            boost_usdc_base_units: 0,
+            boost_expiry_timestamp: 0,
+            merchant_category_code: 5411,
+            rent_payer: Pubkey::new_unique(),
+        }
+    }
+
+    /// Merchant controls that have counted the hold against its category
+    fn merchant_controls_after(vault: &Vault, spend_hold: &SpendHold) -> VaultMerchantControls {
+        VaultMerchantControls {
+            vault: Pubkey::new_unique(),
+            bump: 255,
+            controls: MerchantControls {
+                category_limits: vec![MerchantCategoryLimit {
+                    merchant_category_code: spend_hold.merchant_category_code,
+                    spend_limit_per_timeframe: 500,
+                }],
+                merchant_list_mode: MerchantListMode::Blocklist,
+                merchant_id_hashes: vec![],
+            },
+            timeframe_reset_timestamp: vault.next_timeframe_reset_timestamp,
+            category_spend: vec![MerchantCategorySpend {
+                merchant_category_code: spend_hold.merchant_category_code,
+                spent_per_timeframe: spend_hold.amount_usdc_base_units,
+            }],
+        }
+    }
+
+    /// Resets the primary timeframe as process_spend_limits does once it has elapsed
+    fn reset_primary_timeframe(vault: &mut Vault, next_timeframe_reset_timestamp: u64) {
+        vault.next_timeframe_reset_timestamp = next_timeframe_reset_timestamp;
+        vault.remaining_spend_limit_per_timeframe = vault.spend_limit_per_timeframe;
+    }
+
+    #[test]
+    fn capture_draws_down_the_hold_until_the_final_capture() {
+        let mut vault = test_vault();
+        let mut spend_hold = authorize(&mut vault, 300);
+
+        assert!(!spend_hold.capture(100, false).expect("within hold"));
+        assert_eq!(spend_hold.amount_usdc_base_units, 200);
+
+        assert!(spend_hold.capture(50, true).expect("within hold"));
+        assert_eq!(spend_hold.amount_usdc_base_units, 150);
+    }
+
+    #[test]
+    fn capture_of_the_whole_hold_closes_it() {
+        let mut vault = test_vault();
+        let mut spend_hold = authorize(&mut vault, 300);
+
+        assert!(spend_hold.capture(300, false).expect("within hold"));
+        assert_eq!(spend_hold.amount_usdc_base_units, 0);
+    }
+
+    #[test]
+    fn capture_above_the_hold_is_rejected() {
+        let mut vault = test_vault();
+        let mut spend_hold = authorize(&mut vault, 300);
+
+        assert_eq!(
+            spend_hold.capture(301, false).expect_err("above hold"),
+            PyraError::InsufficientSpendHold.into()
+        );
+        assert_eq!(spend_hold.amount_usdc_base_units, 300);
+    }
+
+    #[test]
+    fn release_after_a_partial_capture_restores_the_uncaptured_amount() {
+        let mut vault = test_vault();
+        let mut spend_hold = authorize(&mut vault, 300);
+        spend_hold.capture(100, true).expect("within hold");
+
+        assert_eq!(spend_hold.release(&mut vault, None, NOW + 1), 200);
+        assert_eq!(spend_hold.amount_usdc_base_units, 0);
+        assert_eq!(vault.remaining_spend_limit_per_timeframe, 900);
+        assert_eq!(vault.recent_spends.get_spent_within(DAY, NOW + 1), 100);
+    }
+
+    #[test]
+    fn fixed_release_only_restores_the_period_it_was_reserved_from() {
+        let mut vault = test_vault();
+        let mut spend_hold = authorize(&mut vault, 300);
+
+        reset_primary_timeframe(&mut vault, NOW + 2 * DAY);
+        vault.remaining_spend_limit_per_timeframe -= 400;
+
+        assert_eq!(spend_hold.release(&mut vault, None, NOW + DAY + 1), 0);
+        assert_eq!(vault.remaining_spend_limit_per_timeframe, 600);
+    }
+
+    #[test]
+    fn calendar_release_only_restores_the_period_it_was_reserved_from() {
+        let mut vault = test_vault();
+        vault.spend_limit_mode = SpendLimitMode::Calendar;
+        vault.calendar_period = CalendarPeriod::Day;
+        let mut spend_hold = authorize(&mut vault, 300);
+
+        assert_eq!(spend_hold.release(&mut vault, None, NOW + 1), 300);
+        assert_eq!(vault.remaining_spend_limit_per_timeframe, 1_000);
+
+        let mut spend_hold = authorize(&mut vault, 300);
+        reset_primary_timeframe(&mut vault, NOW + 2 * DAY);
+
+        assert_eq!(spend_hold.release(&mut vault, None, NOW + DAY + 1), 0);
+        assert_eq!(vault.remaining_spend_limit_per_timeframe, 1_000);
+    }
+
+    #[test]
+    fn rolling_release_restores_the_reservation_while_within_the_timeframe() {
+        let mut vault = test_vault();
+        vault.spend_limit_mode = SpendLimitMode::Rolling;
+        let mut spend_hold = authorize(&mut vault, 300);
+
+        // Reset timestamps still roll over in rolling mode, which doesn't end the reservation
+        vault.next_timeframe_reset_timestamp = NOW + DAY / 2;
+
+        assert_eq!(spend_hold.release(&mut vault, None, NOW + DAY / 2), 300);
+        assert_eq!(vault.remaining_spend_limit_per_timeframe, 1_000);
+        assert_eq!(vault.recent_spends.get_spent_within(DAY, NOW + DAY / 2), 0);
+    }
+
+    #[test]
+    fn rolling_release_after_the_timeframe_leaves_newer_spends_in_place() {
+        let mut vault = test_vault();
+        vault.spend_limit_mode = SpendLimitMode::Rolling;
+        let mut spend_hold = authorize(&mut vault, 300);
+
+        vault
+            .recent_spends
+            .record_spend(200, NOW + DAY, DAY)
+            .expect("records spend");
+        vault.remaining_spend_limit_per_timeframe = 800;
+
+        assert_eq!(spend_hold.release(&mut vault, None, NOW + DAY), 0);
+        assert_eq!(vault.remaining_spend_limit_per_timeframe, 800);
+        assert_eq!(vault.recent_spends.get_spent_within(DAY, NOW + DAY), 200);
+    }
+
+    #[test]
+    fn window_release_only_restores_windows_still_in_the_reserved_period() {
+        let mut vault = with_week_window(test_vault());
+        let mut spend_hold = authorize(&mut vault, 300);
+
+        // The primary day resets, while the week window is still in the period the hold was reserved from
+        reset_primary_timeframe(&mut vault, NOW + 2 * DAY);
+
+        assert_eq!(spend_hold.release(&mut vault, None, NOW + DAY + 1), 0);
+        assert_eq!(vault.remaining_spend_limit_per_timeframe, 1_000);
+        assert_eq!(
+            vault.spend_limit_windows[0].remaining_spend_limit_per_timeframe,
+            5_000
+        );
+    }
+
+    #[test]
+    fn rolling_window_release_only_restores_windows_the_reservation_is_within() {
+        let mut vault = with_week_window(test_vault());
+        vault.spend_limit_mode = SpendLimitMode::Rolling;
+        let mut spend_hold = authorize(&mut vault, 300);
+
+        assert_eq!(spend_hold.release(&mut vault, None, NOW + DAY), 0);
+        assert_eq!(vault.remaining_spend_limit_per_timeframe, 700);
+        assert_eq!(
+            vault.spend_limit_windows[0].remaining_spend_limit_per_timeframe,
+            5_000
+        );
+        assert_eq!(vault.recent_spends.get_spent_within(WEEK, NOW + DAY), 0);
+    }
+
+    #[test]
+    fn release_returns_the_boost_first_while_the_boost_is_unchanged() {
+        let mut vault = test_vault();
+        vault.spend_limit_boost = SpendLimitBoost {
+            additional_spend_limit_per_transaction: 0,
+            additional_spend_limit: 200,
+            remaining_additional_spend_limit: 0,
+            expiry_timestamp: NOW + DAY,
+        };
+        let mut spend_hold = authorize(&mut vault, 300);
+        spend_hold.boost_usdc_base_units = 200;
+        spend_hold.boost_expiry_timestamp = NOW + DAY;
+
+        assert_eq!(spend_hold.release(&mut vault, None, NOW + 1), 100);
+        assert_eq!(
+            vault.spend_limit_boost.remaining_additional_spend_limit,
+            200
+        );
+        assert_eq!(vault.remaining_spend_limit_per_timeframe, 800);
+    }
+
+    #[test]
+    fn release_does_not_top_up_a_replaced_boost() {
+        let mut vault = test_vault();
+        let mut spend_hold = authorize(&mut vault, 300);
+        spend_hold.boost_usdc_base_units = 200;
+        spend_hold.boost_expiry_timestamp = NOW + DAY;
+        vault.spend_limit_boost.expiry_timestamp = NOW + 2 * DAY;
+
+        assert_eq!(spend_hold.release(&mut vault, None, NOW + 1), 100);
+        assert_eq!(vault.spend_limit_boost.remaining_additional_spend_limit, 0);
+    }
+
+    #[test]
+    fn release_returns_the_uncaptured_amount_to_its_merchant_category() {
+        let mut vault = test_vault();
+        let mut spend_hold = authorize(&mut vault, 300);
+        let mut vault_merchant_controls = merchant_controls_after(&vault, &spend_hold);
+        spend_hold.capture(100, true).expect("within hold");
+
+        spend_hold.release(&mut vault, Some(&mut vault_merchant_controls), NOW + 1);
+        assert_eq!(
+            vault_merchant_controls.category_spend[0].spent_per_timeframe,
+            100
+        );
+    }
+
+    #[test]
+    fn release_after_the_category_spend_resets_leaves_it_in_place() {
+        let mut vault = test_vault();
+        let mut spend_hold = authorize(&mut vault, 300);
+        let mut vault_merchant_controls = merchant_controls_after(&vault, &spend_hold);
+
+        // The category spend resets with the vault on the next spend in the new period
+        reset_primary_timeframe(&mut vault, NOW + 2 * DAY);
+        vault_merchant_controls.timeframe_reset_timestamp = NOW + 2 * DAY;
+        vault_merchant_controls.category_spend[0].spent_per_timeframe = 200;
+
+        spend_hold.release(
+            &mut vault,
+            Some(&mut vault_merchant_controls),
+            NOW + DAY + 1,
+        );
+        assert_eq!(
+            vault_merchant_controls.category_spend[0].spent_per_timeframe,
+            200
+        );
+    }
+}
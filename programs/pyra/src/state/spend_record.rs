@@ -0,0 +1,151 @@
+use crate::config::{
+    ANCHOR_DISCRIMINATOR, MAX_SPEND_LIMIT_WINDOWS, PUBKEY_SIZE, U64_SIZE, U8_SIZE,
+};
+use anchor_lang::prelude::*;
+
+/// Spend bridged by complete_spend, kept so refunds of it can restore the spend limits it used
+#[account]
+pub struct SpendRecord {
+    pub vault: Pubkey,
+    pub bump: u8,
+
+    // CCTP nonce of the spend's bridge, part of the PDA seeds
+    pub cctp_nonce: u64,
+
+    // Amount not yet refunded, which caps the spend limit later refunds can restore
+    pub amount_usdc_base_units: u64,
+
+    pub spend_timestamp: u64,
+
+    // Reset timestamps of the primary timeframe and each window when spent. Refunds only restore the limit of the
+    // timeframes still in the period it was spent in.
+    pub timeframe_reset_timestamp: u64,
+    pub window_reset_timestamps: [u64; MAX_SPEND_LIMIT_WINDOWS],
+
+    // Spend caller that paid the rent, and receives it when the record is closed
+    pub rent_payer: Pubkey,
+}
+
+impl Space for SpendRecord {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR
+        + PUBKEY_SIZE
+        + U8_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + (U64_SIZE * MAX_SPEND_LIMIT_WINDOWS)
+        + PUBKEY_SIZE;
+}
+
+impl SpendRecord {
+    /// Draws a refund from the unrefunded amount, returning the part of it that was spent
+    pub fn refund(&mut self, amount_usdc_base_units: u64) -> u64 {
+        let refunded_usdc_base_units = amount_usdc_base_units.min(self.amount_usdc_base_units);
+        self.amount_usdc_base_units -= refunded_usdc_base_units;
+        refunded_usdc_base_units
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{
+        vault::tests::{test_vault, DAY, NOW},
+        Vault,
+    };
+
+    /// Spends the amount as start_spend and complete_spend do
+    fn spend(
+        vault: &mut Vault,
+        amount_usdc_base_units: u64,
+        current_timestamp: u64,
+    ) -> SpendRecord {
+        vault.remaining_spend_limit_per_timeframe -= amount_usdc_base_units;
+        let longest_timeframe_in_seconds = vault.get_longest_timeframe_in_seconds();
+        vault
+            .recent_spends
+            .record_spend(
+                amount_usdc_base_units,
+                current_timestamp,
+                longest_timeframe_in_seconds,
+            )
+            .expect("records spend");
+
+        SpendRecord {
+            vault: Pubkey::new_unique(),
+            bump: 255,
+            cctp_nonce: 1,
+            amount_usdc_base_units,
+            spend_timestamp: current_timestamp,
+            timeframe_reset_timestamp: vault.next_timeframe_reset_timestamp,
+            window_reset_timestamps: vault
+                .spend_limit_windows
+                .map(|window| window.next_timeframe_reset_timestamp),
+            rent_payer: Pubkey::new_unique(),
+        }
+    }
+
+    fn refund(
+        vault: &mut Vault,
+        spend_record: &mut SpendRecord,
+        amount_usdc_base_units: u64,
+        current_timestamp: u64,
+    ) -> u64 {
+        let refunded_usdc_base_units = spend_record.refund(amount_usdc_base_units);
+        vault.restore_spend_limit_since(
+            refunded_usdc_base_units,
+            spend_record.spend_timestamp,
+            spend_record.timeframe_reset_timestamp,
+            spend_record.window_reset_timestamps,
+            current_timestamp,
+        )
+    }
+
+    #[test]
+    fn refunds_are_capped_at_what_is_left_of_the_spend() {
+        let mut vault = test_vault();
+        let mut spend_record = spend(&mut vault, 300, NOW);
+
+        assert_eq!(refund(&mut vault, &mut spend_record, 200, NOW + 1), 200);
+        assert_eq!(refund(&mut vault, &mut spend_record, 200, NOW + 1), 100);
+        assert_eq!(spend_record.amount_usdc_base_units, 0);
+        assert_eq!(vault.remaining_spend_limit_per_timeframe, 1_000);
+    }
+
+    #[test]
+    fn refund_removes_the_refunded_spend_rather_than_the_newest() {
+        let mut vault = test_vault();
+        let mut spend_record = spend(&mut vault, 300, NOW);
+        spend(&mut vault, 200, NOW + 1);
+
+        assert_eq!(refund(&mut vault, &mut spend_record, 300, NOW + 2), 300);
+        assert_eq!(vault.recent_spends.spends[0].amount_usdc_base_units, 0);
+        assert_eq!(vault.recent_spends.spends[1].amount_usdc_base_units, 200);
+    }
+
+    #[test]
+    fn refund_after_the_period_resets_does_not_restore_the_limit() {
+        let mut vault = test_vault();
+        let mut spend_record = spend(&mut vault, 300, NOW);
+        assert!(vault.has_current_spend_period(
+            spend_record.spend_timestamp,
+            spend_record.timeframe_reset_timestamp,
+            spend_record.window_reset_timestamps,
+            NOW + 1,
+        ));
+
+        // Reset as process_spend_limits does once the timeframe has elapsed
+        vault.next_timeframe_reset_timestamp = NOW + 2 * DAY;
+        vault.remaining_spend_limit_per_timeframe = vault.spend_limit_per_timeframe - 400;
+
+        assert!(!vault.has_current_spend_period(
+            spend_record.spend_timestamp,
+            spend_record.timeframe_reset_timestamp,
+            spend_record.window_reset_timestamps,
+            NOW + DAY + 1,
+        ));
+        assert_eq!(refund(&mut vault, &mut spend_record, 300, NOW + DAY + 1), 0);
+        assert_eq!(vault.remaining_spend_limit_per_timeframe, 600);
+    }
+}
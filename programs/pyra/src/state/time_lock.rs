@@ -1,4 +1,4 @@
-use crate::config::{PUBKEY_SIZE, U1_SIZE, U64_SIZE};
+use crate::config::{PUBKEY_SIZE, U1_SIZE, U32_SIZE, U64_SIZE};
 use anchor_lang::prelude::*;
 
 mod spend_limits_order;
@@ -7,16 +7,44 @@ pub use spend_limits_order::*;
 mod withdraw_order;
 pub use withdraw_order::*;
 
+mod admin_proposal;
+pub use admin_proposal::*;
+
+mod owner_transfer_order;
+pub use owner_transfer_order::*;
+
+mod collateral_transfer_order;
+pub use collateral_transfer_order::*;
+
+mod spend_sub_account_order;
+pub use spend_sub_account_order::*;
+
+mod unfreeze_card_order;
+pub use unfreeze_card_order::*;
+
+mod recovery_order;
+pub use recovery_order::*;
+
+mod settle_and_close_order;
+pub use settle_and_close_order::*;
+
+mod merchant_controls_order;
+pub use merchant_controls_order::*;
+
 /// Time lock used to prevent an order being executed before the release_slot
 #[derive(Clone, AnchorSerialize, AnchorDeserialize)]
 pub struct TimeLock {
     pub owner: Pubkey,
     pub is_owner_payer: bool,
     pub release_slot: u64,
+
+    // Vault's owner_epoch when the order was created, so it can't be fulfilled if the owner regains the vault later.
+    // Always 0 for admin proposals, which are invalidated by the admin changing.
+    pub owner_epoch: u32,
 }
 
 impl Space for TimeLock {
-    const INIT_SPACE: usize = PUBKEY_SIZE + U1_SIZE + U64_SIZE;
+    const INIT_SPACE: usize = PUBKEY_SIZE + U1_SIZE + U64_SIZE + U32_SIZE;
 }
 
 pub trait TimeLocked {
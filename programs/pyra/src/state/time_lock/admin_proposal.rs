@@ -0,0 +1,69 @@
+use crate::config::{ANCHOR_DISCRIMINATOR, U8_SIZE};
+use crate::state::time_lock::*;
+use crate::state::GlobalConfigParams;
+
+/// Protocol-level change that can only be executed through a time locked AdminProposal
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub enum AdminAction {
+    UpdateGlobalConfig(GlobalConfigParams),
+    SetRoles {
+        admin: Pubkey,
+        guardian: Pubkey,
+        pauser: Pubkey,
+    },
+    AddDriftMarket {
+        market_index: u16,
+        mint: Pubkey,
+        pyth_feed: [u8; 32],
+        base_units_per_token: u64,
+    },
+    UpdateDriftMarket {
+        market_index: u16,
+        pyth_feed: [u8; 32],
+    },
+    SetDriftMarketDisabled {
+        market_index: u16,
+        is_disabled: bool,
+    },
+    DelistDriftMarket {
+        market_index: u16,
+    },
+    AddSettlementProvider {
+        provider_id: u16,
+        destination_domain: u32,
+        mint_recipient: Pubkey,
+        destination_caller: Pubkey,
+    },
+    UpdateSettlementProvider {
+        provider_id: u16,
+        destination_domain: u32,
+        mint_recipient: Pubkey,
+        destination_caller: Pubkey,
+    },
+    SetSettlementProviderDisabled {
+        provider_id: u16,
+        is_disabled: bool,
+    },
+}
+
+impl Space for AdminAction {
+    // Enum tag + largest variant
+    const INIT_SPACE: usize = U8_SIZE + GlobalConfigParams::INIT_SPACE;
+}
+
+/// Time locked proposal for changing the global config, Drift market registry or settlement provider registry, gives integrators advance notice of changes
+#[account]
+pub struct AdminProposal {
+    pub time_lock: TimeLock,
+    pub action: AdminAction,
+}
+
+impl Space for AdminProposal {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR + TimeLock::INIT_SPACE + AdminAction::INIT_SPACE;
+}
+
+impl TimeLocked for AdminProposal {
+    fn time_lock(&self) -> &TimeLock {
+        &self.time_lock
+    }
+}
@@ -0,0 +1,29 @@
+use crate::config::{ANCHOR_DISCRIMINATOR, PUBKEY_SIZE, U16_SIZE, U64_SIZE};
+use crate::state::time_lock::*;
+
+/// Time locked order for moving collateral out of a vault's spend sub-account into another Drift sub-account
+#[account]
+pub struct CollateralTransferOrder {
+    pub time_lock: TimeLock,
+    pub vault: Pubkey,
+    pub amount_base_units: u64,
+    pub drift_market_index: u16,
+    pub from_sub_account_id: u16,
+    pub to_sub_account_id: u16,
+}
+
+impl Space for CollateralTransferOrder {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR
+        + TimeLock::INIT_SPACE
+        + PUBKEY_SIZE
+        + U64_SIZE
+        + U16_SIZE
+        + U16_SIZE
+        + U16_SIZE;
+}
+
+impl TimeLocked for CollateralTransferOrder {
+    fn time_lock(&self) -> &TimeLock {
+        &self.time_lock
+    }
+}
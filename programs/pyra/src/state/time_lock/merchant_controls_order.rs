@@ -0,0 +1,21 @@
+use crate::config::{ANCHOR_DISCRIMINATOR, PUBKEY_SIZE};
+use crate::state::{time_lock::*, MerchantControls};
+
+/// Time locked order for loosening the merchant controls of a vault
+#[account]
+pub struct MerchantControlsOrder {
+    pub time_lock: TimeLock,
+    pub vault: Pubkey,
+    pub merchant_controls: MerchantControls,
+}
+
+impl Space for MerchantControlsOrder {
+    const INIT_SPACE: usize =
+        ANCHOR_DISCRIMINATOR + TimeLock::INIT_SPACE + PUBKEY_SIZE + MerchantControls::INIT_SPACE;
+}
+
+impl TimeLocked for MerchantControlsOrder {
+    fn time_lock(&self) -> &TimeLock {
+        &self.time_lock
+    }
+}
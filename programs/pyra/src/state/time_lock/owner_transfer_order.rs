@@ -0,0 +1,21 @@
+use crate::config::{ANCHOR_DISCRIMINATOR, PUBKEY_SIZE};
+use crate::state::time_lock::*;
+
+/// Time locked order for transferring a vault to a new owner, who must accept it once the time lock has expired
+#[account]
+pub struct OwnerTransferOrder {
+    pub time_lock: TimeLock,
+    pub vault: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+impl Space for OwnerTransferOrder {
+    const INIT_SPACE: usize =
+        ANCHOR_DISCRIMINATOR + TimeLock::INIT_SPACE + PUBKEY_SIZE + PUBKEY_SIZE;
+}
+
+impl TimeLocked for OwnerTransferOrder {
+    fn time_lock(&self) -> &TimeLock {
+        &self.time_lock
+    }
+}
@@ -0,0 +1,21 @@
+use crate::config::{ANCHOR_DISCRIMINATOR, PUBKEY_SIZE};
+use crate::state::time_lock::*;
+
+/// Time locked order, started by the vault's guardians, for recovering a vault to a new owner. One per vault, at a PDA seeded by the vault. The current owner can veto it until the time lock expires.
+#[account]
+pub struct RecoveryOrder {
+    pub time_lock: TimeLock,
+    pub vault: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+impl Space for RecoveryOrder {
+    const INIT_SPACE: usize =
+        ANCHOR_DISCRIMINATOR + TimeLock::INIT_SPACE + PUBKEY_SIZE + PUBKEY_SIZE;
+}
+
+impl TimeLocked for RecoveryOrder {
+    fn time_lock(&self) -> &TimeLock {
+        &self.time_lock
+    }
+}
@@ -0,0 +1,19 @@
+use crate::config::{ANCHOR_DISCRIMINATOR, PUBKEY_SIZE};
+use crate::state::time_lock::*;
+
+/// Time locked order for settling a vault's loans and withdrawing its collateral to the owner, before closing it
+#[account]
+pub struct SettleAndCloseOrder {
+    pub time_lock: TimeLock,
+    pub vault: Pubkey,
+}
+
+impl Space for SettleAndCloseOrder {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR + TimeLock::INIT_SPACE + PUBKEY_SIZE;
+}
+
+impl TimeLocked for SettleAndCloseOrder {
+    fn time_lock(&self) -> &TimeLock {
+        &self.time_lock
+    }
+}
@@ -1,19 +1,45 @@
-use crate::config::{ANCHOR_DISCRIMINATOR, U64_SIZE};
-use crate::state::time_lock::*;
+use crate::config::{
+    ANCHOR_DISCRIMINATOR, MAX_SPEND_LIMIT_WINDOWS, PUBKEY_SIZE, U1_SIZE, U32_SIZE, U64_SIZE,
+    U8_SIZE, VEC_LENGTH_PREFIX_SIZE,
+};
+use crate::state::{
+    time_lock::*, CalendarPeriod, SpendLimitMode, SpendLimitWindowParams, SpendVelocityLimits,
+};
 
 /// Time locked order for updating the spend limits of a vault
 #[account]
 pub struct SpendLimitsOrder {
     pub time_lock: TimeLock,
+    pub vault: Pubkey,
     pub spend_limit_per_transaction: u64,
     pub spend_limit_per_timeframe: u64,
     pub timeframe_in_seconds: u64,
     pub next_timeframe_reset_timestamp: u64,
+    pub spend_limit_windows: Vec<SpendLimitWindowParams>,
+    pub spend_limit_mode: SpendLimitMode,
+    pub utc_offset_seconds: i32,
+    pub spend_velocity_limits: SpendVelocityLimits,
+    pub calendar_period: CalendarPeriod,
+
+    // Delegate orders keep the vault's timing when they're fulfilled, see Vault::keep_spend_limit_timing
+    pub is_delegate_order: bool,
 }
 
 impl Space for SpendLimitsOrder {
-    const INIT_SPACE: usize =
-        ANCHOR_DISCRIMINATOR + TimeLock::INIT_SPACE + U64_SIZE + U64_SIZE + U64_SIZE + U64_SIZE;
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR
+        + TimeLock::INIT_SPACE
+        + PUBKEY_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + VEC_LENGTH_PREFIX_SIZE
+        + (SpendLimitWindowParams::INIT_SPACE * MAX_SPEND_LIMIT_WINDOWS)
+        + U8_SIZE
+        + U32_SIZE
+        + SpendVelocityLimits::INIT_SPACE
+        + CalendarPeriod::INIT_SPACE
+        + U1_SIZE;
 }
 
 impl TimeLocked for SpendLimitsOrder {
@@ -0,0 +1,20 @@
+use crate::config::{ANCHOR_DISCRIMINATOR, PUBKEY_SIZE, U16_SIZE};
+use crate::state::time_lock::*;
+
+/// Time locked order for changing which Drift sub-account funds a vault's card spends
+#[account]
+pub struct SpendSubAccountOrder {
+    pub time_lock: TimeLock,
+    pub vault: Pubkey,
+    pub spend_sub_account_id: u16,
+}
+
+impl Space for SpendSubAccountOrder {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR + TimeLock::INIT_SPACE + PUBKEY_SIZE + U16_SIZE;
+}
+
+impl TimeLocked for SpendSubAccountOrder {
+    fn time_lock(&self) -> &TimeLock {
+        &self.time_lock
+    }
+}
@@ -0,0 +1,19 @@
+use crate::config::{ANCHOR_DISCRIMINATOR, PUBKEY_SIZE};
+use crate::state::time_lock::*;
+
+/// Time locked order for unfreezing a vault's card, so a compromised owner key can't instantly re-enable spends
+#[account]
+pub struct UnfreezeCardOrder {
+    pub time_lock: TimeLock,
+    pub vault: Pubkey,
+}
+
+impl Space for UnfreezeCardOrder {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR + TimeLock::INIT_SPACE + PUBKEY_SIZE;
+}
+
+impl TimeLocked for UnfreezeCardOrder {
+    fn time_lock(&self) -> &TimeLock {
+        &self.time_lock
+    }
+}
@@ -5,15 +5,23 @@ use crate::state::time_lock::*;
 #[account]
 pub struct WithdrawOrder {
     pub time_lock: TimeLock,
+    pub vault: Pubkey,
     pub amount_base_units: u64,
     pub drift_market_index: u16,
     pub reduce_only: bool,
     pub destination: Pubkey,
+    pub drift_sub_account_id: u16,
 }
 
 impl Space for WithdrawOrder {
-    const INIT_SPACE: usize =
-        ANCHOR_DISCRIMINATOR + TimeLock::INIT_SPACE + U64_SIZE + U16_SIZE + U1_SIZE + PUBKEY_SIZE;
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR
+        + TimeLock::INIT_SPACE
+        + PUBKEY_SIZE
+        + U64_SIZE
+        + U16_SIZE
+        + U1_SIZE
+        + PUBKEY_SIZE
+        + U16_SIZE;
 }
 
 impl TimeLocked for WithdrawOrder {
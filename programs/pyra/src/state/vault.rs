@@ -1,10 +1,21 @@
-use crate::config::{ANCHOR_DISCRIMINATOR, PUBKEY_SIZE, U64_SIZE, U8_SIZE};
+use crate::{
+    check,
+    config::{
+        PyraError, ANCHOR_DISCRIMINATOR, MAX_DEPOSIT_SWEEP_AGE_SLOTS, MAX_RECENT_SPENDS,
+        MAX_SPEND_LIMIT_WINDOWS, PUBKEY_SIZE, SECONDS_PER_DAY, SECONDS_PER_LONGEST_CALENDAR_MONTH,
+        SECONDS_PER_WEEK, U16_SIZE, U1_SIZE, U32_SIZE, U64_SIZE, U8_SIZE, VAULT_RESERVED_SPACE,
+    },
+    state::{SpendLimitsOrder, TimeLock},
+};
 use anchor_lang::prelude::*;
 
+mod migration;
+pub use migration::*;
+
 /// Main user account for the Pyra protocol. Is the authority for DeFi integration accounts, and handles spend limits for the card.
 #[account]
 pub struct Vault {
-    // Note: If the owner becomes changeable in the future, need to add has_one contstraints to all ixs
+    // Changeable through a time locked owner transfer, so all ixs must have has_one = owner constraints
     pub owner: Pubkey,
     pub bump: u8,
 
@@ -17,6 +28,77 @@ pub struct Vault {
 
     // How much to extend the next_timeframe_reset_timestamp by when it's reached
     pub timeframe_in_seconds: u64,
+
+    // Layout version, see migrate_vault for the migration from the unversioned layouts
+    pub version: u8,
+
+    // Stable PDA seed chosen at creation, so the vault address survives owner transfers. Vaults created before ids
+    // were chosen use their first owner's key.
+    pub vault_id: Pubkey,
+
+    // Drift sub-account that funds card spends and receives deposits
+    pub spend_sub_account_id: u16,
+
+    // Number of open Drift sub-accounts, which must be closed down to the spend sub-account before closing the vault
+    pub drift_sub_account_count: u16,
+
+    // Set instantly by the owner to block spends, only cleared through a time locked unfreeze order
+    pub is_card_frozen: bool,
+
+    // Open time locked orders from the current owner_epoch, which must all be closed before the vault can be closed
+    pub open_order_count: u16,
+
+    // Paid the init fee on the owner's behalf and is refunded it on close, Pubkey::default() if the owner paid
+    pub sponsor: Pubkey,
+
+    // Earns a share of the vault's spend fees, Pubkey::default() if the vault wasn't referred
+    pub referrer: Pubkey,
+
+    // Settlement provider in the SettlementProviderRegistry that complete_spend bridges to
+    pub settlement_provider_id: u16,
+
+    // Additional windows enforced alongside the primary timeframe, unused while timeframe_in_seconds is 0
+    pub spend_limit_windows: [SpendLimitWindow; MAX_SPEND_LIMIT_WINDOWS],
+
+    // How the remaining limit of every timeframe is calculated, see SpendLimitMode
+    pub spend_limit_mode: SpendLimitMode,
+
+    // Recorded in both modes, so switching to rolling limits accounts for spends already made
+    pub recent_spends: RecentSpends,
+
+    // Offset from UTC of the calendar that SpendLimitMode::Calendar timeframes reset in
+    pub utc_offset_seconds: i32,
+
+    // Limits on how often the card can spend, alongside the amount limits
+    pub spend_velocity_limits: SpendVelocityLimits,
+
+    // Spends since the primary timeframe last reset, checked against max_spends_per_timeframe in every mode
+    pub spend_count_per_timeframe: u16,
+
+    // Temporary raise of the spend limits, ignored once expired so the vault falls back to its base limits
+    pub spend_limit_boost: SpendLimitBoost,
+
+    // Calendar periods of the primary timeframe and of each additional window, unused outside SpendLimitMode::Calendar
+    pub calendar_period: CalendarPeriod,
+    pub spend_limit_window_calendar_periods: [CalendarPeriod; MAX_SPEND_LIMIT_WINDOWS],
+
+    // Incremented on every owner change, so orders from a previous owner can't be fulfilled if they regain the vault
+    pub owner_epoch: u32,
+
+    // Set for vaults migrated from the unversioned layouts, whose open orders weren't counted, until the owner
+    // attests none remain
+    pub may_have_uncounted_orders: bool,
+
+    // Positions in DriftMarketRegistry::get_deposit_mints of the deposit address ATAs swept by sweep_deposit_address
+    // since the deposit_sweep_slot, which close_user doesn't need again while the sweep is recent
+    pub swept_deposit_mint_flags: u64,
+    pub deposit_sweep_slot: u64,
+
+    // Spend holds that haven't been captured or released, which must all be closed before the vault can be closed
+    pub open_spend_hold_count: u16,
+
+    // New fields should be taken from the reserved space, so future versions don't need a realloc
+    pub reserved: [u8; VAULT_RESERVED_SPACE],
 }
 
 impl Space for Vault {
@@ -27,5 +109,1028 @@ impl Space for Vault {
         + U64_SIZE
         + U64_SIZE
         + U64_SIZE
-        + U64_SIZE;
+        + U64_SIZE
+        + U8_SIZE
+        + PUBKEY_SIZE
+        + U16_SIZE
+        + U16_SIZE
+        + U1_SIZE
+        + U16_SIZE
+        + PUBKEY_SIZE
+        + PUBKEY_SIZE
+        + U16_SIZE
+        + (SpendLimitWindow::INIT_SPACE * MAX_SPEND_LIMIT_WINDOWS)
+        + U8_SIZE
+        + RecentSpends::INIT_SPACE
+        + U32_SIZE
+        + SpendVelocityLimits::INIT_SPACE
+        + U16_SIZE
+        + SpendLimitBoost::INIT_SPACE
+        + CalendarPeriod::INIT_SPACE
+        + (CalendarPeriod::INIT_SPACE * MAX_SPEND_LIMIT_WINDOWS)
+        + U32_SIZE
+        + U1_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U16_SIZE
+        + VAULT_RESERVED_SPACE;
+}
+
+/// Spend limit that resets every timeframe, enforced alongside the vault's primary timeframe
+#[derive(Clone, Copy, Default, AnchorSerialize, AnchorDeserialize)]
+pub struct SpendLimitWindow {
+    pub spend_limit_per_timeframe: u64,
+    pub remaining_spend_limit_per_timeframe: u64,
+    pub next_timeframe_reset_timestamp: u64,
+    pub timeframe_in_seconds: u64,
+}
+
+impl Space for SpendLimitWindow {
+    const INIT_SPACE: usize = U64_SIZE + U64_SIZE + U64_SIZE + U64_SIZE;
+}
+
+impl SpendLimitWindow {
+    pub fn is_active(&self) -> bool {
+        self.timeframe_in_seconds > 0
+    }
+
+    pub fn already_used(&self) -> u64 {
+        self.spend_limit_per_timeframe
+            .saturating_sub(self.remaining_spend_limit_per_timeframe)
+    }
+}
+
+/// Requested spend limit window, the remaining limit is derived from what the vault has already spent
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct SpendLimitWindowParams {
+    pub spend_limit_per_timeframe: u64,
+    pub timeframe_in_seconds: u64,
+    pub next_timeframe_reset_timestamp: u64,
+
+    // Calendar period the window resets at the start of, ignored outside SpendLimitMode::Calendar
+    pub calendar_period: CalendarPeriod,
+}
+
+impl Space for SpendLimitWindowParams {
+    const INIT_SPACE: usize = U64_SIZE + U64_SIZE + U64_SIZE + CalendarPeriod::INIT_SPACE;
+}
+
+/// How the remaining limit of a timeframe is calculated
+#[derive(Clone, Copy, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum SpendLimitMode {
+    // Remaining limit resets to the full limit at each next_timeframe_reset_timestamp
+    Fixed,
+    // Remaining limit is the limit minus the recent spends within the trailing timeframe_in_seconds
+    Rolling,
+    // Remaining limit resets to the full limit at the start of each timeframe's CalendarPeriod in the vault's
+    // utc_offset_seconds
+    Calendar,
+}
+
+/// Calendar period that a SpendLimitMode::Calendar timeframe resets at the start of
+#[derive(Clone, Copy, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum CalendarPeriod {
+    Day,
+    // Starting on Monday
+    Week,
+    Month,
+}
+
+impl Space for CalendarPeriod {
+    const INIT_SPACE: usize = U8_SIZE;
+}
+
+impl CalendarPeriod {
+    /// Timeframe of a calendar timeframe, which is the longest the period can last so spends are kept for all of it
+    pub fn get_timeframe_in_seconds(&self) -> u64 {
+        match self {
+            CalendarPeriod::Day => SECONDS_PER_DAY,
+            CalendarPeriod::Week => SECONDS_PER_WEEK,
+            CalendarPeriod::Month => SECONDS_PER_LONGEST_CALENDAR_MONTH,
+        }
+    }
+}
+
+/// Requested spend limit mode, with the UTC offset and primary calendar period calendar spend limits reset in
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub struct SpendLimitModeParams {
+    pub spend_limit_mode: SpendLimitMode,
+    pub utc_offset_seconds: i32,
+    pub calendar_period: CalendarPeriod,
+}
+
+/// Limits on the number and frequency of spends, 0 disables a limit
+#[derive(Clone, Copy, Default, AnchorSerialize, AnchorDeserialize)]
+pub struct SpendVelocityLimits {
+    pub max_spends_per_timeframe: u16,
+    pub min_seconds_between_spends: u32,
+}
+
+impl Space for SpendVelocityLimits {
+    const INIT_SPACE: usize = U16_SIZE + U32_SIZE;
+}
+
+impl SpendVelocityLimits {
+    /// True if every spend allowed by the current limits is also allowed by these limits
+    pub fn is_loosening(&self, current: &SpendVelocityLimits) -> bool {
+        let max_spends_loosened = self.max_spends_per_timeframe == 0
+            || (current.max_spends_per_timeframe != 0
+                && self.max_spends_per_timeframe >= current.max_spends_per_timeframe);
+
+        max_spends_loosened && self.min_seconds_between_spends <= current.min_seconds_between_spends
+    }
+}
+
+/// Limits added on top of the vault's spend limits until the expiry timestamp
+#[derive(Clone, Copy, Default, AnchorSerialize, AnchorDeserialize)]
+pub struct SpendLimitBoost {
+    pub additional_spend_limit_per_transaction: u64,
+    // Total that can be drawn until the expiry, including what was drawn before the boost was last replaced
+    pub additional_spend_limit: u64,
+    // Drawn down by the part of each spend that exceeds the remaining limit of any timeframe
+    pub remaining_additional_spend_limit: u64,
+    pub expiry_timestamp: u64,
+}
+
+impl Space for SpendLimitBoost {
+    const INIT_SPACE: usize = U64_SIZE + U64_SIZE + U64_SIZE + U64_SIZE;
+}
+
+impl SpendLimitBoost {
+    pub fn is_active(&self, current_timestamp: u64) -> bool {
+        current_timestamp < self.expiry_timestamp
+    }
+
+    pub fn already_used(&self) -> u64 {
+        self.additional_spend_limit
+            .saturating_sub(self.remaining_additional_spend_limit)
+    }
+
+    /// Boost replacing this one. What's already been drawn from this boost is kept while it's active, so replacing a
+    /// boost can raise its total but can't refill it.
+    pub fn get_replacement(
+        &self,
+        additional_spend_limit_per_transaction: u64,
+        additional_spend_limit: u64,
+        expiry_timestamp: u64,
+        current_timestamp: u64,
+    ) -> SpendLimitBoost {
+        let already_used = if self.is_active(current_timestamp) {
+            self.already_used()
+        } else {
+            0
+        };
+
+        SpendLimitBoost {
+            additional_spend_limit_per_transaction,
+            additional_spend_limit,
+            remaining_additional_spend_limit: additional_spend_limit.saturating_sub(already_used),
+            expiry_timestamp,
+        }
+    }
+
+    /// True if the boost allows at least as much as the current boost, for as long
+    pub fn is_loosening(&self, current: &SpendLimitBoost, current_timestamp: u64) -> bool {
+        !current.is_active(current_timestamp)
+            || (self.additional_spend_limit_per_transaction
+                >= current.additional_spend_limit_per_transaction
+                && self.additional_spend_limit >= current.additional_spend_limit
+                && self.expiry_timestamp >= current.expiry_timestamp)
+    }
+}
+
+/// Spend recorded for rolling spend limits
+#[derive(Clone, Copy, Default, AnchorSerialize, AnchorDeserialize)]
+pub struct RecentSpend {
+    // Unix timestamp, u32 keeps the ring buffer compact
+    pub timestamp: u32,
+    pub amount_usdc_base_units: u64,
+}
+
+impl Space for RecentSpend {
+    const INIT_SPACE: usize = U32_SIZE + U64_SIZE;
+}
+
+/// Ring buffer of the vault's most recent spends
+#[derive(Clone, Copy, Default, AnchorSerialize, AnchorDeserialize)]
+pub struct RecentSpends {
+    pub spends: [RecentSpend; MAX_RECENT_SPENDS],
+    // Slot the next spend is written to, which holds the oldest spend once the buffer is full
+    pub next_index: u8,
+}
+
+impl Space for RecentSpends {
+    const INIT_SPACE: usize = (RecentSpend::INIT_SPACE * MAX_RECENT_SPENDS) + U8_SIZE;
+}
+
+impl RecentSpends {
+    /// Total spent within the trailing timeframe, up to and including the current timestamp
+    pub fn get_spent_within(&self, timeframe_in_seconds: u64, current_timestamp: u64) -> u64 {
+        self.spends
+            .iter()
+            .filter(|spend| {
+                u64::from(spend.timestamp).saturating_add(timeframe_in_seconds) > current_timestamp
+            })
+            .fold(0, |total, spend| {
+                total.saturating_add(spend.amount_usdc_base_units)
+            })
+    }
+
+    /// Records a spend, overwriting the oldest. If the oldest is still within the longest timeframe, it's folded
+    /// into the next oldest so it's over-counted rather than forgotten.
+    pub fn record_spend(
+        &mut self,
+        amount_usdc_base_units: u64,
+        current_timestamp: u64,
+        longest_timeframe_in_seconds: u64,
+    ) -> Result<()> {
+        let index = usize::from(self.next_index) % MAX_RECENT_SPENDS;
+        let next_index = (index + 1) % MAX_RECENT_SPENDS;
+
+        let oldest_spend = self.spends[index];
+        if u64::from(oldest_spend.timestamp).saturating_add(longest_timeframe_in_seconds)
+            > current_timestamp
+        {
+            let next_oldest_spend = &mut self.spends[next_index];
+            next_oldest_spend.amount_usdc_base_units = next_oldest_spend
+                .amount_usdc_base_units
+                .checked_add(oldest_spend.amount_usdc_base_units)
+                .ok_or(PyraError::MathOverflow)?;
+        }
+
+        self.spends[index] = RecentSpend {
+            timestamp: u32::try_from(current_timestamp).map_err(|_| PyraError::MathOverflow)?,
+            amount_usdc_base_units,
+        };
+        self.next_index = u8::try_from(next_index).map_err(|_| PyraError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Timestamp of the most recent spend, 0 if no spend has been recorded
+    pub fn get_last_spend_timestamp(&self) -> u64 {
+        let newest_index =
+            (usize::from(self.next_index) + MAX_RECENT_SPENDS - 1) % MAX_RECENT_SPENDS;
+        u64::from(self.spends[newest_index].timestamp)
+    }
+
+    /// Removes an amount from the spend recorded at the timestamp, or the spends it was folded into. The caller
+    /// ensures the spend is still within the longest timeframe, so it hasn't been dropped.
+    pub fn release_spends_since(&mut self, amount_usdc_base_units: u64, timestamp: u64) {
+        let mut amount_to_release = amount_usdc_base_units;
+        let oldest_index = usize::from(self.next_index);
+
+        // Folded spends move into the next oldest, so the oldest spends since the timestamp hold the amount
+        for offset in 0..MAX_RECENT_SPENDS {
+            if amount_to_release == 0 {
+                break;
+            }
+
+            let spend = &mut self.spends[(oldest_index + offset) % MAX_RECENT_SPENDS];
+            if u64::from(spend.timestamp) < timestamp {
+                continue;
+            }
+            let released = spend.amount_usdc_base_units.min(amount_to_release);
+            spend.amount_usdc_base_units -= released;
+            amount_to_release -= released;
+        }
+    }
+}
+
+impl Vault {
+    /// Longest timeframe across the primary timeframe and the additional windows
+    pub fn get_longest_timeframe_in_seconds(&self) -> u64 {
+        self.spend_limit_windows
+            .iter()
+            .map(|window| window.timeframe_in_seconds)
+            .fold(self.timeframe_in_seconds, u64::max)
+    }
+
+    /// Builds the requested windows, keeping what's already been used from windows with the same timeframe
+    /// (otherwise changing anything in a window would reset its remaining limit completely)
+    pub fn get_updated_spend_limit_windows(
+        &self,
+        spend_limit_windows: &[SpendLimitWindowParams],
+    ) -> Result<[SpendLimitWindow; MAX_SPEND_LIMIT_WINDOWS]> {
+        check!(
+            spend_limit_windows.len() <= MAX_SPEND_LIMIT_WINDOWS,
+            PyraError::InvalidSpendLimitWindows
+        );
+
+        let mut updated_windows = [SpendLimitWindow::default(); MAX_SPEND_LIMIT_WINDOWS];
+        for (i, (updated_window, params)) in updated_windows
+            .iter_mut()
+            .zip(spend_limit_windows)
+            .enumerate()
+        {
+            check!(
+                params.timeframe_in_seconds > 0
+                    && !spend_limit_windows[..i].iter().any(|existing| existing
+                        .timeframe_in_seconds
+                        == params.timeframe_in_seconds),
+                PyraError::InvalidSpendLimitWindows
+            );
+
+            let already_used = self
+                .get_spend_limit_window(params.timeframe_in_seconds)
+                .map_or(0, |window| window.already_used());
+
+            *updated_window = SpendLimitWindow {
+                spend_limit_per_timeframe: params.spend_limit_per_timeframe,
+                remaining_spend_limit_per_timeframe: params
+                    .spend_limit_per_timeframe
+                    .saturating_sub(already_used),
+                next_timeframe_reset_timestamp: params.next_timeframe_reset_timestamp,
+                timeframe_in_seconds: params.timeframe_in_seconds,
+            };
+        }
+
+        Ok(updated_windows)
+    }
+
+    pub fn get_spend_limit_window(&self, timeframe_in_seconds: u64) -> Option<&SpendLimitWindow> {
+        self.spend_limit_windows.iter().find(|window| {
+            window.is_active() && window.timeframe_in_seconds == timeframe_in_seconds
+        })
+    }
+
+    /// Returns an amount spent at the timestamp to the remaining limit of each timeframe still in the period it was
+    /// spent in, and removes it from the recent spends. Returns the amount restored to the primary timeframe.
+    pub fn restore_spend_limit_since(
+        &mut self,
+        amount_usdc_base_units: u64,
+        spend_timestamp: u64,
+        timeframe_reset_timestamp: u64,
+        window_reset_timestamps: [u64; MAX_SPEND_LIMIT_WINDOWS],
+        current_timestamp: u64,
+    ) -> u64 {
+        // The spend's recent spend is dropped once it's outside the longest timeframe
+        if spend_timestamp.saturating_add(self.get_longest_timeframe_in_seconds())
+            > current_timestamp
+        {
+            self.recent_spends
+                .release_spends_since(amount_usdc_base_units, spend_timestamp);
+        }
+
+        let remaining_before_restore = self.remaining_spend_limit_per_timeframe;
+        if self.is_spent_in_current_period(
+            self.next_timeframe_reset_timestamp,
+            timeframe_reset_timestamp,
+            self.timeframe_in_seconds,
+            spend_timestamp,
+            current_timestamp,
+        ) {
+            self.remaining_spend_limit_per_timeframe = get_restored_spend_limit(
+                remaining_before_restore,
+                self.spend_limit_per_timeframe,
+                amount_usdc_base_units,
+            );
+        }
+
+        for (i, spent_reset_timestamp) in window_reset_timestamps.into_iter().enumerate() {
+            let window = self.spend_limit_windows[i];
+            if window.is_active()
+                && self.is_spent_in_current_period(
+                    window.next_timeframe_reset_timestamp,
+                    spent_reset_timestamp,
+                    window.timeframe_in_seconds,
+                    spend_timestamp,
+                    current_timestamp,
+                )
+            {
+                self.spend_limit_windows[i].remaining_spend_limit_per_timeframe =
+                    get_restored_spend_limit(
+                        window.remaining_spend_limit_per_timeframe,
+                        window.spend_limit_per_timeframe,
+                        amount_usdc_base_units,
+                    );
+            }
+        }
+
+        self.remaining_spend_limit_per_timeframe
+            .saturating_sub(remaining_before_restore)
+    }
+
+    /// True if any timeframe is still in the period a spend at the timestamp was made in
+    pub fn has_current_spend_period(
+        &self,
+        spend_timestamp: u64,
+        timeframe_reset_timestamp: u64,
+        window_reset_timestamps: [u64; MAX_SPEND_LIMIT_WINDOWS],
+        current_timestamp: u64,
+    ) -> bool {
+        self.is_spent_in_current_period(
+            self.next_timeframe_reset_timestamp,
+            timeframe_reset_timestamp,
+            self.timeframe_in_seconds,
+            spend_timestamp,
+            current_timestamp,
+        ) || self
+            .spend_limit_windows
+            .iter()
+            .zip(window_reset_timestamps)
+            .any(|(window, spent_reset_timestamp)| {
+                window.is_active()
+                    && self.is_spent_in_current_period(
+                        window.next_timeframe_reset_timestamp,
+                        spent_reset_timestamp,
+                        window.timeframe_in_seconds,
+                        spend_timestamp,
+                        current_timestamp,
+                    )
+            })
+    }
+
+    /// Rolling limits are recalculated from the recent spends, so a spend counts while it's within the trailing
+    /// timeframe, rather than until the reset timestamp changes
+    fn is_spent_in_current_period(
+        &self,
+        next_timeframe_reset_timestamp: u64,
+        spent_timeframe_reset_timestamp: u64,
+        timeframe_in_seconds: u64,
+        spend_timestamp: u64,
+        current_timestamp: u64,
+    ) -> bool {
+        if self.spend_limit_mode == SpendLimitMode::Rolling {
+            spend_timestamp.saturating_add(timeframe_in_seconds) > current_timestamp
+        } else {
+            next_timeframe_reset_timestamp == spent_timeframe_reset_timestamp
+        }
+    }
+
+    /// Returns an amount to the spend limit boost, unless the boost it was drawn from has been replaced
+    pub fn restore_spend_limit_boost(
+        &mut self,
+        amount_usdc_base_units: u64,
+        expiry_timestamp: u64,
+    ) {
+        if self.spend_limit_boost.expiry_timestamp == expiry_timestamp {
+            self.spend_limit_boost.remaining_additional_spend_limit = self
+                .spend_limit_boost
+                .remaining_additional_spend_limit
+                .saturating_add(amount_usdc_base_units)
+                .min(self.spend_limit_boost.additional_spend_limit);
+        }
+    }
+
+    /// Counts a capture towards the velocity limits. It isn't rejected by them, as the authorization it settles
+    /// already passed them.
+    pub fn record_capture(&mut self, current_timestamp: u64) -> Result<()> {
+        self.spend_count_per_timeframe = self.spend_count_per_timeframe.saturating_add(1);
+
+        // Recorded without an amount, as the amount was recorded by the authorization
+        let longest_timeframe_in_seconds = self.get_longest_timeframe_in_seconds();
+        self.recent_spends
+            .record_spend(0, current_timestamp, longest_timeframe_in_seconds)
+    }
+
+    /// True if the spend limits keep the vault's timeframes, reset timestamps and windows, so applying them keeps
+    /// what's already been spent in each timeframe
+    pub fn keeps_spend_limit_timing(
+        &self,
+        timeframe_in_seconds: u64,
+        next_timeframe_reset_timestamp: u64,
+        spend_limit_windows: &[SpendLimitWindowParams],
+    ) -> bool {
+        let active_window_count = self
+            .spend_limit_windows
+            .iter()
+            .filter(|window| window.is_active())
+            .count();
+
+        timeframe_in_seconds == self.timeframe_in_seconds
+            && next_timeframe_reset_timestamp == self.next_timeframe_reset_timestamp
+            && spend_limit_windows.len() == active_window_count
+            && spend_limit_windows.iter().all(|params| {
+                self.get_spend_limit_window(params.timeframe_in_seconds)
+                    .is_some_and(|window| {
+                        window.next_timeframe_reset_timestamp
+                            == params.next_timeframe_reset_timestamp
+                    })
+            })
+    }
+
+    /// Replaces the timing of a delegate's order with the vault's current timing, which may have rolled over since
+    /// the order was initiated, and keeps the vault's velocity limits if the order's are looser
+    pub fn keep_spend_limit_timing(&self, spend_limits_order: &mut SpendLimitsOrder) {
+        spend_limits_order.timeframe_in_seconds = self.timeframe_in_seconds;
+        spend_limits_order.next_timeframe_reset_timestamp = self.next_timeframe_reset_timestamp;
+        spend_limits_order.spend_limit_mode = self.spend_limit_mode;
+        spend_limits_order.utc_offset_seconds = self.utc_offset_seconds;
+        spend_limits_order.calendar_period = self.calendar_period;
+
+        for params in spend_limits_order.spend_limit_windows.iter_mut() {
+            let current_window = self
+                .spend_limit_windows
+                .iter()
+                .zip(self.spend_limit_window_calendar_periods)
+                .find(|(window, _)| {
+                    window.is_active() && window.timeframe_in_seconds == params.timeframe_in_seconds
+                });
+            if let Some((window, calendar_period)) = current_window {
+                params.next_timeframe_reset_timestamp = window.next_timeframe_reset_timestamp;
+                params.calendar_period = calendar_period;
+            }
+        }
+
+        if !self
+            .spend_velocity_limits
+            .is_loosening(&spend_limits_order.spend_velocity_limits)
+        {
+            spend_limits_order.spend_velocity_limits = self.spend_velocity_limits;
+        }
+    }
+
+    /// Deposit mint flags swept within the last MAX_DEPOSIT_SWEEP_AGE_SLOTS, older sweeps may have received deposits since
+    pub fn get_swept_deposit_mint_flags(&self, current_slot: u64) -> u64 {
+        if current_slot.saturating_sub(self.deposit_sweep_slot) <= MAX_DEPOSIT_SWEEP_AGE_SLOTS {
+            self.swept_deposit_mint_flags
+        } else {
+            0
+        }
+    }
+
+    /// Adds to the recent sweep, or starts a new sweep if the last one is too old to count
+    pub fn record_deposit_sweep(&mut self, swept_deposit_mint_flags: u64, current_slot: u64) {
+        let recent_flags = self.get_swept_deposit_mint_flags(current_slot);
+        if recent_flags == 0 {
+            self.deposit_sweep_slot = current_slot;
+        }
+        self.swept_deposit_mint_flags = recent_flags | swept_deposit_mint_flags;
+    }
+
+    pub fn add_open_order(&mut self) -> Result<()> {
+        self.open_order_count = self
+            .open_order_count
+            .checked_add(1)
+            .ok_or(PyraError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn add_spend_hold(&mut self) -> Result<()> {
+        self.open_spend_hold_count = self
+            .open_spend_hold_count
+            .checked_add(1)
+            .ok_or(PyraError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn remove_spend_hold(&mut self) {
+        self.open_spend_hold_count = self.open_spend_hold_count.saturating_sub(1);
+    }
+
+    /// Orders from a previous owner_epoch were dropped from the count when the vault changed owner, and orders
+    /// created before the vault was upgraded were never counted, so neither is removed
+    pub fn remove_open_order(&mut self, time_lock: &TimeLock) {
+        if time_lock.owner_epoch == self.owner_epoch {
+            self.open_order_count = self.open_order_count.saturating_sub(1);
+        }
+    }
+
+    /// Moves the vault to a new owner_epoch, which invalidates every open order so they no longer block closing it
+    pub fn change_owner(&mut self, new_owner: Pubkey) -> Result<()> {
+        self.owner = new_owner;
+        self.owner_epoch = self
+            .owner_epoch
+            .checked_add(1)
+            .ok_or(PyraError::MathOverflow)?;
+        self.open_order_count = 0;
+
+        Ok(())
+    }
+}
+
+/// Restored limits are capped at the timeframe limit, so a restore can't raise it
+fn get_restored_spend_limit(
+    remaining_spend_limit: u64,
+    spend_limit: u64,
+    amount_usdc_base_units: u64,
+) -> u64 {
+    remaining_spend_limit
+        .saturating_add(amount_usdc_base_units)
+        .min(spend_limit)
+        .max(remaining_spend_limit)
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    pub(in crate::state) const DAY: u64 = 86_400;
+    pub(in crate::state) const NOW: u64 = 1_700_000_000;
+
+    pub(in crate::state) fn test_vault() -> Vault {
+        Vault {
+            owner: Pubkey::new_unique(),
+            bump: 255,
+            spend_limit_per_transaction: 1_000,
+            spend_limit_per_timeframe: 1_000,
+            remaining_spend_limit_per_timeframe: 1_000,
+            next_timeframe_reset_timestamp: NOW + DAY,
+            timeframe_in_seconds: DAY,
+            version: crate::config::VAULT_VERSION,
+            vault_id: Pubkey::new_unique(),
+            spend_sub_account_id: 0,
+            drift_sub_account_count: 1,
+            is_card_frozen: false,
+            open_order_count: 0,
+            sponsor: Pubkey::default(),
+            referrer: Pubkey::default(),
+            settlement_provider_id: 0,
+            spend_limit_windows: [SpendLimitWindow::default(); MAX_SPEND_LIMIT_WINDOWS],
+            spend_limit_mode: SpendLimitMode::Fixed,
+            recent_spends: RecentSpends::default(),
+            utc_offset_seconds: 0,
+            spend_velocity_limits: SpendVelocityLimits::default(),
+            spend_count_per_timeframe: 0,
+            spend_limit_boost: SpendLimitBoost::default(),
+            calendar_period: CalendarPeriod::Day,
+            spend_limit_window_calendar_periods: [CalendarPeriod::Day; MAX_SPEND_LIMIT_WINDOWS],
+            owner_epoch: 0,
+            may_have_uncounted_orders: false,
+            swept_deposit_mint_flags: 0,
+            deposit_sweep_slot: 0,
+            open_spend_hold_count: 0,
+            reserved: [0; VAULT_RESERVED_SPACE],
+        }
+    }
+
+    #[test]
+    fn get_updated_spend_limit_windows_keeps_the_used_amount_of_matching_timeframes() {
+        let mut vault = test_vault();
+        vault.spend_limit_windows[0] = SpendLimitWindow {
+            spend_limit_per_timeframe: 5_000,
+            remaining_spend_limit_per_timeframe: 3_000,
+            next_timeframe_reset_timestamp: NOW + 7 * DAY,
+            timeframe_in_seconds: 7 * DAY,
+        };
+
+        let windows = vault
+            .get_updated_spend_limit_windows(&[
+                SpendLimitWindowParams {
+                    spend_limit_per_timeframe: 30_000,
+                    timeframe_in_seconds: 30 * DAY,
+                    next_timeframe_reset_timestamp: NOW + 30 * DAY,
+                    calendar_period: CalendarPeriod::Day,
+                },
+                SpendLimitWindowParams {
+                    spend_limit_per_timeframe: 1_000,
+                    timeframe_in_seconds: 7 * DAY,
+                    next_timeframe_reset_timestamp: NOW + 7 * DAY,
+                    calendar_period: CalendarPeriod::Day,
+                },
+            ])
+            .expect("valid windows");
+
+        // 2_000 was already used in the weekly window, which saturates when lowered below it
+        assert_eq!(windows[0].remaining_spend_limit_per_timeframe, 30_000);
+        assert_eq!(windows[1].spend_limit_per_timeframe, 1_000);
+        assert_eq!(windows[1].remaining_spend_limit_per_timeframe, 0);
+        assert!(!windows[2].is_active());
+    }
+
+    #[test]
+    fn get_updated_spend_limit_windows_rejects_empty_or_duplicate_timeframes() {
+        let vault = test_vault();
+        let weekly = SpendLimitWindowParams {
+            spend_limit_per_timeframe: 1_000,
+            timeframe_in_seconds: 7 * DAY,
+            next_timeframe_reset_timestamp: NOW + 7 * DAY,
+            calendar_period: CalendarPeriod::Day,
+        };
+
+        assert_eq!(
+            vault
+                .get_updated_spend_limit_windows(&[SpendLimitWindowParams {
+                    timeframe_in_seconds: 0,
+                    ..weekly.clone()
+                }])
+                .err(),
+            Some(PyraError::InvalidSpendLimitWindows.into())
+        );
+        assert_eq!(
+            vault
+                .get_updated_spend_limit_windows(&[weekly.clone(), weekly])
+                .err(),
+            Some(PyraError::InvalidSpendLimitWindows.into())
+        );
+    }
+
+    #[test]
+    fn record_spend_folds_the_oldest_spend_still_within_the_longest_timeframe() {
+        let mut recent_spends = RecentSpends::default();
+        for i in 0..MAX_RECENT_SPENDS as u64 {
+            recent_spends
+                .record_spend(i + 1, NOW + i, DAY)
+                .expect("records spend");
+        }
+        let spent_before = recent_spends.get_spent_within(DAY, NOW + DAY - 1);
+
+        recent_spends
+            .record_spend(100, NOW + 100, DAY)
+            .expect("records spend");
+
+        // The oldest spend of 1 is folded into the next oldest spend of 2, rather than forgotten
+        assert_eq!(recent_spends.next_index, 1);
+        assert_eq!(recent_spends.spends[0].amount_usdc_base_units, 100);
+        assert_eq!(recent_spends.spends[1].amount_usdc_base_units, 3);
+        assert_eq!(
+            recent_spends.get_spent_within(DAY, NOW + DAY - 1),
+            spent_before + 100
+        );
+
+        // Once the oldest spend is outside the longest timeframe it's dropped
+        recent_spends
+            .record_spend(100, NOW + DAY + 1, DAY)
+            .expect("records spend");
+        assert_eq!(recent_spends.spends[2].amount_usdc_base_units, 3);
+    }
+
+    #[test]
+    fn get_spent_within_only_counts_the_trailing_timeframe() {
+        let mut recent_spends = RecentSpends::default();
+        recent_spends
+            .record_spend(10, NOW, DAY)
+            .expect("records spend");
+        recent_spends
+            .record_spend(20, NOW + DAY / 2, DAY)
+            .expect("records spend");
+
+        assert_eq!(recent_spends.get_spent_within(DAY, NOW + DAY - 1), 30);
+        assert_eq!(recent_spends.get_spent_within(DAY, NOW + DAY), 20);
+        assert_eq!(recent_spends.get_spent_within(DAY / 4, NOW + DAY / 2), 20);
+        assert_eq!(recent_spends.get_spent_within(DAY, NOW + 2 * DAY), 0);
+    }
+
+    #[test]
+    fn release_spends_since_removes_from_the_oldest_spends_since_the_timestamp() {
+        let mut recent_spends = RecentSpends::default();
+        for i in 0..MAX_RECENT_SPENDS as u64 + 1 {
+            recent_spends
+                .record_spend(i + 1, NOW + i, DAY)
+                .expect("records spend");
+        }
+
+        // The spend of 1 at NOW was folded into the spend of 2 at NOW + 1, so releasing from NOW starts there
+        recent_spends.release_spends_since(4, NOW);
+        assert_eq!(recent_spends.spends[1].amount_usdc_base_units, 0);
+        assert_eq!(recent_spends.spends[2].amount_usdc_base_units, 2);
+
+        // Spends before the timestamp are kept
+        recent_spends.release_spends_since(3, NOW + 3);
+        assert_eq!(recent_spends.spends[2].amount_usdc_base_units, 2);
+        assert_eq!(recent_spends.spends[3].amount_usdc_base_units, 1);
+    }
+
+    #[test]
+    fn velocity_limits_are_loosened_only_by_raising_or_disabling_them() {
+        let current = SpendVelocityLimits {
+            max_spends_per_timeframe: 10,
+            min_seconds_between_spends: 60,
+        };
+
+        assert!(current.is_loosening(&current));
+        assert!(SpendVelocityLimits::default().is_loosening(&current));
+        assert!(SpendVelocityLimits {
+            max_spends_per_timeframe: 20,
+            min_seconds_between_spends: 30,
+        }
+        .is_loosening(&current));
+
+        assert!(!SpendVelocityLimits {
+            max_spends_per_timeframe: 9,
+            ..current
+        }
+        .is_loosening(&current));
+        assert!(!SpendVelocityLimits {
+            min_seconds_between_spends: 61,
+            ..current
+        }
+        .is_loosening(&current));
+        // Enabling a disabled spend count limit tightens it, however high it's set
+        assert!(!current.is_loosening(&SpendVelocityLimits {
+            max_spends_per_timeframe: 0,
+            min_seconds_between_spends: 60,
+        }));
+    }
+
+    #[test]
+    fn boost_expires_at_its_expiry_timestamp() {
+        let boost = SpendLimitBoost {
+            additional_spend_limit_per_transaction: 100,
+            additional_spend_limit: 1_000,
+            remaining_additional_spend_limit: 1_000,
+            expiry_timestamp: NOW + DAY,
+        };
+
+        assert!(boost.is_active(NOW + DAY - 1));
+        assert!(!boost.is_active(NOW + DAY));
+        assert!(!SpendLimitBoost::default().is_active(NOW));
+    }
+
+    #[test]
+    fn boost_is_loosening_only_if_it_allows_as_much_for_as_long() {
+        let current = SpendLimitBoost {
+            additional_spend_limit_per_transaction: 100,
+            additional_spend_limit: 1_000,
+            remaining_additional_spend_limit: 400,
+            expiry_timestamp: NOW + DAY,
+        };
+
+        assert!(SpendLimitBoost {
+            additional_spend_limit_per_transaction: 200,
+            expiry_timestamp: NOW + 2 * DAY,
+            ..current
+        }
+        .is_loosening(&current, NOW));
+        assert!(!SpendLimitBoost {
+            additional_spend_limit: 999,
+            remaining_additional_spend_limit: 999,
+            ..current
+        }
+        .is_loosening(&current, NOW));
+        assert!(!SpendLimitBoost {
+            expiry_timestamp: NOW + DAY - 1,
+            ..current
+        }
+        .is_loosening(&current, NOW));
+
+        // Any boost loosens an expired boost
+        assert!(SpendLimitBoost::default().is_loosening(&current, NOW + DAY));
+    }
+
+    #[test]
+    fn replacing_a_boost_keeps_what_was_already_drawn_from_it() {
+        let current = SpendLimitBoost {
+            additional_spend_limit_per_transaction: 100,
+            additional_spend_limit: 1_000,
+            remaining_additional_spend_limit: 400,
+            expiry_timestamp: NOW + DAY,
+        };
+
+        // Re-boosting for the same total doesn't refill the 600 already drawn
+        let replacement = current.get_replacement(100, 1_000, NOW + 2 * DAY, NOW);
+        assert!(replacement.is_loosening(&current, NOW));
+        assert_eq!(replacement.remaining_additional_spend_limit, 400);
+
+        // Raising the total only adds the raise
+        let replacement = current.get_replacement(100, 1_500, NOW + DAY, NOW);
+        assert_eq!(replacement.remaining_additional_spend_limit, 900);
+
+        // A boost replacing an expired boost starts in full
+        let replacement = current.get_replacement(100, 1_000, NOW + 2 * DAY, NOW + DAY);
+        assert_eq!(replacement.remaining_additional_spend_limit, 1_000);
+    }
+
+    fn weekly_window_params(next_timeframe_reset_timestamp: u64) -> SpendLimitWindowParams {
+        SpendLimitWindowParams {
+            spend_limit_per_timeframe: 5_000,
+            timeframe_in_seconds: 7 * DAY,
+            next_timeframe_reset_timestamp,
+            calendar_period: CalendarPeriod::Day,
+        }
+    }
+
+    #[test]
+    fn delegate_spend_limits_must_keep_the_timeframes_and_reset_timestamps() {
+        let mut vault = test_vault();
+        vault.remaining_spend_limit_per_timeframe = 400;
+        vault.spend_limit_windows[0] = SpendLimitWindow {
+            spend_limit_per_timeframe: 5_000,
+            remaining_spend_limit_per_timeframe: 4_400,
+            next_timeframe_reset_timestamp: NOW + 7 * DAY,
+            timeframe_in_seconds: 7 * DAY,
+        };
+        let windows = [weekly_window_params(NOW + 7 * DAY)];
+
+        assert!(vault.keeps_spend_limit_timing(DAY, NOW + DAY, &windows));
+
+        // Moving a reset into the past or shortening a timeframe would reset the used amount on the next spend
+        assert!(!vault.keeps_spend_limit_timing(DAY, NOW, &windows));
+        assert!(!vault.keeps_spend_limit_timing(1, NOW + DAY, &windows));
+        assert!(!vault.keeps_spend_limit_timing(DAY, NOW + DAY, &[weekly_window_params(NOW)]));
+
+        // Dropping a window and adding it back later would reset its used amount
+        assert!(!vault.keeps_spend_limit_timing(DAY, NOW + DAY, &[]));
+    }
+
+    #[test]
+    fn keep_spend_limit_timing_replaces_the_timing_of_a_delegate_order() {
+        let mut vault = test_vault();
+        vault.spend_limit_windows[0] = SpendLimitWindow {
+            spend_limit_per_timeframe: 5_000,
+            remaining_spend_limit_per_timeframe: 4_400,
+            next_timeframe_reset_timestamp: NOW + 14 * DAY,
+            timeframe_in_seconds: 7 * DAY,
+        };
+        vault.spend_velocity_limits = SpendVelocityLimits {
+            max_spends_per_timeframe: 10,
+            min_seconds_between_spends: 60,
+        };
+
+        // Initiated before the vault's timeframes rolled over
+        let mut spend_limits_order = SpendLimitsOrder {
+            time_lock: time_lock(0),
+            vault: Pubkey::new_unique(),
+            spend_limit_per_transaction: 2_000,
+            spend_limit_per_timeframe: 2_000,
+            timeframe_in_seconds: DAY,
+            next_timeframe_reset_timestamp: NOW,
+            spend_limit_windows: vec![weekly_window_params(NOW + 7 * DAY)],
+            spend_limit_mode: SpendLimitMode::Fixed,
+            utc_offset_seconds: 0,
+            spend_velocity_limits: SpendVelocityLimits::default(),
+            calendar_period: CalendarPeriod::Day,
+            is_delegate_order: true,
+        };
+        vault.keep_spend_limit_timing(&mut spend_limits_order);
+
+        assert_eq!(spend_limits_order.next_timeframe_reset_timestamp, NOW + DAY);
+        assert_eq!(
+            spend_limits_order.spend_limit_windows[0].next_timeframe_reset_timestamp,
+            NOW + 14 * DAY
+        );
+        assert_eq!(spend_limits_order.spend_limit_per_timeframe, 2_000);
+        assert_eq!(
+            spend_limits_order
+                .spend_velocity_limits
+                .max_spends_per_timeframe,
+            10
+        );
+
+        // Stricter velocity limits are kept
+        spend_limits_order
+            .spend_velocity_limits
+            .min_seconds_between_spends = 120;
+        vault.keep_spend_limit_timing(&mut spend_limits_order);
+        assert_eq!(
+            spend_limits_order
+                .spend_velocity_limits
+                .min_seconds_between_spends,
+            120
+        );
+    }
+
+    fn time_lock(owner_epoch: u32) -> TimeLock {
+        TimeLock {
+            owner: Pubkey::new_unique(),
+            is_owner_payer: true,
+            release_slot: 0,
+            owner_epoch,
+        }
+    }
+
+    #[test]
+    fn deposit_sweeps_only_count_while_recent() {
+        let mut vault = test_vault();
+        let slot = 1_000;
+
+        vault.record_deposit_sweep(0b011, slot);
+        vault.record_deposit_sweep(0b100, slot + MAX_DEPOSIT_SWEEP_AGE_SLOTS);
+        assert_eq!(
+            vault.get_swept_deposit_mint_flags(slot + MAX_DEPOSIT_SWEEP_AGE_SLOTS),
+            0b111
+        );
+
+        // The sweep is aged from its first batch, after which a new sweep starts
+        assert_eq!(
+            vault.get_swept_deposit_mint_flags(slot + MAX_DEPOSIT_SWEEP_AGE_SLOTS + 1),
+            0
+        );
+        vault.record_deposit_sweep(0b100, slot + MAX_DEPOSIT_SWEEP_AGE_SLOTS + 1);
+        assert_eq!(
+            vault.get_swept_deposit_mint_flags(slot + MAX_DEPOSIT_SWEEP_AGE_SLOTS + 1),
+            0b100
+        );
+    }
+
+    #[test]
+    fn change_owner_moves_to_a_new_owner_epoch_and_drops_open_orders() {
+        let mut vault = test_vault();
+        vault.add_open_order().expect("no overflow");
+        let new_owner = Pubkey::new_unique();
+
+        vault.change_owner(new_owner).expect("no overflow");
+
+        assert_eq!(vault.owner, new_owner);
+        assert_eq!(vault.owner_epoch, 1);
+        assert_eq!(vault.open_order_count, 0);
+    }
+
+    #[test]
+    fn remove_open_order_ignores_orders_from_previous_owner_epochs() {
+        let mut vault = test_vault();
+        vault
+            .change_owner(Pubkey::new_unique())
+            .expect("no overflow");
+        vault.add_open_order().expect("no overflow");
+
+        vault.remove_open_order(&time_lock(0));
+        assert_eq!(vault.open_order_count, 1);
+
+        vault.remove_open_order(&time_lock(1));
+        assert_eq!(vault.open_order_count, 0);
+    }
 }
@@ -0,0 +1,253 @@
+use crate::{
+    check,
+    config::{
+        PyraError, ANCHOR_DISCRIMINATOR, DEFAULT_SETTLEMENT_PROVIDER_ID, MAX_SPEND_LIMIT_WINDOWS,
+        PUBKEY_SIZE, U64_SIZE, U8_SIZE, VAULT_RESERVED_SPACE, VAULT_VERSION,
+    },
+    state::{
+        CalendarPeriod, RecentSpends, SpendLimitBoost, SpendLimitMode, SpendLimitWindow,
+        SpendVelocityLimits, Vault,
+    },
+};
+use anchor_lang::{prelude::*, Discriminator};
+
+/// Original Vault layout, before spend limits were added
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct VaultV1 {
+    pub owner: Pubkey,
+    pub bump: u8,
+}
+
+impl Space for VaultV1 {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR + PUBKEY_SIZE + U8_SIZE;
+}
+
+/// Vault layout with spend limits, before the version field and reserved space were added
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct VaultV2 {
+    pub owner: Pubkey,
+    pub bump: u8,
+    pub spend_limit_per_transaction: u64,
+    pub spend_limit_per_timeframe: u64,
+    pub remaining_spend_limit_per_timeframe: u64,
+    pub next_timeframe_reset_timestamp: u64,
+    pub timeframe_in_seconds: u64,
+}
+
+impl Space for VaultV2 {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR
+        + PUBKEY_SIZE
+        + U8_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE;
+}
+
+/// Values that can't be derived from the previous layout, supplied by the owner when migrating
+pub struct VaultMigrationParams {
+    pub spend_limit_per_transaction: u64,
+    pub spend_limit_per_timeframe: u64,
+    pub timeframe_in_seconds: u64,
+    pub next_timeframe_reset_timestamp: u64,
+}
+
+impl VaultV1 {
+    fn into_v2(self, params: &VaultMigrationParams) -> VaultV2 {
+        VaultV2 {
+            owner: self.owner,
+            bump: self.bump,
+            spend_limit_per_transaction: params.spend_limit_per_transaction,
+            spend_limit_per_timeframe: params.spend_limit_per_timeframe,
+            remaining_spend_limit_per_timeframe: params.spend_limit_per_timeframe,
+            next_timeframe_reset_timestamp: params.next_timeframe_reset_timestamp,
+            timeframe_in_seconds: params.timeframe_in_seconds,
+        }
+    }
+}
+
+impl VaultV2 {
+    /// Keeps the spend limits, and starts every field added since at its default
+    fn into_current(self) -> Box<Vault> {
+        Box::new(Vault {
+            owner: self.owner,
+            bump: self.bump,
+            spend_limit_per_transaction: self.spend_limit_per_transaction,
+            spend_limit_per_timeframe: self.spend_limit_per_timeframe,
+            remaining_spend_limit_per_timeframe: self.remaining_spend_limit_per_timeframe,
+            next_timeframe_reset_timestamp: self.next_timeframe_reset_timestamp,
+            timeframe_in_seconds: self.timeframe_in_seconds,
+            version: VAULT_VERSION,
+            // Owners couldn't change in the unversioned layouts, so the vault was derived from the current owner
+            vault_id: self.owner,
+            // Unversioned vaults only had the sub-account 0
+            spend_sub_account_id: 0,
+            drift_sub_account_count: 1,
+            is_card_frozen: false,
+            open_order_count: 0,
+            sponsor: Pubkey::default(),
+            referrer: Pubkey::default(),
+            // Unversioned vaults all settled through the original Base provider
+            settlement_provider_id: DEFAULT_SETTLEMENT_PROVIDER_ID,
+            spend_limit_windows: [SpendLimitWindow::default(); MAX_SPEND_LIMIT_WINDOWS],
+            spend_limit_mode: SpendLimitMode::Fixed,
+            recent_spends: RecentSpends::default(),
+            utc_offset_seconds: 0,
+            spend_velocity_limits: SpendVelocityLimits::default(),
+            spend_count_per_timeframe: 0,
+            spend_limit_boost: SpendLimitBoost::default(),
+            calendar_period: CalendarPeriod::Day,
+            spend_limit_window_calendar_periods: [CalendarPeriod::Day; MAX_SPEND_LIMIT_WINDOWS],
+            owner_epoch: 0,
+            // Orders opened by unversioned vaults weren't counted, so the owner must attest none remain before the
+            // vault can close
+            may_have_uncounted_orders: true,
+            swept_deposit_mint_flags: 0,
+            deposit_sweep_slot: 0,
+            open_spend_hold_count: 0,
+            reserved: [0; VAULT_RESERVED_SPACE],
+        })
+    }
+}
+
+// The version field directly follows the v2 fields in the versioned layout
+const VERSION_OFFSET: usize = VaultV2::INIT_SPACE;
+
+/// Unversioned layouts (v1 and v2) are identified by their size, the versioned layout by its version field
+pub fn get_vault_version(data: &[u8]) -> Result<u8> {
+    check!(
+        data.len() >= ANCHOR_DISCRIMINATOR
+            && data[..ANCHOR_DISCRIMINATOR].eq(&Vault::DISCRIMINATOR),
+        PyraError::InvalidVaultAccount
+    );
+
+    let version = match data.len() {
+        VaultV1::INIT_SPACE => 1,
+        VaultV2::INIT_SPACE => 2,
+        _ => *data
+            .get(VERSION_OFFSET)
+            .ok_or(PyraError::FailedToDeserializeVaultBytes)?,
+    };
+
+    Ok(version)
+}
+
+/// Deserializes a vault account in an unversioned layout and migrates it to the current layout
+pub fn migrate_vault(data: &[u8], params: &VaultMigrationParams) -> Result<Box<Vault>> {
+    let mut vault_bytes = &data[ANCHOR_DISCRIMINATOR..];
+
+    let vault_v2 = match get_vault_version(data)? {
+        1 => VaultV1::deserialize(&mut vault_bytes)
+            .map_err(|_| PyraError::FailedToDeserializeVaultBytes)?
+            .into_v2(params),
+        2 => VaultV2::deserialize(&mut vault_bytes)
+            .map_err(|_| PyraError::FailedToDeserializeVaultBytes)?,
+        VAULT_VERSION => return Err(PyraError::VaultAlreadyUpToDate.into()),
+        _ => return Err(PyraError::UnsupportedVaultVersion.into()),
+    };
+
+    Ok(vault_v2.into_current())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> VaultMigrationParams {
+        VaultMigrationParams {
+            spend_limit_per_transaction: 100,
+            spend_limit_per_timeframe: 1_000,
+            timeframe_in_seconds: 86_400,
+            next_timeframe_reset_timestamp: 1_700_000_000,
+        }
+    }
+
+    fn account_data<T: AnchorSerialize>(vault: &T) -> Vec<u8> {
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        data.extend(vault.try_to_vec().expect("serializes"));
+        data
+    }
+
+    #[test]
+    fn migrates_v1_to_current_with_params_and_defaults() {
+        let owner = Pubkey::new_unique();
+        let data = account_data(&VaultV1 { owner, bump: 254 });
+        assert_eq!(data.len(), VaultV1::INIT_SPACE);
+        assert_eq!(get_vault_version(&data).expect("version"), 1);
+
+        let vault = migrate_vault(&data, &params()).expect("migrates");
+        assert_eq!(vault.version, VAULT_VERSION);
+        assert_eq!(vault.owner, owner);
+        assert_eq!(vault.vault_id, owner);
+        assert_eq!(vault.bump, 254);
+        assert_eq!(vault.spend_limit_per_transaction, 100);
+        assert_eq!(vault.spend_limit_per_timeframe, 1_000);
+        assert_eq!(vault.remaining_spend_limit_per_timeframe, 1_000);
+        assert_eq!(vault.timeframe_in_seconds, 86_400);
+        assert_eq!(vault.next_timeframe_reset_timestamp, 1_700_000_000);
+        assert_eq!(vault.drift_sub_account_count, 1);
+        assert_eq!(vault.settlement_provider_id, DEFAULT_SETTLEMENT_PROVIDER_ID);
+        assert!(vault.spend_limit_mode == SpendLimitMode::Fixed);
+        assert!(vault.may_have_uncounted_orders);
+        assert!(vault.reserved.iter().all(|byte| *byte == 0));
+        assert_eq!(
+            vault.try_to_vec().expect("serializes").len() + ANCHOR_DISCRIMINATOR,
+            Vault::INIT_SPACE
+        );
+    }
+
+    #[test]
+    fn migrates_v2_without_using_params() {
+        let owner = Pubkey::new_unique();
+        let data = account_data(&VaultV2 {
+            owner,
+            bump: 1,
+            spend_limit_per_transaction: 5,
+            spend_limit_per_timeframe: 50,
+            remaining_spend_limit_per_timeframe: 20,
+            next_timeframe_reset_timestamp: 7,
+            timeframe_in_seconds: 60,
+        });
+        assert_eq!(data.len(), VaultV2::INIT_SPACE);
+
+        let vault = migrate_vault(&data, &params()).expect("migrates");
+        assert_eq!(vault.spend_limit_per_transaction, 5);
+        assert_eq!(vault.spend_limit_per_timeframe, 50);
+        assert_eq!(vault.remaining_spend_limit_per_timeframe, 20);
+        assert_eq!(vault.next_timeframe_reset_timestamp, 7);
+        assert_eq!(vault.timeframe_in_seconds, 60);
+    }
+
+    #[test]
+    fn rejects_current_unknown_and_foreign_accounts() {
+        let mut data = account_data(&VaultV2 {
+            owner: Pubkey::new_unique(),
+            bump: 1,
+            spend_limit_per_transaction: 0,
+            spend_limit_per_timeframe: 0,
+            remaining_spend_limit_per_timeframe: 0,
+            next_timeframe_reset_timestamp: 0,
+            timeframe_in_seconds: 0,
+        });
+        data.resize(Vault::INIT_SPACE, 0);
+
+        data[VERSION_OFFSET] = VAULT_VERSION;
+        assert_eq!(
+            migrate_vault(&data, &params()).err(),
+            Some(PyraError::VaultAlreadyUpToDate.into())
+        );
+
+        data[VERSION_OFFSET] = VAULT_VERSION + 1;
+        assert_eq!(
+            migrate_vault(&data, &params()).err(),
+            Some(PyraError::UnsupportedVaultVersion.into())
+        );
+
+        data[0] = data[0].wrapping_add(1);
+        assert_eq!(
+            get_vault_version(&data).expect_err("not a vault"),
+            PyraError::InvalidVaultAccount.into()
+        );
+    }
+}
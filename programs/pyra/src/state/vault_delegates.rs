@@ -0,0 +1,124 @@
+use crate::{
+    check,
+    config::{
+        PyraError, ANCHOR_DISCRIMINATOR, MAX_VAULT_DELEGATES, PUBKEY_SIZE, U64_SIZE, U8_SIZE,
+        VEC_LENGTH_PREFIX_SIZE,
+    },
+};
+use anchor_lang::prelude::*;
+
+/// Key the owner has granted scoped permissions on their vault
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct VaultDelegate {
+    pub delegate: Pubkey,
+
+    // Bitmask of DelegatePermissions
+    pub permissions: u8,
+
+    // Spend limits can't be raised above these values by the delegate
+    pub max_spend_limit_per_transaction: u64,
+    pub max_spend_limit_per_timeframe: u64,
+}
+
+impl Space for VaultDelegate {
+    const INIT_SPACE: usize = PUBKEY_SIZE + U8_SIZE + U64_SIZE + U64_SIZE;
+}
+
+impl VaultDelegate {
+    pub fn has_permission(&self, permission: DelegatePermission) -> bool {
+        self.permissions & permission.flag() != 0
+    }
+}
+
+/// Owner actions that can be delegated
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub enum DelegatePermission {
+    // Initiate spend limit changes and instantly increase spend limits, up to the delegate's maximums
+    SpendLimits,
+    // Run manual collateral repays, which skip the auto repay health checks
+    CollateralRepay,
+    // Initiate withdraws where the destination is the vault owner
+    WithdrawToOwner,
+}
+
+impl DelegatePermission {
+    fn flag(self) -> u8 {
+        1 << (self as u8)
+    }
+
+    pub fn to_flags(permissions: &[DelegatePermission]) -> u8 {
+        permissions
+            .iter()
+            .fold(0, |flags, permission| flags | permission.flag())
+    }
+}
+
+/// Registry of delegates for a vault. Delegates are only valid for the owner that granted them, so they are dropped when the vault changes owner.
+#[account]
+pub struct VaultDelegates {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub bump: u8,
+    pub delegates: Vec<VaultDelegate>,
+}
+
+impl Space for VaultDelegates {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR
+        + PUBKEY_SIZE
+        + PUBKEY_SIZE
+        + U8_SIZE
+        + VEC_LENGTH_PREFIX_SIZE
+        + (VaultDelegate::INIT_SPACE * MAX_VAULT_DELEGATES);
+}
+
+impl VaultDelegates {
+    pub fn get_delegate(&self, vault_owner: &Pubkey, delegate: &Pubkey) -> Option<&VaultDelegate> {
+        if !self.owner.eq(vault_owner) {
+            return None;
+        }
+
+        self.delegates
+            .iter()
+            .find(|existing| existing.delegate.eq(delegate))
+    }
+
+    /// Adds the delegate, or replaces its permissions if it already exists
+    pub fn set_delegate(&mut self, vault_owner: Pubkey, delegate: VaultDelegate) -> Result<()> {
+        if !self.owner.eq(&vault_owner) {
+            self.owner = vault_owner;
+            self.delegates.clear();
+        }
+
+        match self
+            .delegates
+            .iter_mut()
+            .find(|existing| existing.delegate.eq(&delegate.delegate))
+        {
+            Some(existing) => *existing = delegate,
+            None => {
+                check!(
+                    self.delegates.len() < MAX_VAULT_DELEGATES,
+                    PyraError::VaultDelegatesFull
+                );
+                self.delegates.push(delegate);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn revoke_delegate(&mut self, vault_owner: &Pubkey, delegate: &Pubkey) -> Result<()> {
+        // Delegates granted by a previous owner are already inactive
+        check!(self.owner.eq(vault_owner), PyraError::VaultDelegateNotFound);
+
+        let position = self
+            .delegates
+            .iter()
+            .position(|existing| existing.delegate.eq(delegate))
+            .ok_or(PyraError::VaultDelegateNotFound)?;
+
+        self.delegates.remove(position);
+
+        Ok(())
+    }
+}
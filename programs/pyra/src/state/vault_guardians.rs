@@ -0,0 +1,189 @@
+use crate::{
+    check,
+    config::{
+        PyraError, ANCHOR_DISCRIMINATOR, MAX_VAULT_GUARDIANS, MIN_RECOVERY_DELAY_SLOTS,
+        PUBKEY_SIZE, U64_SIZE, U8_SIZE, VEC_LENGTH_PREFIX_SIZE,
+    },
+};
+use anchor_lang::prelude::*;
+
+/// M-of-N guardian set that can recover a vault to a new owner if the owner loses their key. Like delegates,
+/// guardians are only valid for the owner that set them, so a regular owner transfer drops them.
+#[account]
+pub struct VaultGuardians {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub bump: u8,
+
+    // Number of guardian signatures required to initiate a recovery
+    pub threshold: u8,
+
+    // How long the owner has to veto a recovery before it can be fulfilled
+    pub recovery_delay_slots: u64,
+
+    pub guardians: Vec<Pubkey>,
+}
+
+impl Space for VaultGuardians {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR
+        + PUBKEY_SIZE
+        + PUBKEY_SIZE
+        + U8_SIZE
+        + U8_SIZE
+        + U64_SIZE
+        + VEC_LENGTH_PREFIX_SIZE
+        + (PUBKEY_SIZE * MAX_VAULT_GUARDIANS);
+}
+
+impl VaultGuardians {
+    pub fn set_guardians(
+        &mut self,
+        vault_owner: Pubkey,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+        recovery_delay_slots: u64,
+    ) -> Result<()> {
+        check!(
+            !guardians.is_empty() && guardians.len() <= MAX_VAULT_GUARDIANS,
+            PyraError::InvalidGuardianSet
+        );
+        for (i, guardian) in guardians.iter().enumerate() {
+            check!(
+                !guardian.eq(&vault_owner) && !guardians[..i].contains(guardian),
+                PyraError::InvalidGuardianSet
+            );
+        }
+
+        check!(
+            threshold > 0 && usize::from(threshold) <= guardians.len(),
+            PyraError::InvalidGuardianThreshold
+        );
+
+        check!(
+            recovery_delay_slots >= MIN_RECOVERY_DELAY_SLOTS,
+            PyraError::InvalidRecoveryDelay
+        );
+
+        self.owner = vault_owner;
+        self.threshold = threshold;
+        self.recovery_delay_slots = recovery_delay_slots;
+        self.guardians = guardians;
+
+        Ok(())
+    }
+
+    /// Returns the distinct guardians that have signed, checking they reach the threshold
+    pub fn validate_guardian_signers(
+        &self,
+        vault_owner: &Pubkey,
+        signers: &[AccountInfo],
+    ) -> Result<Vec<Pubkey>> {
+        // Guardians set by a previous owner are inactive
+        check!(
+            self.owner.eq(vault_owner),
+            PyraError::GuardianThresholdNotReached
+        );
+
+        let mut approving_guardians: Vec<Pubkey> = Vec::with_capacity(self.guardians.len());
+        for signer in signers {
+            if signer.is_signer
+                && self.guardians.contains(signer.key)
+                && !approving_guardians.contains(signer.key)
+            {
+                approving_guardians.push(*signer.key);
+            }
+        }
+
+        check!(
+            approving_guardians.len() >= usize::from(self.threshold),
+            PyraError::GuardianThresholdNotReached
+        );
+
+        Ok(approving_guardians)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_guardian_signers_counts_each_signing_guardian_once() {
+        let owner = Pubkey::new_unique();
+        let guardians = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let vault_guardians = VaultGuardians {
+            vault: Pubkey::new_unique(),
+            owner,
+            bump: 255,
+            threshold: 2,
+            recovery_delay_slots: MIN_RECOVERY_DELAY_SLOTS,
+            guardians: guardians.clone(),
+        };
+
+        // The first guardian signs twice, the second is passed without signing, and the owner isn't a guardian
+        let signers = [
+            (guardians[0], true),
+            (guardians[0], true),
+            (guardians[1], false),
+            (owner, true),
+        ];
+        let mut lamports = [0; 4];
+        let mut data: [[u8; 0]; 4] = [[]; 4];
+        let mut account_infos: Vec<AccountInfo> = signers
+            .iter()
+            .zip(lamports.iter_mut())
+            .zip(data.iter_mut())
+            .map(|(((key, is_signer), lamports), data)| {
+                AccountInfo::new(key, *is_signer, false, lamports, data, &crate::ID, false, 0)
+            })
+            .collect();
+
+        assert_eq!(
+            vault_guardians
+                .validate_guardian_signers(&owner, &account_infos)
+                .expect_err("one distinct guardian"),
+            PyraError::GuardianThresholdNotReached.into()
+        );
+
+        account_infos[2].is_signer = true;
+        assert_eq!(
+            vault_guardians
+                .validate_guardian_signers(&owner, &account_infos)
+                .expect("threshold reached"),
+            guardians
+        );
+    }
+
+    #[test]
+    fn guardians_set_by_a_previous_owner_are_inactive() {
+        let guardian = Pubkey::new_unique();
+        let vault_guardians = VaultGuardians {
+            vault: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            bump: 255,
+            threshold: 1,
+            recovery_delay_slots: MIN_RECOVERY_DELAY_SLOTS,
+            guardians: vec![guardian],
+        };
+
+        let mut lamports = 0;
+        let mut data = [];
+        let account_info = AccountInfo::new(
+            &guardian,
+            true,
+            false,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+            false,
+            0,
+        );
+
+        assert_eq!(
+            vault_guardians
+                .validate_guardian_signers(&Pubkey::new_unique(), &[account_info])
+                .expect_err("previous owner's guardians"),
+            PyraError::GuardianThresholdNotReached.into()
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{
+    check,
+    config::{
+        PyraError, ANCHOR_DISCRIMINATOR, MAX_MERCHANT_CATEGORY_LIMITS, MAX_MERCHANT_IDS,
+        MERCHANT_ID_HASH_SIZE, PUBKEY_SIZE, U16_SIZE, U64_SIZE, U8_SIZE, VEC_LENGTH_PREFIX_SIZE,
+    },
+};
+use anchor_lang::prelude::*;
+
+/// Spend limit for a merchant category code, a limit of 0 blocks the category
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct MerchantCategoryLimit {
+    pub merchant_category_code: u16,
+    pub spend_limit_per_timeframe: u64,
+}
+
+impl Space for MerchantCategoryLimit {
+    const INIT_SPACE: usize = U16_SIZE + U64_SIZE;
+}
+
+/// How the merchant id list is applied to spends
+#[derive(Clone, Copy, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum MerchantListMode {
+    // Spends at listed merchants are rejected
+    Blocklist,
+    // Only spends at listed merchants are allowed
+    Allowlist,
+}
+
+/// Merchant restrictions set by the owner, applied on top of the vault's spend limits
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct MerchantControls {
+    pub category_limits: Vec<MerchantCategoryLimit>,
+    pub merchant_list_mode: MerchantListMode,
+    pub merchant_id_hashes: Vec<[u8; MERCHANT_ID_HASH_SIZE]>,
+}
+
+impl Space for MerchantControls {
+    const INIT_SPACE: usize = VEC_LENGTH_PREFIX_SIZE
+        + (MerchantCategoryLimit::INIT_SPACE * MAX_MERCHANT_CATEGORY_LIMITS)
+        + U8_SIZE
+        + VEC_LENGTH_PREFIX_SIZE
+        + (MERCHANT_ID_HASH_SIZE * MAX_MERCHANT_IDS);
+}
+
+impl MerchantControls {
+    pub fn validate(&self) -> Result<()> {
+        check!(
+            self.category_limits.len() <= MAX_MERCHANT_CATEGORY_LIMITS,
+            PyraError::InvalidMerchantControls
+        );
+        check!(
+            self.merchant_id_hashes.len() <= MAX_MERCHANT_IDS,
+            PyraError::InvalidMerchantControls
+        );
+
+        for (i, limit) in self.category_limits.iter().enumerate() {
+            check!(
+                !self.category_limits[..i].iter().any(|existing| {
+                    existing.merchant_category_code == limit.merchant_category_code
+                }),
+                PyraError::InvalidMerchantControls
+            );
+        }
+
+        for (i, merchant_id_hash) in self.merchant_id_hashes.iter().enumerate() {
+            check!(
+                !self.merchant_id_hashes[..i].contains(merchant_id_hash),
+                PyraError::InvalidMerchantControls
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn get_category_limit(&self, merchant_category_code: u16) -> Option<u64> {
+        self.category_limits
+            .iter()
+            .find(|limit| limit.merchant_category_code == merchant_category_code)
+            .map(|limit| limit.spend_limit_per_timeframe)
+    }
+
+    pub fn is_merchant_allowed(&self, merchant_id_hash: &[u8; MERCHANT_ID_HASH_SIZE]) -> bool {
+        let is_listed = self.merchant_id_hashes.contains(merchant_id_hash);
+        match self.merchant_list_mode {
+            MerchantListMode::Blocklist => !is_listed,
+            MerchantListMode::Allowlist => is_listed,
+        }
+    }
+
+    /// True if every spend allowed by these controls is also allowed by the current controls
+    pub fn is_tightening(&self, current: &MerchantControls) -> bool {
+        // Every existing category limit must be kept, at the same or a lower limit
+        let categories_tightened = current.category_limits.iter().all(|current_limit| {
+            self.get_category_limit(current_limit.merchant_category_code)
+                .is_some_and(|limit| limit <= current_limit.spend_limit_per_timeframe)
+        });
+
+        let merchants_tightened = match (current.merchant_list_mode, self.merchant_list_mode) {
+            // Blocked merchants must stay blocked
+            (MerchantListMode::Blocklist, MerchantListMode::Blocklist) => current
+                .merchant_id_hashes
+                .iter()
+                .all(|hash| self.merchant_id_hashes.contains(hash)),
+            // Allowed merchants can only be removed
+            (MerchantListMode::Allowlist, MerchantListMode::Allowlist) => self
+                .merchant_id_hashes
+                .iter()
+                .all(|hash| current.merchant_id_hashes.contains(hash)),
+            // An allowlist is stricter than a blocklist, once it doesn't allow a blocked merchant
+            (MerchantListMode::Blocklist, MerchantListMode::Allowlist) => !self
+                .merchant_id_hashes
+                .iter()
+                .any(|hash| current.merchant_id_hashes.contains(hash)),
+            (MerchantListMode::Allowlist, MerchantListMode::Blocklist) => false,
+        };
+
+        categories_tightened && merchants_tightened
+    }
+}
+
+/// Amount spent in a limited merchant category during the vault's current timeframe
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct MerchantCategorySpend {
+    pub merchant_category_code: u16,
+    pub spent_per_timeframe: u64,
+}
+
+impl Space for MerchantCategorySpend {
+    const INIT_SPACE: usize = U16_SIZE + U64_SIZE;
+}
+
+/// Merchant controls for a vault. Controls protect the vault's funds rather than the owner's permissions, so they are kept when the vault changes owner.
+#[account]
+pub struct VaultMerchantControls {
+    pub vault: Pubkey,
+    pub bump: u8,
+    pub controls: MerchantControls,
+
+    // Vault's next_timeframe_reset_timestamp when the category spend was last reset, so it resets with the vault
+    pub timeframe_reset_timestamp: u64,
+    pub category_spend: Vec<MerchantCategorySpend>,
+}
+
+impl Space for VaultMerchantControls {
+    const INIT_SPACE: usize = ANCHOR_DISCRIMINATOR
+        + PUBKEY_SIZE
+        + U8_SIZE
+        + MerchantControls::INIT_SPACE
+        + U64_SIZE
+        + VEC_LENGTH_PREFIX_SIZE
+        + (MerchantCategorySpend::INIT_SPACE * MAX_MERCHANT_CATEGORY_LIMITS);
+}
+
+impl VaultMerchantControls {
+    /// Replaces the controls, keeping the amount already spent in categories that are still limited
+    pub fn set_controls(&mut self, controls: MerchantControls) {
+        self.category_spend.retain(|spend| {
+            controls
+                .get_category_limit(spend.merchant_category_code)
+                .is_some()
+        });
+        self.controls = controls;
+    }
+
+    fn get_category_spent(&self, merchant_category_code: u16) -> u64 {
+        self.category_spend
+            .iter()
+            .find(|spend| spend.merchant_category_code == merchant_category_code)
+            .map_or(0, |spend| spend.spent_per_timeframe)
+    }
+
+    /// Checks the spend against the merchant controls and records it against its category limit
+    pub fn process_spend(
+        &mut self,
+        merchant_category_code: u16,
+        merchant_id_hash: &[u8; MERCHANT_ID_HASH_SIZE],
+        amount_usdc_base_units: u64,
+        vault_next_timeframe_reset_timestamp: u64,
+    ) -> Result<()> {
+        check!(
+            self.controls.is_merchant_allowed(merchant_id_hash),
+            PyraError::MerchantBlocked
+        );
+
+        if self.timeframe_reset_timestamp != vault_next_timeframe_reset_timestamp {
+            self.timeframe_reset_timestamp = vault_next_timeframe_reset_timestamp;
+            self.category_spend.clear();
+        }
+
+        let Some(category_limit) = self.controls.get_category_limit(merchant_category_code) else {
+            return Ok(());
+        };
+
+        let spent_per_timeframe = self
+            .get_category_spent(merchant_category_code)
+            .checked_add(amount_usdc_base_units)
+            .ok_or(PyraError::MathOverflow)?;
+
+        if category_limit < spent_per_timeframe {
+            let error_code = PyraError::InsufficientMerchantCategorySpendLimit;
+            anchor_lang::prelude::msg!(
+                "Error \"{}\" (category {}: {} < {}) thrown at {}:{}",
+                error_code,
+                merchant_category_code,
+                category_limit,
+                spent_per_timeframe,
+                file!(),
+                line!()
+            );
+            return Err(error_code.into());
+        }
+
+        match self
+            .category_spend
+            .iter_mut()
+            .find(|spend| spend.merchant_category_code == merchant_category_code)
+        {
+            Some(spend) => spend.spent_per_timeframe = spent_per_timeframe,
+            None => self.category_spend.push(MerchantCategorySpend {
+                merchant_category_code,
+                spent_per_timeframe,
+            }),
+        }
+
+        Ok(())
+    }
+
+    /// Returns a released amount to its category, unless the category spend has reset since it was counted
+    pub fn release_spend(
+        &mut self,
+        merchant_category_code: u16,
+        amount_usdc_base_units: u64,
+        vault_timeframe_reset_timestamp: u64,
+    ) {
+        if self.timeframe_reset_timestamp != vault_timeframe_reset_timestamp {
+            return;
+        }
+
+        if let Some(spend) = self
+            .category_spend
+            .iter_mut()
+            .find(|spend| spend.merchant_category_code == merchant_category_code)
+        {
+            spend.spent_per_timeframe = spend
+                .spent_per_timeframe
+                .saturating_sub(amount_usdc_base_units);
+        }
+    }
+}
@@ -1,7 +1,16 @@
 mod helpers;
 pub use helpers::*;
 
+mod calendar;
+pub use calendar::*;
+
 mod margin;
 pub use margin::*;
 
+mod spend_limits;
+pub use spend_limits::*;
+
+mod sweep;
+pub use sweep::*;
+
 mod macros;
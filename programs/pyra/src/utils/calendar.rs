@@ -0,0 +1,298 @@
+use crate::{
+    check,
+    config::{PyraError, MAX_SPEND_LIMIT_WINDOWS, MAX_UTC_OFFSET_SECONDS, SECONDS_PER_DAY},
+    state::{CalendarPeriod, SpendLimitMode, SpendLimitWindowParams},
+};
+use anchor_lang::prelude::*;
+
+// Days from 0000-03-01 to 1970-01-01, as the civil date conversions count years from March
+const DAYS_FROM_CIVIL_EPOCH_TO_UNIX_EPOCH: u64 = 719_468;
+const DAYS_PER_ERA: u64 = 146_097; // 400 years
+
+/// Calendar limits need every timeframe to be its calendar period's timeframe, other modes accept any timeframe
+pub fn validate_calendar_spend_limits(
+    spend_limit_mode: SpendLimitMode,
+    timeframe_in_seconds: u64,
+    calendar_period: CalendarPeriod,
+    spend_limit_windows: &[SpendLimitWindowParams],
+    utc_offset_seconds: i32,
+) -> Result<()> {
+    check!(
+        utc_offset_seconds.unsigned_abs() <= MAX_UTC_OFFSET_SECONDS,
+        PyraError::InvalidCalendarSpendLimits
+    );
+
+    if spend_limit_mode != SpendLimitMode::Calendar {
+        return Ok(());
+    }
+
+    let is_valid_timeframe = |timeframe_in_seconds: u64, calendar_period: CalendarPeriod| {
+        timeframe_in_seconds == calendar_period.get_timeframe_in_seconds()
+    };
+    check!(
+        is_valid_timeframe(timeframe_in_seconds, calendar_period)
+            && spend_limit_windows.iter().all(|window| {
+                is_valid_timeframe(window.timeframe_in_seconds, window.calendar_period)
+            }),
+        PyraError::InvalidCalendarSpendLimits
+    );
+
+    Ok(())
+}
+
+/// Calendar periods of the requested windows, in the same order as Vault::get_updated_spend_limit_windows
+pub fn get_spend_limit_window_calendar_periods(
+    spend_limit_windows: &[SpendLimitWindowParams],
+) -> [CalendarPeriod; MAX_SPEND_LIMIT_WINDOWS] {
+    let mut calendar_periods = [CalendarPeriod::Day; MAX_SPEND_LIMIT_WINDOWS];
+    for (calendar_period, window) in calendar_periods.iter_mut().zip(spend_limit_windows) {
+        *calendar_period = window.calendar_period;
+    }
+
+    calendar_periods
+}
+
+/// Start of the calendar period following the current timestamp, in the given UTC offset
+pub fn get_next_calendar_reset_timestamp(
+    current_timestamp: u64,
+    calendar_period: CalendarPeriod,
+    utc_offset_seconds: i32,
+) -> Result<u64> {
+    let local_timestamp = current_timestamp
+        .checked_add_signed(i64::from(utc_offset_seconds))
+        .ok_or(PyraError::MathOverflow)?;
+    let local_days = local_timestamp / SECONDS_PER_DAY;
+
+    let reset_days = match calendar_period {
+        CalendarPeriod::Day => local_days + 1,
+        CalendarPeriod::Week => {
+            // 1970-01-01 was a Thursday, 3 days after the start of its week
+            let days_since_monday = (local_days + 3) % 7;
+            local_days - days_since_monday + 7
+        }
+        CalendarPeriod::Month => {
+            let (year, month) = get_year_and_month(local_days);
+            match month {
+                12 => get_days_from_month_start(year + 1, 1),
+                _ => get_days_from_month_start(year, month + 1),
+            }
+        }
+    };
+
+    let local_reset_timestamp = reset_days
+        .checked_mul(SECONDS_PER_DAY)
+        .ok_or(PyraError::MathOverflow)?;
+
+    Ok(local_reset_timestamp
+        .checked_add_signed(-i64::from(utc_offset_seconds))
+        .ok_or(PyraError::MathOverflow)?)
+}
+
+/// Converts days since the Unix epoch to the civil year and month (1-12)
+fn get_year_and_month(days_since_unix_epoch: u64) -> (u64, u64) {
+    let days = days_since_unix_epoch + DAYS_FROM_CIVIL_EPOCH_TO_UNIX_EPOCH;
+    let era = days / DAYS_PER_ERA;
+    let day_of_era = days - era * DAYS_PER_ERA;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+
+    // Months are counted from March, so the leap day is the last day of the year
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    (year, month)
+}
+
+/// Converts the first day of a civil year and month (1-12) to days since the Unix epoch
+fn get_days_from_month_start(year: u64, month: u64) -> u64 {
+    let year = year - u64::from(month <= 2);
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let month_from_march = (month + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * DAYS_PER_ERA + day_of_era - DAYS_FROM_CIVIL_EPOCH_TO_UNIX_EPOCH
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SECONDS_PER_WEEK;
+
+    const UTC_MINUS_5: i32 = -18_000;
+    const UTC_PLUS_14: i32 = 50_400;
+
+    fn next_reset(current_timestamp: u64, calendar_period: CalendarPeriod, offset: i32) -> u64 {
+        get_next_calendar_reset_timestamp(current_timestamp, calendar_period, offset)
+            .expect("valid timestamp")
+    }
+
+    fn window(
+        timeframe_in_seconds: u64,
+        calendar_period: CalendarPeriod,
+    ) -> SpendLimitWindowParams {
+        SpendLimitWindowParams {
+            spend_limit_per_timeframe: 1_000,
+            timeframe_in_seconds,
+            next_timeframe_reset_timestamp: 0,
+            calendar_period,
+        }
+    }
+
+    #[test]
+    fn day_resets_at_the_next_midnight() {
+        // 2024-01-01 00:00 UTC resets at 2024-01-02 00:00 UTC
+        assert_eq!(
+            next_reset(1_704_067_200, CalendarPeriod::Day, 0),
+            1_704_153_600
+        );
+        assert_eq!(
+            next_reset(1_704_153_599, CalendarPeriod::Day, 0),
+            1_704_153_600
+        );
+    }
+
+    #[test]
+    fn week_resets_on_the_next_monday() {
+        // Sunday 2024-01-07 23:59:59 UTC and Monday 2024-01-01 00:00 UTC both reset at Monday 2024-01-08
+        assert_eq!(
+            next_reset(1_704_671_999, CalendarPeriod::Week, 0),
+            1_704_672_000
+        );
+        assert_eq!(
+            next_reset(1_704_067_200, CalendarPeriod::Week, 0),
+            1_704_672_000
+        );
+    }
+
+    #[test]
+    fn month_resets_at_the_next_month_start() {
+        // 2024-01-31 23:59:59 UTC resets at 2024-02-01
+        assert_eq!(
+            next_reset(1_706_745_599, CalendarPeriod::Month, 0),
+            1_706_745_600
+        );
+        // 2024-02-01 00:00 UTC resets at 2024-03-01
+        assert_eq!(
+            next_reset(1_706_745_600, CalendarPeriod::Month, 0),
+            1_709_251_200
+        );
+        // 2023-12-15 UTC resets at 2024-01-01
+        assert_eq!(
+            next_reset(1_702_598_400, CalendarPeriod::Month, 0),
+            1_704_067_200
+        );
+    }
+
+    #[test]
+    fn month_resets_after_the_leap_day() {
+        // 2024-02-29 12:00 UTC resets at 2024-03-01
+        assert_eq!(
+            next_reset(1_709_208_000, CalendarPeriod::Month, 0),
+            1_709_251_200
+        );
+        // 2023-02-28 12:00 UTC resets at 2023-03-01
+        assert_eq!(
+            next_reset(1_677_585_600, CalendarPeriod::Month, 0),
+            1_677_628_800
+        );
+        // 2000 is a leap year, 2100 isn't
+        assert_eq!(
+            next_reset(951_782_400, CalendarPeriod::Month, 0),
+            951_868_800
+        );
+        assert_eq!(
+            next_reset(4_107_499_200, CalendarPeriod::Month, 0),
+            4_107_542_400
+        );
+    }
+
+    #[test]
+    fn negative_offset_resets_at_local_midnight() {
+        // 2024-03-01 03:00 UTC is still 2024-02-29 in UTC-5, so it resets at 2024-03-01 05:00 UTC
+        assert_eq!(
+            next_reset(1_709_262_000, CalendarPeriod::Month, UTC_MINUS_5),
+            1_709_269_200
+        );
+        assert_eq!(
+            next_reset(1_709_262_000, CalendarPeriod::Day, UTC_MINUS_5),
+            1_709_269_200
+        );
+    }
+
+    #[test]
+    fn positive_offset_resets_at_local_midnight() {
+        // 2024-01-31 11:00 UTC is already 2024-02-01 in UTC+14, so it resets at 2024-03-01 00:00 UTC+14
+        assert_eq!(
+            next_reset(1_706_698_800, CalendarPeriod::Month, UTC_PLUS_14),
+            1_709_251_200 - 50_400
+        );
+    }
+
+    #[test]
+    fn calendar_timeframes_must_match_their_calendar_period() {
+        validate_calendar_spend_limits(
+            SpendLimitMode::Calendar,
+            SECONDS_PER_DAY,
+            CalendarPeriod::Day,
+            &[
+                window(SECONDS_PER_WEEK, CalendarPeriod::Week),
+                window(
+                    CalendarPeriod::Month.get_timeframe_in_seconds(),
+                    CalendarPeriod::Month,
+                ),
+            ],
+            UTC_MINUS_5,
+        )
+        .expect("valid calendar limits");
+
+        let invalid_limits = [
+            (SECONDS_PER_WEEK, CalendarPeriod::Day, vec![], 0),
+            (2_592_000, CalendarPeriod::Month, vec![], 0),
+            (
+                SECONDS_PER_DAY,
+                CalendarPeriod::Day,
+                vec![window(SECONDS_PER_DAY, CalendarPeriod::Week)],
+                0,
+            ),
+            (
+                SECONDS_PER_DAY,
+                CalendarPeriod::Day,
+                vec![],
+                UTC_PLUS_14 + 1,
+            ),
+        ];
+        for (timeframe_in_seconds, calendar_period, windows, utc_offset_seconds) in invalid_limits {
+            assert_eq!(
+                validate_calendar_spend_limits(
+                    SpendLimitMode::Calendar,
+                    timeframe_in_seconds,
+                    calendar_period,
+                    &windows,
+                    utc_offset_seconds,
+                )
+                .expect_err("invalid calendar limits"),
+                PyraError::InvalidCalendarSpendLimits.into()
+            );
+        }
+    }
+
+    #[test]
+    fn other_modes_ignore_calendar_periods() {
+        validate_calendar_spend_limits(
+            SpendLimitMode::Fixed,
+            3_600,
+            CalendarPeriod::Month,
+            &[window(7_200, CalendarPeriod::Day)],
+            0,
+        )
+        .expect("valid fixed limits");
+    }
+}
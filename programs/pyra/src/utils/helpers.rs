@@ -1,28 +1,137 @@
 use crate::{
     check,
-    config::{
-        DriftMarket, PyraError, ANCHOR_DISCRIMINATOR, DRIFT_MARKETS, TIME_LOCK_RENT_PAYER_SEEDS,
+    config::{PyraError, ANCHOR_DISCRIMINATOR, TIME_LOCK_RENT_PAYER_SEEDS},
+    state::{
+        DelegatePermission, DriftMarket, DriftMarketRegistry, SettleAndCloseOrder,
+        SpendLimitWindowParams, SpendVelocityLimits, TimeLock, TimeLockRentPayer, TimeLocked,
+        Vault, VaultDelegate, VaultDelegates,
     },
-    state::{TimeLock, TimeLocked},
 };
 use anchor_lang::{prelude::*, Discriminator};
 use anchor_spl::{
     associated_token::get_associated_token_address_with_program_id,
     token_interface::{TokenAccount, TokenInterface},
 };
+use pyth_solana_receiver_sdk::price_update::{Price, PriceUpdateV2};
 use solana_program::{
     instruction::{get_stack_height, Instruction},
     program::{invoke, invoke_signed},
     system_instruction, system_program,
 };
 
-pub fn get_drift_market(market_index: u16) -> Result<&'static DriftMarket> {
-    Ok(DRIFT_MARKETS
+pub fn get_drift_market(registry: &DriftMarketRegistry, market_index: u16) -> Result<&DriftMarket> {
+    Ok(registry
+        .markets
         .iter()
         .find(|market| market.market_index == market_index)
         .ok_or(PyraError::InvalidMarketIndex)?)
 }
 
+/// Validates the authority is the vault owner or a delegate with the permission. Returns the delegate if it's not the owner.
+pub fn validate_vault_authority<'a>(
+    vault: &Vault,
+    authority: &Pubkey,
+    vault_delegates: &'a Option<Box<Account<'_, VaultDelegates>>>,
+    permission: DelegatePermission,
+) -> Result<Option<&'a VaultDelegate>> {
+    if vault.owner.eq(authority) {
+        return Ok(None);
+    }
+
+    let delegate = vault_delegates
+        .as_ref()
+        .and_then(|vault_delegates| vault_delegates.get_delegate(&vault.owner, authority))
+        .filter(|delegate| delegate.has_permission(permission))
+        .ok_or(PyraError::InvalidVaultAuthority)?;
+
+    Ok(Some(delegate))
+}
+
+/// True if a settle and close order from the vault's current owner was passed. Settle repays don't wait for the
+/// order's time lock, so loans can be repaid before it's released.
+pub fn is_settle_repay(
+    vault: &Account<'_, Vault>,
+    settle_and_close_order: &Option<Box<Account<'_, SettleAndCloseOrder>>>,
+) -> Result<bool> {
+    let settle_and_close_order = match settle_and_close_order.as_ref() {
+        Some(settle_and_close_order) => settle_and_close_order,
+        None => return Ok(false),
+    };
+
+    check!(
+        settle_and_close_order.vault.eq(&vault.key()),
+        PyraError::InvalidSettleAndCloseOrder
+    );
+    check!(
+        settle_and_close_order.time_lock.owner_epoch == vault.owner_epoch,
+        PyraError::StaleOwnerEpoch
+    );
+    check!(
+        settle_and_close_order.time_lock.owner.eq(&vault.owner),
+        PyraError::InvalidTimeLockOwner
+    );
+
+    Ok(true)
+}
+
+/// Delegates can only set spend limits up to the maximums granted by the owner
+pub fn validate_delegate_spend_limits(
+    delegate: Option<&VaultDelegate>,
+    spend_limit_per_transaction: u64,
+    spend_limit_per_timeframe: u64,
+    spend_limit_windows: &[SpendLimitWindowParams],
+) -> Result<()> {
+    if let Some(delegate) = delegate {
+        check!(
+            spend_limit_per_transaction <= delegate.max_spend_limit_per_transaction,
+            PyraError::DelegateSpendLimitExceeded
+        );
+        check!(
+            spend_limit_per_timeframe <= delegate.max_spend_limit_per_timeframe,
+            PyraError::DelegateSpendLimitExceeded
+        );
+        check!(
+            spend_limit_windows
+                .iter()
+                .all(|window| window.spend_limit_per_timeframe
+                    <= delegate.max_spend_limit_per_timeframe),
+            PyraError::DelegateSpendLimitExceeded
+        );
+    }
+
+    Ok(())
+}
+
+/// Delegates can't change the vault's timeframes or reset timestamps, or loosen its velocity limits, as either would
+/// reset or bypass what's already been spent
+pub fn validate_delegate_spend_limit_timing(
+    delegate: Option<&VaultDelegate>,
+    vault: &Vault,
+    timeframe_in_seconds: u64,
+    next_timeframe_reset_timestamp: u64,
+    spend_limit_windows: &[SpendLimitWindowParams],
+    spend_velocity_limits: &SpendVelocityLimits,
+) -> Result<()> {
+    if delegate.is_some() {
+        check!(
+            vault.keeps_spend_limit_timing(
+                timeframe_in_seconds,
+                next_timeframe_reset_timestamp,
+                spend_limit_windows
+            ),
+            PyraError::IllegalDelegateSpendLimitChange
+        );
+        check!(
+            vault
+                .spend_velocity_limits
+                .is_loosening(spend_velocity_limits),
+            PyraError::IllegalDelegateSpendLimitChange
+        );
+    }
+
+    Ok(())
+}
+
 pub fn validate_account_fresh(account: &AccountInfo) -> Result<()> {
     check!(
         account.owner.key().eq(&system_program::ID),
@@ -80,6 +189,91 @@ pub fn normalize_price_exponents(
     }
 }
 
+/// One side of a swap, valued with its market's Pyth price
+pub struct SwapLeg<'a> {
+    pub amount_base_units: u64,
+    pub market: &'a DriftMarket,
+    pub price_update: &'a PriceUpdateV2,
+}
+
+/// Takes the deposit and withdraw amounts, their prices, and validates that the withdraw amount is within slippage of the deposit amount. Returns the oracle prices used.
+#[inline(never)]
+pub fn validate_swap_prices(
+    deposit: SwapLeg,
+    withdraw: SwapLeg,
+    pyth_max_price_age_seconds: u64,
+    max_slippage_bps: u16,
+) -> Result<(Price, Price)> {
+    // Get the deposit price, assuming worst case of lowest end of confidence interval
+    let deposit_price = deposit.price_update.get_price_no_older_than(
+        &Clock::get()?,
+        pyth_max_price_age_seconds,
+        &deposit.market.pyth_feed,
+    )?;
+    check!(deposit_price.price > 0, PyraError::NegativeOraclePrice);
+    let deposit_lowest_price = u64::try_from(deposit_price.price)
+        .map_err(|_| PyraError::MathOverflow)?
+        .checked_sub(deposit_price.conf)
+        .ok_or(PyraError::NegativeOraclePrice)?;
+
+    // Get the withdraw price, assuming worst case of highest end of confidence interval
+    let withdraw_price = withdraw.price_update.get_price_no_older_than(
+        &Clock::get()?,
+        pyth_max_price_age_seconds,
+        &withdraw.market.pyth_feed,
+    )?;
+    check!(withdraw_price.price > 0, PyraError::NegativeOraclePrice);
+    let withdraw_highest_price = u64::try_from(withdraw_price.price)
+        .map_err(|_| PyraError::MathOverflow)?
+        .checked_add(withdraw_price.conf)
+        .ok_or(PyraError::MathOverflow)?;
+
+    // Normalize prices to the same exponents
+    let (deposit_lowest_price_normalized, withdraw_highest_price_normalized) =
+        normalize_price_exponents(
+            deposit_lowest_price as u128,
+            deposit_price.exponent,
+            withdraw_highest_price as u128,
+            withdraw_price.exponent,
+        )?;
+
+    // Normalize amounts to the same decimals (base units per token)
+    let deposit_amount_normalized: u128 = (deposit.amount_base_units as u128)
+        .checked_mul(withdraw.market.base_units_per_token as u128)
+        .ok_or(PyraError::MathOverflow)?;
+    let withdraw_amount_normalized: u128 = (withdraw.amount_base_units as u128)
+        .checked_mul(deposit.market.base_units_per_token as u128)
+        .ok_or(PyraError::MathOverflow)?;
+
+    // Calculate values
+    let deposit_value: u128 = deposit_amount_normalized
+        .checked_mul(deposit_lowest_price_normalized)
+        .ok_or(PyraError::MathOverflow)?;
+    let withdraw_value: u128 = withdraw_amount_normalized
+        .checked_mul(withdraw_highest_price_normalized)
+        .ok_or(PyraError::MathOverflow)?;
+
+    // Allow for slippage, using integar multiplication to prevent floating point errors
+    let slippage_multiplier_deposit: u128 = 100 * 100; // 100% x 100bps
+    let slippage_multiplier_withdraw: u128 = slippage_multiplier_deposit
+        .checked_sub(max_slippage_bps as u128)
+        .ok_or(PyraError::MathOverflow)?;
+
+    let deposit_slippage_check_value = deposit_value
+        .checked_mul(slippage_multiplier_deposit)
+        .ok_or(PyraError::MathOverflow)?;
+    let withdraw_slippage_check_value = withdraw_value
+        .checked_mul(slippage_multiplier_withdraw)
+        .ok_or(PyraError::MathOverflow)?;
+
+    check!(
+        deposit_slippage_check_value >= withdraw_slippage_check_value,
+        PyraError::MaxSlippageExceeded
+    );
+
+    Ok((deposit_price, withdraw_price))
+}
+
 pub fn validate_start_collateral_repay_ix(
     current_instruction: &Instruction,
     start_collateral_repay: &Instruction,
@@ -110,35 +304,18 @@ pub fn validate_start_collateral_repay_ix(
     Ok(())
 }
 
-pub fn evm_address_to_solana(ethereum_address: &str) -> Result<Pubkey> {
-    // Used for Circle bridge
-    let cleaned_address = ethereum_address.trim_start_matches("0x");
-    check!(cleaned_address.len() == 40, PyraError::InvalidEvmAddress);
-
-    let mut bytes = [0u8; 32];
-    for i in 0..20 {
-        let pos = i * 2;
-        let byte_str = &cleaned_address[pos..pos + 2];
-        bytes[i + 12] =
-            u8::from_str_radix(byte_str, 16).map_err(|_| PyraError::InvalidEvmAddress)?;
-    }
-
-    Ok(Pubkey::new_from_array(bytes))
-}
-
+/// Validates the time lock rent payer, returning its data
 fn validate_time_lock_rent_payer<'info>(
     time_lock_rent_payer: &AccountInfo<'info>,
-) -> Result<(&'info [u8], u8)> {
-    let time_lock_rent_payer_seeds = TIME_LOCK_RENT_PAYER_SEEDS;
-    let (expected_pda, bump) =
-        Pubkey::find_program_address(&[time_lock_rent_payer_seeds], &crate::ID);
+) -> Result<TimeLockRentPayer> {
+    let (expected_pda, _) = Pubkey::find_program_address(&[TIME_LOCK_RENT_PAYER_SEEDS], &crate::ID);
 
     check!(
         time_lock_rent_payer.key().eq(&expected_pda),
         PyraError::InvalidTimeLockRentPayer
     );
 
-    Ok((time_lock_rent_payer_seeds, bump))
+    TimeLockRentPayer::load(time_lock_rent_payer)
 }
 
 pub fn allocate_time_lock_program_payer<'info>(
@@ -149,38 +326,77 @@ pub fn allocate_time_lock_program_payer<'info>(
 ) -> Result<()> {
     validate_account_fresh(time_lock)?;
 
-    let (time_lock_rent_payer_seeds, bump) = validate_time_lock_rent_payer(time_lock_rent_payer)?;
-
-    let seeds_with_bump = &[time_lock_rent_payer_seeds, &[bump]];
-    let signer_seeds = &[&seeds_with_bump[..]];
-
     let rent = Rent::get()?;
     let required_lamports = rent.minimum_balance(space);
 
-    // Transfer required lamports
-    invoke_signed(
-        &system_instruction::transfer(
-            &time_lock_rent_payer.key(),
-            &time_lock.key(),
-            required_lamports,
-        ),
-        &[
-            time_lock_rent_payer.to_account_info(),
-            time_lock.to_account_info(),
-            system_program.to_account_info(),
-        ],
-        signer_seeds,
+    fund_time_lock_program_payer(time_lock_rent_payer, time_lock, required_lamports)?;
+
+    allocate_program_account(
+        time_lock,
+        system_program,
+        u64::try_from(space).map_err(|_| PyraError::MathOverflow)?,
+        &[],
     )?;
 
-    allocate_time_lock(
+    Ok(())
+}
+
+/// Allocates a time lock at a PDA, so only one can be open per seeds. Lamports already sent to the PDA count
+/// towards its rent, so they can't block it being allocated.
+pub fn allocate_pda_time_lock_program_payer<'info>(
+    time_lock_rent_payer: &AccountInfo<'info>,
+    time_lock: &AccountInfo<'info>,
+    time_lock_seeds: &[&[u8]],
+    system_program: &Program<'info, System>,
+    space: usize,
+) -> Result<()> {
+    check!(
+        time_lock.owner.key().eq(&system_program::ID),
+        PyraError::AccountAlreadyInitialized
+    );
+    check!(
+        time_lock.data_is_empty(),
+        PyraError::AccountAlreadyInitialized
+    );
+
+    let rent = Rent::get()?;
+    let required_lamports = rent
+        .minimum_balance(space)
+        .saturating_sub(time_lock.lamports());
+
+    if required_lamports > 0 {
+        fund_time_lock_program_payer(time_lock_rent_payer, time_lock, required_lamports)?;
+    }
+
+    allocate_program_account(
         time_lock,
         system_program,
         u64::try_from(space).map_err(|_| PyraError::MathOverflow)?,
+        &[time_lock_seeds],
     )?;
 
     Ok(())
 }
 
+fn fund_time_lock_program_payer<'info>(
+    time_lock_rent_payer: &AccountInfo<'info>,
+    time_lock: &AccountInfo<'info>,
+    lamports: u64,
+) -> Result<()> {
+    let mut time_lock_rent_payer_data = validate_time_lock_rent_payer(time_lock_rent_payer)?;
+    time_lock_rent_payer_data.live_time_lock_rent = time_lock_rent_payer_data
+        .live_time_lock_rent
+        .checked_add(lamports)
+        .ok_or(PyraError::MathOverflow)?;
+    time_lock_rent_payer_data.save(time_lock_rent_payer)?;
+
+    // Transfer required lamports, the rent payer holds data so it's owned by the program
+    time_lock_rent_payer.sub_lamports(lamports)?;
+    time_lock.add_lamports(lamports)?;
+
+    Ok(())
+}
+
 pub fn allocate_time_lock_owner_payer<'info>(
     owner: &Signer<'info>,
     time_lock: &Signer<'info>,
@@ -201,36 +417,78 @@ pub fn allocate_time_lock_owner_payer<'info>(
         ],
     )?;
 
-    allocate_time_lock(
+    allocate_program_account(
         time_lock,
         system_program,
         u64::try_from(space).map_err(|_| PyraError::MathOverflow)?,
+        &[],
     )?;
 
     Ok(())
 }
 
-fn allocate_time_lock<'info>(
-    time_lock: &Signer<'info>,
+fn allocate_program_account<'info>(
+    account: &AccountInfo<'info>,
     system_program: &Program<'info, System>,
     space: u64,
+    signer_seeds: &[&[&[u8]]],
 ) -> Result<()> {
     // Allocate data
-    invoke(
-        &system_instruction::allocate(&time_lock.key(), space),
-        &[
-            time_lock.to_account_info(),
-            system_program.to_account_info(),
-        ],
+    invoke_signed(
+        &system_instruction::allocate(&account.key(), space),
+        &[account.to_account_info(), system_program.to_account_info()],
+        signer_seeds,
     )?;
 
     // Change ownership to program
-    invoke(
-        &system_instruction::assign(&time_lock.key(), &crate::ID),
-        &[
-            time_lock.to_account_info(),
-            system_program.to_account_info(),
-        ],
+    invoke_signed(
+        &system_instruction::assign(&account.key(), &crate::ID),
+        &[account.to_account_info(), system_program.to_account_info()],
+        signer_seeds,
+    )?;
+
+    Ok(())
+}
+
+/// Creates a program account at a PDA, paid for by the payer. Lamports already sent to the PDA count towards its
+/// rent, so they can't block it being created.
+pub fn create_pda_account<'info>(
+    payer: &Signer<'info>,
+    account: &AccountInfo<'info>,
+    account_seeds: &[&[u8]],
+    system_program: &Program<'info, System>,
+    space: usize,
+) -> Result<()> {
+    check!(
+        account.owner.key().eq(&system_program::ID),
+        PyraError::AccountAlreadyInitialized
+    );
+    check!(
+        account.data_is_empty(),
+        PyraError::AccountAlreadyInitialized
+    );
+
+    let rent = Rent::get()?;
+    let required_lamports = rent
+        .minimum_balance(space)
+        .saturating_sub(account.lamports());
+
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(&payer.key(), &account.key(), required_lamports),
+            &[
+                payer.to_account_info(),
+                account.to_account_info(),
+                system_program.to_account_info(),
+            ],
+        )?;
+    }
+
+    allocate_program_account(
+        account,
+        system_program,
+        u64::try_from(space).map_err(|_| PyraError::MathOverflow)?,
+        &[account_seeds],
     )?;
 
     Ok(())
@@ -248,6 +506,16 @@ pub fn validate_time_lock(owner: &Pubkey, time_lock: &TimeLock) -> Result<()> {
     Ok(())
 }
 
+/// Validates a vault order's time lock, which is only valid for the owner_epoch it was created in
+pub fn validate_vault_time_lock(vault: &Vault, time_lock: &TimeLock) -> Result<()> {
+    check!(
+        time_lock.owner_epoch == vault.owner_epoch,
+        PyraError::StaleOwnerEpoch
+    );
+
+    validate_time_lock(&vault.owner, time_lock)
+}
+
 pub fn close_time_lock<'info, T>(
     time_lock: &Account<'info, T>,
     time_lock_rent_payer: &AccountInfo<'info>,
@@ -261,20 +529,33 @@ where
             PyraError::InvalidTimeLockRentPayer
         );
     } else {
-        validate_time_lock_rent_payer(time_lock_rent_payer)?;
+        // Saturates, as orders opened before the rent payer tracked its rent weren't counted
+        let mut time_lock_rent_payer_data = validate_time_lock_rent_payer(time_lock_rent_payer)?;
+        time_lock_rent_payer_data.live_time_lock_rent = time_lock_rent_payer_data
+            .live_time_lock_rent
+            .saturating_sub(time_lock.to_account_info().lamports());
+        time_lock_rent_payer_data.save(time_lock_rent_payer)?;
     };
 
-    // Transfer all rent to payer
-    let time_lock_balance = time_lock.to_account_info().lamports();
-    **time_lock_rent_payer.lamports.borrow_mut() = time_lock_rent_payer
+    close_program_account(&time_lock.to_account_info(), time_lock_rent_payer)
+}
+
+/// Closes an account owned by the program, sending all its rent to the destination
+pub fn close_program_account<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    // Transfer all rent to the destination
+    let account_balance = account.lamports();
+    **destination.lamports.borrow_mut() = destination
         .lamports()
-        .checked_add(time_lock_balance)
+        .checked_add(account_balance)
         .ok_or(PyraError::MathOverflow)?;
-    **time_lock.to_account_info().lamports.borrow_mut() = 0;
+    **account.lamports.borrow_mut() = 0;
 
     // Clear data and owner
-    time_lock.to_account_info().data.borrow_mut().fill(0);
-    time_lock.to_account_info().owner = &system_program::ID;
+    account.data.borrow_mut().fill(0);
+    account.assign(&system_program::ID);
 
     Ok(())
 }
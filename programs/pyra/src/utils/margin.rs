@@ -2,11 +2,16 @@ use crate::config::PyraError;
 use anchor_lang::prelude::*;
 use drift::{
     instructions::optional_accounts::{load_maps, AccountMaps},
-    math::margin::{
-        calculate_margin_requirement_and_total_collateral_and_liability_info, MarginRequirementType,
+    math::{
+        margin::{
+            calculate_margin_requirement_and_total_collateral_and_liability_info,
+            MarginRequirementType,
+        },
+        spot_balance::get_token_amount,
     },
     state::{
         margin_calculation::{MarginCalculation, MarginContext},
+        spot_market::{SpotBalanceType, SpotMarket},
         spot_market_map::get_writable_spot_market_set_from_many,
         state::State,
         user::User,
@@ -100,3 +105,45 @@ fn calculate_pyra_account_health(initial_margin_calculation: MarginCalculation)
     let health_u8 = u8::try_from(health).map_err(|_| PyraError::MathOverflow)?;
     Ok(health_u8)
 }
+
+/// True if the Drift user has an outstanding borrow in any spot market
+pub fn has_spot_borrows(drift_user: &User) -> bool {
+    drift_user.spot_positions.iter().any(|position| {
+        position.balance_type == SpotBalanceType::Borrow && position.scaled_balance != 0
+    })
+}
+
+/// Tokens the Drift user has borrowed from the spot market, 0 if its position in the market is a deposit or empty.
+/// The spot market is read from the remaining accounts passed to Drift.
+pub fn get_spot_borrow_base_units<'info>(
+    drift_user: &User,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    market_index: u16,
+) -> Result<u64> {
+    let position = match drift_user.get_spot_position(market_index) {
+        Ok(position) => position,
+        Err(_) => return Ok(0),
+    };
+    if position.balance_type != SpotBalanceType::Borrow || position.scaled_balance == 0 {
+        return Ok(0);
+    }
+
+    let spot_market_loader = remaining_accounts
+        .iter()
+        .filter_map(|account| AccountLoader::<SpotMarket>::try_from(account).ok())
+        .find(|loader| {
+            loader
+                .load()
+                .is_ok_and(|spot_market| spot_market.market_index == market_index)
+        })
+        .ok_or(PyraError::InvalidSpotMarket)?;
+    let spot_market = spot_market_loader.load()?;
+
+    let borrow_base_units = get_token_amount(
+        u128::from(position.scaled_balance),
+        &spot_market,
+        &SpotBalanceType::Borrow,
+    )?;
+
+    Ok(u64::try_from(borrow_base_units).map_err(|_| PyraError::MathOverflow)?)
+}
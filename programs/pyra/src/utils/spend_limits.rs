@@ -0,0 +1,310 @@
+use crate::{
+    check,
+    config::{PyraError, MERCHANT_ID_HASH_SIZE},
+    state::{CalendarPeriod, SpendHold, SpendLimitMode, Vault, VaultMerchantControls},
+    utils::get_next_calendar_reset_timestamp,
+};
+use anchor_lang::prelude::*;
+
+/// Checks the spend against the vault's spend limits and merchant controls, and deducts it from them.
+/// Returns the amount drawn from the spend limit boost.
+pub fn process_spend_limits(
+    vault: &mut Vault,
+    vault_merchant_controls_info: &AccountInfo,
+    amount_usdc_base_units: u64,
+    merchant_category_code: u16,
+    merchant_id_hash: &[u8; MERCHANT_ID_HASH_SIZE],
+) -> Result<u64> {
+    let current_timestamp_signed = Clock::get()?.unix_timestamp;
+    check!(current_timestamp_signed > 0, PyraError::InvalidTimestamp);
+    let current_timestamp =
+        u64::try_from(current_timestamp_signed).map_err(|_| PyraError::MathOverflow)?;
+
+    // Expired boosts are ignored, so the vault falls back to its base limits without another transaction
+    let spend_limit_boost = vault.spend_limit_boost;
+    let (additional_spend_limit_per_transaction, available_boost) =
+        if spend_limit_boost.is_active(current_timestamp) {
+            (
+                spend_limit_boost.additional_spend_limit_per_transaction,
+                spend_limit_boost.remaining_additional_spend_limit,
+            )
+        } else {
+            (0, 0)
+        };
+
+    // Check transaction spend limit and timeframe
+    let spend_limit_per_transaction = vault
+        .spend_limit_per_transaction
+        .saturating_add(additional_spend_limit_per_transaction);
+    if spend_limit_per_transaction < amount_usdc_base_units {
+        let error_code = PyraError::InsufficientTransactionSpendLimit;
+        anchor_lang::prelude::msg!(
+            "Error \"{}\" ({} < {}) thrown at {}:{}",
+            error_code,
+            spend_limit_per_transaction,
+            amount_usdc_base_units,
+            file!(),
+            line!()
+        );
+        return Err(error_code.into());
+    }
+
+    if vault.timeframe_in_seconds == 0 {
+        let error_code = PyraError::InsufficientTimeframeSpendLimit;
+        anchor_lang::prelude::msg!(
+            "Error \"{}\" (timeframe_in_seconds == 0) thrown at {}:{}",
+            error_code,
+            file!(),
+            line!()
+        );
+        return Err(error_code.into());
+    }
+
+    let is_calendar = vault.spend_limit_mode == SpendLimitMode::Calendar;
+
+    // If the timeframe has elapsed, incrememt it and reset spend limit
+    if let Some(next_timeframe_reset_timestamp) = get_rolled_over_reset_timestamp(
+        vault.next_timeframe_reset_timestamp,
+        vault.timeframe_in_seconds,
+        current_timestamp,
+        is_calendar.then_some(vault.calendar_period),
+        vault.utc_offset_seconds,
+    )? {
+        vault.next_timeframe_reset_timestamp = next_timeframe_reset_timestamp;
+        vault.remaining_spend_limit_per_timeframe = vault.spend_limit_per_timeframe;
+        vault.spend_count_per_timeframe = 0;
+    }
+
+    // Check velocity limits, which cap how often the card can spend regardless of amount
+    let spend_velocity_limits = vault.spend_velocity_limits;
+    let last_spend_timestamp = vault.recent_spends.get_last_spend_timestamp();
+    let next_spend_timestamp = last_spend_timestamp
+        .checked_add(u64::from(spend_velocity_limits.min_seconds_between_spends))
+        .ok_or(PyraError::MathOverflow)?;
+    if current_timestamp < next_spend_timestamp {
+        let error_code = PyraError::SpendIntervalNotElapsed;
+        anchor_lang::prelude::msg!(
+            "Error \"{}\" ({} < {}) thrown at {}:{}",
+            error_code,
+            current_timestamp,
+            next_spend_timestamp,
+            file!(),
+            line!()
+        );
+        return Err(error_code.into());
+    }
+
+    if spend_velocity_limits.max_spends_per_timeframe != 0
+        && vault.spend_count_per_timeframe >= spend_velocity_limits.max_spends_per_timeframe
+    {
+        let error_code = PyraError::InsufficientSpendCountLimit;
+        anchor_lang::prelude::msg!(
+            "Error \"{}\" ({} >= {}) thrown at {}:{}",
+            error_code,
+            vault.spend_count_per_timeframe,
+            spend_velocity_limits.max_spends_per_timeframe,
+            file!(),
+            line!()
+        );
+        return Err(error_code.into());
+    }
+
+    // Saturates rather than failing, as the count is unbounded while max_spends_per_timeframe is 0
+    vault.spend_count_per_timeframe = vault.spend_count_per_timeframe.saturating_add(1);
+
+    // Rolling limits are recalculated from the recent spends, reset timestamps still roll over for merchant controls
+    let is_rolling = vault.spend_limit_mode == SpendLimitMode::Rolling;
+    if is_rolling {
+        let spent_within_timeframe = vault
+            .recent_spends
+            .get_spent_within(vault.timeframe_in_seconds, current_timestamp);
+        vault.remaining_spend_limit_per_timeframe = vault
+            .spend_limit_per_timeframe
+            .saturating_sub(spent_within_timeframe);
+    }
+
+    // Check remaining spend limit, an active boost covers the part of the spend beyond it
+    let available_spend_limit = vault
+        .remaining_spend_limit_per_timeframe
+        .saturating_add(available_boost);
+    if available_spend_limit < amount_usdc_base_units {
+        let error_code = PyraError::InsufficientTimeframeSpendLimit;
+        anchor_lang::prelude::msg!(
+            "Error \"{}\" ({} < {}) thrown at {}:{}",
+            error_code,
+            available_spend_limit,
+            amount_usdc_base_units,
+            file!(),
+            line!()
+        );
+        return Err(error_code.into());
+    }
+
+    // Vaults without merchant controls have no account at the PDA
+    if !vault_merchant_controls_info.data_is_empty() {
+        check!(
+            vault_merchant_controls_info.owner.eq(&crate::id()),
+            PyraError::InvalidMerchantControls
+        );
+        let mut vault_merchant_controls = VaultMerchantControls::try_deserialize(
+            &mut &vault_merchant_controls_info.try_borrow_data()?[..],
+        )?;
+        vault_merchant_controls.process_spend(
+            merchant_category_code,
+            merchant_id_hash,
+            amount_usdc_base_units,
+            vault.next_timeframe_reset_timestamp,
+        )?;
+        vault_merchant_controls
+            .try_serialize(&mut &mut vault_merchant_controls_info.try_borrow_mut_data()?[..])?;
+    }
+
+    // Adjust remaining spend limit, the boost is drawn down by the largest shortfall across the timeframes
+    let mut boost_used =
+        amount_usdc_base_units.saturating_sub(vault.remaining_spend_limit_per_timeframe);
+    vault.remaining_spend_limit_per_timeframe = vault
+        .remaining_spend_limit_per_timeframe
+        .saturating_sub(amount_usdc_base_units);
+
+    // Additional windows are rolled over and checked independently of the primary timeframe
+    for (window, calendar_period) in vault
+        .spend_limit_windows
+        .iter_mut()
+        .zip(vault.spend_limit_window_calendar_periods)
+        .filter(|(window, _)| window.is_active())
+    {
+        if let Some(next_timeframe_reset_timestamp) = get_rolled_over_reset_timestamp(
+            window.next_timeframe_reset_timestamp,
+            window.timeframe_in_seconds,
+            current_timestamp,
+            is_calendar.then_some(calendar_period),
+            vault.utc_offset_seconds,
+        )? {
+            window.next_timeframe_reset_timestamp = next_timeframe_reset_timestamp;
+            window.remaining_spend_limit_per_timeframe = window.spend_limit_per_timeframe;
+        }
+
+        if is_rolling {
+            window.remaining_spend_limit_per_timeframe =
+                window.spend_limit_per_timeframe.saturating_sub(
+                    vault
+                        .recent_spends
+                        .get_spent_within(window.timeframe_in_seconds, current_timestamp),
+                );
+        }
+
+        let available_spend_limit = window
+            .remaining_spend_limit_per_timeframe
+            .saturating_add(available_boost);
+        if available_spend_limit < amount_usdc_base_units {
+            let error_code = PyraError::InsufficientTimeframeSpendLimit;
+            anchor_lang::prelude::msg!(
+                "Error \"{}\" ({} < {} in {}s window) thrown at {}:{}",
+                error_code,
+                available_spend_limit,
+                amount_usdc_base_units,
+                window.timeframe_in_seconds,
+                file!(),
+                line!()
+            );
+            return Err(error_code.into());
+        }
+
+        boost_used = boost_used
+            .max(amount_usdc_base_units.saturating_sub(window.remaining_spend_limit_per_timeframe));
+        window.remaining_spend_limit_per_timeframe = window
+            .remaining_spend_limit_per_timeframe
+            .saturating_sub(amount_usdc_base_units);
+    }
+
+    if boost_used > 0 {
+        vault.spend_limit_boost.remaining_additional_spend_limit = vault
+            .spend_limit_boost
+            .remaining_additional_spend_limit
+            .checked_sub(boost_used)
+            .ok_or(PyraError::InsufficientTimeframeSpendLimit)?;
+    }
+
+    let longest_timeframe_in_seconds = vault.get_longest_timeframe_in_seconds();
+    vault.recent_spends.record_spend(
+        amount_usdc_base_units,
+        current_timestamp,
+        longest_timeframe_in_seconds,
+    )?;
+
+    Ok(boost_used)
+}
+
+/// Releases a spend hold back to the vault's spend limits and merchant controls. Returns the amount restored to the
+/// primary timeframe.
+pub fn release_spend_hold(
+    spend_hold: &mut SpendHold,
+    vault: &mut Vault,
+    vault_merchant_controls_info: &AccountInfo,
+    current_timestamp: u64,
+) -> Result<u64> {
+    // Vaults without merchant controls have no account at the PDA
+    if vault_merchant_controls_info.data_is_empty() {
+        return Ok(spend_hold.release(vault, None, current_timestamp));
+    }
+
+    check!(
+        vault_merchant_controls_info.owner.eq(&crate::id()),
+        PyraError::InvalidMerchantControls
+    );
+    let mut vault_merchant_controls = VaultMerchantControls::try_deserialize(
+        &mut &vault_merchant_controls_info.try_borrow_data()?[..],
+    )?;
+    let spend_limit_restored =
+        spend_hold.release(vault, Some(&mut vault_merchant_controls), current_timestamp);
+    vault_merchant_controls
+        .try_serialize(&mut &mut vault_merchant_controls_info.try_borrow_mut_data()?[..])?;
+
+    Ok(spend_limit_restored)
+}
+
+/// Returns the next reset timestamp if the timeframe has elapsed, or None if it's still in the future.
+/// New reset timestamp = old reset timestamp + the amount of timeframes required to reach a timestamp in the future,
+/// or the start of the next calendar period for calendar spend limits
+fn get_rolled_over_reset_timestamp(
+    next_timeframe_reset_timestamp: u64,
+    timeframe_in_seconds: u64,
+    current_timestamp: u64,
+    calendar_period: Option<CalendarPeriod>,
+    utc_offset_seconds: i32,
+) -> Result<Option<u64>> {
+    if current_timestamp <= next_timeframe_reset_timestamp {
+        return Ok(None);
+    }
+
+    // Calendar months vary in length, so calendar resets realign to the next period instead of adding timeframes
+    if let Some(calendar_period) = calendar_period {
+        let calendar_reset_timestamp = get_next_calendar_reset_timestamp(
+            current_timestamp,
+            calendar_period,
+            utc_offset_seconds,
+        )?;
+        return Ok(Some(calendar_reset_timestamp));
+    }
+
+    let overflow = current_timestamp
+        .checked_sub(next_timeframe_reset_timestamp)
+        .ok_or(PyraError::MathOverflow)?;
+
+    // Callers ensure the timeframe isn't 0
+    let overflow_in_timeframes = overflow
+        .checked_div(timeframe_in_seconds)
+        .ok_or(PyraError::MathOverflow)?;
+
+    let seconds_to_add = overflow_in_timeframes
+        .checked_add(1) // Bring the next reset into the future
+        .ok_or(PyraError::MathOverflow)?
+        .checked_mul(timeframe_in_seconds)
+        .ok_or(PyraError::MathOverflow)?;
+
+    let rolled_over_reset_timestamp = next_timeframe_reset_timestamp
+        .checked_add(seconds_to_add)
+        .ok_or(PyraError::MathOverflow)?;
+
+    Ok(Some(rolled_over_reset_timestamp))
+}
@@ -0,0 +1,117 @@
+use crate::{check, config::PyraError, events::SweptTokenAccount};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+/// Sweeps token accounts held by the deposit address or vault to the owner and closes them, passed in groups of
+/// [token_account, mint, token_program, owner_token_account]. Returns the swept accounts, and the mints whose deposit
+/// address ATA was passed (including ATAs that were never created).
+pub fn sweep_token_accounts<'info>(
+    token_account_groups: &'info [AccountInfo<'info>],
+    owner: &AccountInfo<'info>,
+    deposit_address: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    signer_seeds_deposit_address: &[&[&[u8]]],
+    signer_seeds_vault: &[&[&[u8]]],
+) -> Result<(Vec<SweptTokenAccount>, Vec<Pubkey>)> {
+    let groups = token_account_groups.chunks_exact(4);
+    check!(
+        groups.remainder().is_empty(),
+        PyraError::InvalidSweepTokenAccount
+    );
+
+    let mut token_accounts_swept = Vec::with_capacity(groups.len());
+    let mut deposit_address_mints_swept = Vec::with_capacity(groups.len());
+
+    for accounts in groups {
+        let token_program = Interface::<TokenInterface>::try_from(&accounts[2])?;
+        let mint = InterfaceAccount::<Mint>::try_from(&accounts[1])?;
+        check!(
+            accounts[1].owner.eq(&token_program.key()),
+            PyraError::InvalidMint
+        );
+
+        let deposit_address_ata = get_associated_token_address_with_program_id(
+            &deposit_address.key(),
+            &mint.key(),
+            &token_program.key(),
+        );
+        let is_deposit_address_ata = accounts[0].key().eq(&deposit_address_ata);
+        if is_deposit_address_ata {
+            deposit_address_mints_swept.push(mint.key());
+        }
+
+        // Deposit address ATAs that were never created have nothing to sweep
+        if accounts[0].data_is_empty() {
+            check!(is_deposit_address_ata, PyraError::InvalidSweepTokenAccount);
+            continue;
+        }
+
+        check!(
+            accounts[0].owner.eq(&token_program.key()),
+            PyraError::InvalidSweepTokenAccount
+        );
+
+        let token_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[0])?;
+        check!(token_account.mint.eq(&mint.key()), PyraError::InvalidMint);
+
+        let owner_token_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[3])?;
+        check!(
+            owner_token_account.owner.eq(&owner.key()) && owner_token_account.mint.eq(&mint.key()),
+            PyraError::InvalidSweepTokenAccount
+        );
+
+        // Deposit address ATAs (supported or not) and any leftover mules, which are held by the vault wherever they
+        // were seeded
+        let (authority, signer_seeds) = if token_account.owner.eq(&deposit_address.key()) {
+            (deposit_address.clone(), signer_seeds_deposit_address)
+        } else if token_account.owner.eq(&vault.key()) {
+            (vault.clone(), signer_seeds_vault)
+        } else {
+            return Err(PyraError::InvalidSweepTokenAccount.into());
+        };
+
+        let amount_base_units = token_account.amount;
+        if amount_base_units > 0 {
+            transfer_checked(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    TransferChecked {
+                        from: token_account.to_account_info(),
+                        to: owner_token_account.to_account_info(),
+                        authority: authority.clone(),
+                        mint: mint.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount_base_units,
+                mint.decimals,
+            )?;
+        }
+
+        let lamports_reclaimed = token_account.to_account_info().lamports();
+        close_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            CloseAccount {
+                account: token_account.to_account_info(),
+                destination: owner.clone(),
+                authority,
+            },
+            signer_seeds,
+        ))?;
+
+        token_accounts_swept.push(SweptTokenAccount {
+            token_account: token_account.key(),
+            mint: mint.key(),
+            amount_base_units,
+            lamports_reclaimed,
+        });
+    }
+
+    Ok((token_accounts_swept, deposit_address_mints_swept))
+}